@@ -5,11 +5,68 @@
 
 async function run() {
     await wasm_bindgen('./rust-web-roguelike_bg.wasm');
-    //initButtons();
+    requestAnimationFrame(updateButtons);
   }
 
   run();
 
-// const initButtons = () => {
-//     //it used to contain click listeners
-//     }
\ No newline at end of file
+//the buttons come from the game, which knows what makes sense on the current screen
+let shown = "";
+
+const updateButtons = () => {
+    const state = wasm_bindgen.web_ui_state();
+    //only redraw when the game offers something different
+    if (state !== shown) {
+        shown = state;
+        renderButtons(JSON.parse(state));
+    }
+    requestAnimationFrame(updateButtons);
+}
+
+const makeButton = (button, label) => {
+    const input = document.createElement("input");
+    input.type = "button";
+    input.value = label;
+    input.addEventListener("click", () => wasm_bindgen.press_web_button(button.id));
+    return input;
+}
+
+const renderButtons = (ui) => {
+    const keypad = document.getElementById("keypad");
+    keypad.innerHTML = "";
+
+    if (ui.title) {
+        const title = document.createElement("div");
+        title.className = "title";
+        title.textContent = ui.title;
+        keypad.appendChild(title);
+    }
+
+    //movement, three by three
+    if (ui.keypad && ui.keypad.length > 0) {
+        const table = document.createElement("table");
+        for (let row = 0; row < ui.keypad.length; row += 3) {
+            const tr = document.createElement("tr");
+            ui.keypad.slice(row, row + 3).forEach(button => {
+                const td = document.createElement("td");
+                td.appendChild(makeButton(button, button.label));
+                tr.appendChild(td);
+            });
+            table.appendChild(tr);
+        }
+        keypad.appendChild(table);
+    }
+
+    const actions = document.createElement("div");
+    (ui.actions || []).forEach(button => actions.appendChild(makeButton(button, button.label)));
+    keypad.appendChild(actions);
+
+    //menu rows, one per line
+    const options = document.createElement("div");
+    options.className = "options";
+    (ui.options || []).forEach(button => {
+        options.appendChild(makeButton(button, "(" + button.id + ") " + button.label));
+        options.appendChild(document.createElement("br"));
+    });
+    keypad.appendChild(options);
+}
@@ -1,6 +1,6 @@
 extern crate specs;
 use specs::prelude::*;
-use crate::{EquipmentChanged, Item, InBackpack, Equipped, Pools, Attributes, gamelog::GameLog};
+use crate::{EquipmentChanged, Item, InBackpack, Equipped, Pools, Attributes, Stackable, gamelog::{GameLog, LogCategory}, carry_capacity_lbs};
 use std::collections::HashMap;
 
 pub struct EncumbranceSystem {}
@@ -16,12 +16,13 @@ impl<'a> System<'a> for EncumbranceSystem {
         WriteStorage<'a, Pools>,
         ReadStorage<'a, Attributes>,
         ReadExpect<'a, Entity>,
-        WriteExpect<'a, GameLog>
+        WriteExpect<'a, GameLog>,
+        ReadStorage<'a, Stackable>
     );
 
     fn run(&mut self, data : Self::SystemData) {
         let (mut equip_dirty, entities, items, backpacks, wielded, 
-            mut pools, attributes, player, mut gamelog) = data;
+            mut pools, attributes, player, mut gamelog, stacks) = data;
 
         if equip_dirty.is_empty() { return; }
 
@@ -35,18 +36,19 @@ impl<'a> System<'a> for EncumbranceSystem {
         equip_dirty.clear();
 
         // Total up equipped items
-        for (item, equipped) in (&items, &wielded).join() {
+        //stacks weigh as much as everything in them
+        for (item, equipped, stack) in (&items, &wielded, stacks.maybe()).join() {
             if to_update.contains_key(&equipped.owner) {
                 let totals = to_update.get_mut(&equipped.owner).unwrap();
-                *totals += item.weight_lbs;
+                *totals += item.weight_lbs * stack.map_or(1, |s| s.quantity) as f32;
             }
         }
 
         // Total up carried items
-        for (item, carried) in (&items, &backpacks).join() {
+        for (item, carried, stack) in (&items, &backpacks, stacks.maybe()).join() {
             if to_update.contains_key(&carried.owner) {
                 let totals = to_update.get_mut(&carried.owner).unwrap();
-                *totals += item.weight_lbs;
+                *totals += item.weight_lbs * stack.map_or(1, |s| s.quantity) as f32;
             }
         }
 
@@ -56,11 +58,11 @@ impl<'a> System<'a> for EncumbranceSystem {
                 pool.total_weight = *weight;
 
                 if let Some(attr) = attributes.get(*entity) {
-                    let carry_capacity_lbs = (attr.strength.base + attr.strength.modifiers) * 15;
+                    let carry_capacity_lbs = carry_capacity_lbs(attr);
                     if pool.total_weight as i32 > carry_capacity_lbs {
                         // Overburdened
                         if *entity == *player {
-                            gamelog.entries.push("You are overburdened.".to_string());
+                            gamelog.add(LogCategory::System, "You are overburdened.");
                         }
                     }
                 }
@@ -1,6 +1,6 @@
 extern crate specs;
 use specs::prelude::*;
-use crate::{Initiative, Position, MyTurn, Attributes, RunState};
+use crate::{Initiative, Position, MyTurn, Attributes, RunState, gamelog::GameLog, roll_initiative};
 
 pub struct InitiativeSystem {}
 
@@ -14,12 +14,13 @@ impl<'a> System<'a> for InitiativeSystem {
                         ReadStorage<'a, Attributes>,
                         WriteExpect<'a, RunState>,
                         ReadExpect<'a, Entity>,
-                        ReadExpect<'a, rltk::Point>
+                        ReadExpect<'a, rltk::Point>,
+                        WriteExpect<'a, GameLog>
                     );
 
     fn run(&mut self, data : Self::SystemData) {
         let (mut initiatives, positions, mut turns, entities, mut rng, attributes, 
-            mut runstate, player, player_pos) = data;
+            mut runstate, player, player_pos, mut log) = data;
 
         if *runstate != RunState::Ticking { return; } // We'll be adding Ticking in a moment; use MonsterTurn if you want to test in the meantime
 
@@ -32,19 +33,15 @@ impl<'a> System<'a> for InitiativeSystem {
             if initiative.current < 1 {
                 let mut myturn = true;
 
-                // Re-roll
-                initiative.current = 6 + rng.roll_dice(1, 6);
-
-                // Give a bonus for DEX
-                if let Some(attr) = attributes.get(entity) {
-                    initiative.current -= attr.dexterity.bonus;
-                }
+                // Re-roll, with a bonus for DEX
+                initiative.current = roll_initiative(&mut rng, attributes.get(entity));
 
                 // TODO: More initiative granting boosts/penalties will go here later
 
                 // If its the player, we want to go to an AwaitingInput state
                 if entity == *player {
                     *runstate = RunState::AwaitingInput;
+                    log.turn += 1;
                 } else {
                     // don't give turns to NPCs far away
                     let distance = rltk::DistanceAlg::Pythagoras.distance2d(*player_pos, rltk::Point::new(pos.x, pos.y));
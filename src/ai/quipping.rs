@@ -1,6 +1,6 @@
 extern crate specs;
 use specs::prelude::*;
-use crate::{gamelog::GameLog, Quips, Name, MyTurn, Viewshed};
+use crate::{gamelog::{GameLog, LogCategory, LogEntry}, Quips, Name, MyTurn, Viewshed};
 
 pub struct QuipSystem {}
 
@@ -23,7 +23,7 @@ impl<'a> System<'a> for QuipSystem {
                     if quip.available.len() == 1 { 0 } 
                     else { (rng.roll_dice(1, quip.available.len() as i32)-1) as usize };
                 
-                gamelog.entries.push(format!("{} says \"{}\"", name.name, quip.available[quip_index]));
+                gamelog.push(LogEntry::new(LogCategory::Dialogue).npc_name(&name.name).append(format!(" says \"{}\"", quip.available[quip_index])));
                 quip.available.remove(quip_index);
             }                
         }
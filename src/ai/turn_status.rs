@@ -1,6 +1,6 @@
 extern crate specs;
 use specs::prelude::*;
-use crate::{MyTurn, Confusion, RunState};
+use crate::{MyTurn, Confusion, Immobilized, RunState};
 
 pub struct TurnStatusSystem {}
 
@@ -8,13 +8,16 @@ impl<'a> System<'a> for TurnStatusSystem {
     #[allow(clippy::type_complexity)]
     type SystemData = ( WriteStorage<'a, MyTurn>,
                         WriteStorage<'a, Confusion>,
+                        WriteStorage<'a, Immobilized>,
                         Entities<'a>,
-                        ReadExpect<'a, RunState>);
+                        ReadExpect<'a, RunState>,
+                        ReadExpect<'a, Entity>);
 
     fn run(&mut self, data : Self::SystemData) {
-        let (mut turns, mut confusion, entities, runstate) = data;
+        let (mut turns, mut confusion, mut immobilized, entities, runstate, player) = data;
 
-        if *runstate != RunState::Ticking { return; }
+        //initiative has just handed the player their turn if it's AwaitingInput; statuses count down then too
+        if *runstate != RunState::Ticking && *runstate != RunState::AwaitingInput { return; }
 
         let mut not_my_turn : Vec<Entity> = Vec::new();
         let mut not_confused : Vec<Entity> = Vec::new();
@@ -22,7 +25,18 @@ impl<'a> System<'a> for TurnStatusSystem {
             confused.turns -= 1;
             if confused.turns < 1 {
                 not_confused.push(entity);
-            } else {
+            } else if entity != *player {
+                not_my_turn.push(entity);
+            }
+        }
+
+        //netted entities lose their turn, same as confused ones; the player keeps theirs, and player.rs decides what a netted or confused player gets to do with it
+        let mut not_immobilized : Vec<Entity> = Vec::new();
+        for (entity, _turn, netted) in (&entities, &mut turns, &mut immobilized).join() {
+            netted.turns -= 1;
+            if netted.turns < 1 {
+                not_immobilized.push(entity);
+            } else if entity != *player {
                 not_my_turn.push(entity);
             }
         }
@@ -34,5 +48,9 @@ impl<'a> System<'a> for TurnStatusSystem {
         for e in not_confused {
             confusion.remove(e);
         }
+
+        for e in not_immobilized {
+            immobilized.remove(e);
+        }
     }
-}
\ No newline at end of file
+}
@@ -1,23 +1,22 @@
 use specs::prelude::*;
-use super::{Map,TileType,Position,Renderable, Hidden};
+use super::{Map,TileType,tile_properties,Position,Renderable, Hidden, Vendor, map_builders::MapSnapshot, layout::layout};
 use rltk::{Point, Rltk, Console, RGB};
 
 const SHOW_BOUNDARIES : bool = true;
 
 pub fn get_screen_bounds(ecs: &World, ctx : &mut Rltk) -> (i32, i32, i32, i32) {
     let player_pos = ecs.fetch::<Point>();
-    //RLTK Console dimensions
-    //let (x_chars, y_chars) = ctx.get_char_size();
-    let (x_chars, y_chars) = (48, 44);
+    //the map view, not the whole console
+    let (x_chars, y_chars) = layout(ctx).view_size();
 
      //center the camera
-    let center_x = (x_chars / 2) as i32;
-    let center_y = (y_chars / 2) as i32;
+    let center_x = x_chars / 2;
+    let center_y = y_chars / 2;
 
     let min_x = player_pos.x - center_x;
-    let max_x = min_x + x_chars as i32;
+    let max_x = min_x + x_chars;
     let min_y = player_pos.y - center_y;
-    let max_y = min_y + y_chars as i32;
+    let max_y = min_y + y_chars;
 
     (min_x, max_x, min_y, max_y)
 }
@@ -64,25 +63,99 @@ pub fn render_camera(ecs: &World, ctx : &mut Rltk) {
         if map.visible_tiles[idx] { 
             let entity_screen_x = pos.x - min_x;
             let entity_screen_y = pos.y - min_y;
-            //clipping
-            if entity_screen_x > 0 && entity_screen_x < map_width && entity_screen_y > 0 && entity_screen_y < map_height {
+            //clipping, to the view so nothing lands on the panels
+            if entity_screen_x > 0 && entity_screen_x < max_x - min_x && entity_screen_y > 0 && entity_screen_y < max_y - min_y {
                 ctx.set(entity_screen_x, entity_screen_y, render.fg, render.bg, render.glyph);
             }
         }
     }
 }
 
-pub fn render_debug_map(map : &Map, ctx : &mut Rltk) {
-    //locked to middle of screen
-    let player_pos = Point::new(map.width / 2, map.height / 2);
-    let (x_chars, y_chars) = ctx.get_char_size();
+//things the overview marks over the terrain, by map index
+fn overview_marks(ecs : &World, map : &Map) -> Vec<(usize, u8, RGB)> {
+    let player_pos = ecs.fetch::<Point>();
+    let positions = ecs.read_storage::<Position>();
+    let vendors = ecs.read_storage::<Vendor>();
 
-    let center_x = (x_chars / 2) as i32;
-    let center_y = (y_chars / 2) as i32;
+    let mut marks = Vec::new();
+    //most important last, so it wins when several share a cell
+    for (idx, tile) in map.tiles.iter().enumerate() {
+        if !map.revealed_tiles[idx] { continue; }
+        match tile {
+            TileType::DownStairs => marks.push((idx, rltk::to_cp437('>'), RGB::named(rltk::MAGENTA))),
+            TileType::UpStairs => marks.push((idx, rltk::to_cp437('<'), RGB::named(rltk::MAGENTA))),
+            _ => {}
+        }
+    }
+    for (pos, _vendor) in (&positions, &vendors).join() {
+        let idx = map.xy_idx(pos.x, pos.y);
+        if map.revealed_tiles[idx] {
+            marks.push((idx, rltk::to_cp437('$'), RGB::named(rltk::GOLD)));
+        }
+    }
+    marks.push((map.xy_idx(player_pos.x, player_pos.y), rltk::to_cp437('@'), RGB::named(rltk::YELLOW)));
+    marks
+}
 
-    let min_x = player_pos.x - center_x;
+/// The revealed map shrunk down, each cell standing for a scale x scale block of tiles;
+/// (map_x, map_y) is the tile at the top left
+pub fn render_overview(ecs : &World, ctx : &mut Rltk, screen : (i32, i32, i32, i32), map_x : i32, map_y : i32, scale : i32) {
+    let map = ecs.fetch::<Map>();
+    let (screen_x, screen_y, width, height) = screen;
+
+    //what's seen of the terrain, one cell at a time
+    for cy in 0 .. height {
+        for cx in 0 .. width {
+            let (x0, y0) = (map_x + cx * scale, map_y + cy * scale);
+            //the commonest revealed tile in the block stands for all of it
+            let mut counts : Vec<(TileType, i32)> = Vec::new();
+            for y in y0 .. y0 + scale {
+                for x in x0 .. x0 + scale {
+                    if x < 0 || x >= map.width || y < 0 || y >= map.height { continue; }
+                    let idx = map.xy_idx(x, y);
+                    if !map.revealed_tiles[idx] { continue; }
+                    match counts.iter_mut().find(|(tile, _)| *tile == map.tiles[idx]) {
+                        Some(count) => count.1 += 1,
+                        None => counts.push((map.tiles[idx], 1))
+                    }
+                }
+            }
+            if let Some((tile, _)) = counts.iter().max_by_key(|(_, n)| *n) {
+                if scale == 1 {
+                    let (glyph, fg, bg) = get_tile_glyph(map.xy_idx(x0, y0), &*map);
+                    ctx.set(screen_x + cx, screen_y + cy, fg, bg, glyph);
+                } else {
+                    let fg = tile_properties(*tile).fg;
+                    ctx.set(screen_x + cx, screen_y + cy, fg, fg * 0.5, rltk::to_cp437(' '));
+                }
+            }
+        }
+    }
+
+    for (idx, glyph, fg) in overview_marks(ecs, &*map).iter() {
+        let (x, y) = map.idx_xy(*idx);
+        let (cx, cy) = ((x - map_x).div_euclid(scale), (y - map_y).div_euclid(scale));
+        if cx >= 0 && cx < width && cy >= 0 && cy < height {
+            ctx.set(screen_x + cx, screen_y + cy, *fg, RGB::named(rltk::BLACK), *glyph);
+        }
+    }
+}
+
+/// How much to shrink the map so all of it fits in width x height
+pub fn overview_scale(map : &Map, width : i32, height : i32) -> i32 {
+    i32::max(1, i32::max((map.width + width - 1) / width, (map.height + height - 1) / height))
+}
+
+//the debug view is locked to the middle of the map
+fn debug_origin(map : &Map, ctx : &mut Rltk) -> (i32, i32) {
+    let (x_chars, y_chars) = ctx.get_char_size();
+    (map.width / 2 - (x_chars / 2) as i32, map.height / 2 - (y_chars / 2) as i32)
+}
+
+pub fn render_debug_map(map : &Map, ctx : &mut Rltk) {
+    let (x_chars, y_chars) = ctx.get_char_size();
+    let (min_x, min_y) = debug_origin(map, ctx);
     let max_x = min_x + x_chars as i32;
-    let min_y = player_pos.y - center_y;
     let max_y = min_y + y_chars as i32;
 
     let map_width = map.width-1;
@@ -109,45 +182,84 @@ pub fn render_debug_map(map : &Map, ctx : &mut Rltk) {
     //no entities rendered
 }
 
-fn get_tile_glyph(idx: usize, map : &Map) -> (u8, RGB, RGB) {
-    let glyph;
-    let mut fg;
-    let mut bg = RGB::from_f32(0., 0., 0.);
+/// Which of the builder's working data the visualizer draws over the map
+#[derive(Clone, Copy, Default)]
+pub struct DebugOverlays {
+    pub rooms : bool,
+    pub corridors : bool,
+    pub submaps : bool,
+    pub spawns : bool
+}
 
-    // Render a tile depending upon the tile type
-    match map.tiles[idx] {
-        TileType::Floor => {
-            glyph = rltk::to_cp437('.');
-            //fg = RGB::named(rltk::CHOCOLATE);
-            //fg = RGB::named(rltk::BROWN4); //too red
-            //fg = RGB::named(rltk::SANDY_BROWN); //fits sand but not earth
-            //fg = RGB::named(rltk::X11_GRAY);
-            //fg = RGB::named(rltk::LIGHT_GREY);
-            //fg = RGB::from_f32(0.6, 0.6, 0.6); //nice gray
-            //fg = RGB::from_f32(0.5, 0.43, 0.41);
-            fg = RGB::from_f32(0.5, 0.47, 0.45);
+pub fn render_debug_overlays(snapshot : &MapSnapshot, overlays : &DebugOverlays, ctx : &mut Rltk) {
+    let (x_chars, y_chars) = ctx.get_char_size();
+    let (min_x, min_y) = debug_origin(&snapshot.map, ctx);
+    let on_screen = |x : i32, y : i32| -> Option<(i32, i32)> {
+        let (sx, sy) = (x - min_x, y - min_y);
+        if sx >= 0 && sx < x_chars as i32 && sy >= 0 && sy < y_chars as i32 { Some((sx, sy)) } else { None }
+    };
+
+    if overlays.submaps {
+        if let Some(submaps) = &snapshot.submaps {
+            for rect in submaps.iter() {
+                for y in rect.y1 ..= rect.y2 {
+                    for x in rect.x1 ..= rect.x2 {
+                        if let Some((sx, sy)) = on_screen(x, y) { ctx.set_bg(sx, sy, RGB::named(rltk::DARK_GREEN)); }
+                    }
+                }
+            }
+        }
+    }
+    if overlays.rooms {
+        if let Some(rooms) = &snapshot.rooms {
+            for rect in rooms.iter() {
+                for y in rect.y1 ..= rect.y2 {
+                    for x in rect.x1 ..= rect.x2 {
+                        //outline only, so the room itself stays readable
+                        if x == rect.x1 || x == rect.x2 || y == rect.y1 || y == rect.y2 {
+                            if let Some((sx, sy)) = on_screen(x, y) { ctx.set_bg(sx, sy, RGB::named(rltk::DARK_BLUE)); }
+                        }
+                    }
+                }
+            }
         }
-        TileType::FloorIndoor => {
-            glyph = rltk::to_cp437('.');
-            fg = RGB::from_f32(0.0, 0.5, 0.5);       
+    }
+    if overlays.corridors {
+        if let Some(corridors) = &snapshot.corridors {
+            for corridor in corridors.iter() {
+                for idx in corridor.iter() {
+                    let (x, y) = (*idx as i32 % snapshot.map.width, *idx as i32 / snapshot.map.width);
+                    if let Some((sx, sy)) = on_screen(x, y) { ctx.set_bg(sx, sy, RGB::named(rltk::DARK_RED)); }
+                }
+            }
+        }
+    }
+    if overlays.spawns {
+        for (idx, name) in snapshot.spawns.iter() {
+            let (x, y) = (*idx as i32 % snapshot.map.width, *idx as i32 / snapshot.map.width);
+            if let Some((sx, sy)) = on_screen(x, y) {
+                let glyph = name.chars().next().map_or(rltk::to_cp437('?'), rltk::to_cp437);
+                ctx.set(sx, sy, RGB::named(rltk::MAGENTA), RGB::named(rltk::BLACK), glyph);
+            }
         }
+    }
+}
+
+fn get_tile_glyph(idx: usize, map : &Map) -> (u8, RGB, RGB) {
+    let mut bg = RGB::from_f32(0., 0., 0.);
+
+    // Render a tile depending upon the tile type; looks come from data/tiles.json
+    let tile = tile_properties(map.tiles[idx]);
+    let mut fg = tile.fg;
+    let glyph = match map.tiles[idx] {
+        //walls join up with their neighbours
         TileType::Wall => {
             let x = idx as i32 % map.width;
             let y = idx as i32 / map.width;
-            glyph = wall_glyph(&*map, x, y);
-            fg = RGB::named(rltk::LIGHT_GREY);
-            //fg = RGB::from_f32(1.0, 1.0, 1.0);
+            wall_glyph(&*map, x, y)
         }
-        TileType::Tree => {
-            glyph = rltk::to_cp437('♣'); 
-            fg = RGB::from_f32(0.0, 0.6, 0.0);
-        }
-        TileType::DownStairs => {
-            glyph = rltk::to_cp437('>');
-            fg = RGB::named(rltk::LIGHT_GREY);
-            //fg = RGB::from_f32(1.0, 1.0, 1.0);
-        }
-    }
+        _ => tile.glyph
+    };
     //grayscale out of FOV
     if !map.visible_tiles[idx] { fg = fg.to_greyscale() }
     else { 
@@ -162,11 +274,14 @@ fn get_tile_glyph(idx: usize, map : &Map) -> (u8, RGB, RGB) {
 //the tutorial checked for revealed also, but that led to pillars mysteriously transforming
 fn is_wall(map: &Map, x: i32, y: i32) -> bool {
     let idx = map.xy_idx(x, y);
-    map.tiles[idx] == TileType::Wall //&& map.revealed_tiles[idx]
+    //windows sit in a wall, so the wall lines carry on through them
+    map.tiles[idx] == TileType::Wall || map.tiles[idx] == TileType::Window //&& map.revealed_tiles[idx]
 }
 
 fn wall_glyph(map : &Map, x: i32, y:i32) -> u8 {
-    if x < 1 || x > map.width-2 || y < 1 || y > map.height-2 as i32 { return 35; }
+    //anything the bitmask can't place just uses the wall glyph from tiles.json
+    let plain = tile_properties(TileType::Wall).glyph;
+    if x < 1 || x > map.width-2 || y < 1 || y > map.height-2 as i32 { return plain; }
     let mut mask : u8 = 0;
 
     
@@ -192,6 +307,6 @@ fn wall_glyph(map : &Map, x: i32, y:i32) -> u8 {
         12 => { 205 } // Wall to the east and west
         13 => { 202 } // Wall to the east, west, and south
         14 => { 203 } // Wall to the east, west, and north
-        _ => { 35 } // We missed one?
+        _ => { plain } // We missed one?
     }
 }
\ No newline at end of file
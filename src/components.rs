@@ -8,7 +8,7 @@ pub struct Position {
     pub y: i32,
 }
 
-#[derive(Component)]
+#[derive(Component, Clone)]
 pub struct Renderable {
     pub glyph: u8,
     pub fg: RGB,
@@ -35,7 +35,7 @@ pub struct Vendor {
     pub categories : Vec<String>
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone)]
 pub struct Name {
     pub name : String
 }
@@ -88,24 +88,18 @@ pub struct WantsToMelee {
     pub target : Entity
 }
 
-#[derive(Component, Debug)]
-pub struct SufferDamage {
-    pub amount : i32,
-    pub from_player: bool //some things treat player special
-}
-
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone)]
 pub struct Item {
     pub weight_lbs : f32,
     pub base_value : f32,
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone)]
 pub struct MedItem {
     pub heal_amount : i32
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone)]
 pub struct InBackpack {
     pub owner : Entity
 }
@@ -113,25 +107,45 @@ pub struct InBackpack {
 #[derive(Component, Debug, Clone)]
 pub struct EquipmentChanged {}
 
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone)]
 pub struct Consumable {} //in the sense that it's limited use-only
 
-#[derive(Component, Debug)]
+//a gun or the like: each use spends a charge, and an empty one reloads from the ammo item
+#[derive(Component, Debug, Clone)]
+pub struct Charges {
+    pub current : i32,
+    pub max : i32,
+    pub ammo : String
+}
+
+#[derive(Component, Debug, Clone)]
 pub struct Ranged {
     pub range : i32
 }
 
-#[derive(Component, Debug)]
+#[derive(Component, Debug, Clone)]
 pub struct InflictsDamage {
     pub damage : i32
 }
 
-#[derive(Component, Debug)]
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum AoEShape { Circle, Cone, Line }
+
+#[derive(Component, Debug, Clone)]
 pub struct AreaOfEffect {
-    pub radius : i32
+    pub radius : i32,
+    pub shape : AoEShape
 }
 
-#[derive(Component, Debug)]
+//damage drops the further a target is from the blast centre
+#[derive(Component, Debug, Clone)]
+pub struct DamageFalloff {}
+
+//area effect that doesn't hurt its user or the user's faction
+#[derive(Component, Debug, Clone)]
+pub struct SparesAllies {}
+
+#[derive(Component, Debug, Clone)]
 pub struct Confusion {
     pub turns : i32
 }
@@ -180,6 +194,36 @@ pub struct EntryTrigger {}
 #[derive(Component, Debug, Clone)]
 pub struct SingleActivation {}
 
+//trap effects beyond damage
+#[derive(Component, Debug, Clone)]
+pub struct Teleports {
+    pub range : i32
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct Alarm {
+    pub faction : String
+}
+
+#[derive(Component, Debug, Clone)]
+pub struct SpawnsEntity {
+    pub name : String,
+    //set up by the player rather than summoned, so it shouldn't start out hidden
+    pub deploy : bool
+}
+
+//a trap set up by someone, who gets the credit for whatever it does
+#[derive(Component, Debug, Clone)]
+pub struct DeployedBy {
+    pub owner : Entity
+}
+
+//the status and the thing that causes it share a component, same as Confusion
+#[derive(Component, Debug, Clone)]
+pub struct Immobilized {
+    pub turns : i32
+}
+
 #[derive(Component, Debug, Clone)]
 pub struct BlocksVisibility {}
 
@@ -256,6 +300,29 @@ pub struct WantsToDropItem {
     pub item : Entity
 }
 
+#[derive(Component, Debug)]
+pub struct WantsToThrowItem {
+    pub item : Entity,
+    pub target : rltk::Point
+}
+
+//several identical items carried as one entity
+#[derive(Component, Debug, Clone)]
+pub struct Stackable {
+    pub quantity : i32
+}
+
+//breaks instead of landing when thrown
+#[derive(Component, Debug, Clone)]
+pub struct Fragile {}
+
+//a door that won't open until someone with a lockpick comes along
+#[derive(Component, Debug, Clone)]
+pub struct Locked {}
+
+#[derive(Component, Debug, Clone)]
+pub struct Lockpick {}
+
 #[derive(Component, Debug)]
 pub struct WantsToRemoveItem {
     pub item : Entity
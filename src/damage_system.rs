@@ -1,41 +1,10 @@
 extern crate specs;
 use specs::prelude::*;
-use super::{Pools, SufferDamage, Player, Name, gamelog::GameLog, RunState,
+use super::{Pools, Player, Name, gamelog::{GameLog, LogCategory, LogEntry}, RunState,
 Position, Equipped, InBackpack};
 //console is RLTK's wrapper around either println or the web console macro
 use rltk::{console};
 
-pub struct DamageSystem {}
-
-impl<'a> System<'a> for DamageSystem {
-    type SystemData = ( WriteStorage<'a, Pools>,
-                        WriteStorage<'a, SufferDamage>,
-                        ReadExpect<'a, Entity>,
-                    );
-
-    fn run(&mut self, data : Self::SystemData) {
-        let (mut pools, mut damage, player) = data;
-        let mut money_gain = 0.0f32;
-
-        for (mut pool, damage) in (&mut pools, &damage).join() {
-            pool.hit_points.current -= damage.amount;
-
-            // if player, gain money
-            if pool.hit_points.current < 1 && damage.from_player {
-                money_gain += pool.money;
-            }
-        }
-
-        //effectively auto-pickup money
-        if money_gain != 0.0 {
-            let mut player_stats = pools.get_mut(*player).unwrap();
-            player_stats.money += money_gain;
-        }
-
-        damage.clear();
-    }
-}
-
 pub fn delete_the_dead(ecs : &mut World) {
     let mut dead : Vec<Entity> = Vec::new();
     // Using a scope to make the borrow checker happy
@@ -52,14 +21,14 @@ pub fn delete_the_dead(ecs : &mut World) {
                     None => {
                         let victim_name = names.get(entity);
                         if let Some(victim_name) = victim_name {
-                            log.entries.push(format!("{} is dead", &victim_name.name));
+                            log.push(LogEntry::new(LogCategory::Combat).npc_name(&victim_name.name).append(" is dead"));
                         }
                         dead.push(entity);
                     },
                     Some(_) => {
                         let mut runstate = ecs.write_resource::<RunState>();
                         *runstate = RunState::GameOver;
-                        //log.entries.push("You are dead".to_string())
+                        //log.add(LogCategory::Combat, "You are dead")
                     }
                 }
             }
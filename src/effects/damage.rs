@@ -0,0 +1,41 @@
+use specs::prelude::*;
+use super::*;
+use crate::components::Pools;
+
+//the one way anything loses hit points - melee, items and traps all come through here
+pub fn inflict_damage(ecs : &mut World, damage : &EffectSpawner, target : Entity) {
+    if let EffectType::Damage{ amount } = damage.effect_type {
+        let player_entity = *ecs.fetch::<Entity>();
+        let mut pools = ecs.write_storage::<Pools>();
+        let mut money_gain = 0.0f32;
+        if let Some(pool) = pools.get_mut(target) {
+            pool.hit_points.current -= amount;
+            // if player, gain money
+            if pool.hit_points.current < 1 && damage.creator == Some(player_entity) {
+                money_gain += pool.money;
+            }
+            add_effect(None,
+                EffectType::Particle{ glyph: rltk::to_cp437('‼'), fg: rltk::RGB::named(rltk::RED), bg: rltk::RGB::named(rltk::BLACK), lifespan: 200.0 },
+                Targets::Single{ target });
+        }
+
+        //effectively auto-pickup money
+        if money_gain != 0.0 {
+            if let Some(player_stats) = pools.get_mut(player_entity) {
+                player_stats.money += money_gain;
+            }
+        }
+    }
+}
+
+pub fn heal_damage(ecs : &mut World, heal : &EffectSpawner, target : Entity) {
+    if let EffectType::Healing{ amount } = heal.effect_type {
+        let mut pools = ecs.write_storage::<Pools>();
+        if let Some(pool) = pools.get_mut(target) {
+            pool.hit_points.current = i32::min(pool.hit_points.max, pool.hit_points.current + amount);
+            add_effect(None,
+                EffectType::Particle{ glyph: rltk::to_cp437('♥'), fg: rltk::RGB::named(rltk::GREEN), bg: rltk::RGB::named(rltk::BLACK), lifespan: 200.0 },
+                Targets::Single{ target });
+        }
+    }
+}
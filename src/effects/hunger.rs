@@ -0,0 +1,15 @@
+use specs::prelude::*;
+use super::*;
+use crate::components::Pools;
+
+pub fn feed(ecs : &mut World, _food : &EffectSpawner, target : Entity) {
+    if let Some(pool) = ecs.write_storage::<Pools>().get_mut(target) {
+        pool.hunger += 150;
+    }
+}
+
+pub fn quench(ecs : &mut World, _drink : &EffectSpawner, target : Entity) {
+    if let Some(pool) = ecs.write_storage::<Pools>().get_mut(target) {
+        pool.thirst += 250;
+    }
+}
@@ -0,0 +1,130 @@
+use std::sync::Mutex;
+use specs::prelude::*;
+use std::collections::VecDeque;
+use rltk::RGB;
+use super::Map;
+
+mod targeting;
+pub use targeting::*;
+mod triggers;
+mod damage;
+mod hunger;
+mod status;
+mod movement;
+mod spawning;
+mod particles;
+
+//one queue for everything - items, traps and whatever else wants to affect the world
+lazy_static! {
+    pub static ref EFFECT_QUEUE : Mutex<VecDeque<EffectSpawner>> = Mutex::new(VecDeque::new());
+}
+
+pub enum EffectType {
+    Damage { amount : i32 },
+    Healing { amount : i32 },
+    Confusion { turns : i32 },
+    Immobilize { turns : i32 },
+    Food,
+    Drink,
+    Teleport { range : i32 },
+    Alarm { faction : String },
+    Spawn { name : String, deploy : bool },
+    Particle { glyph : u8, fg : RGB, bg : RGB, lifespan : f32 },
+    //these expand into the effects their source entity has
+    ItemUse { item : Entity },
+    TriggerFire { trigger : Entity },
+}
+
+#[derive(Clone)]
+pub enum Targets {
+    Single { target : Entity },
+    TargetList { targets : Vec<Entity> },
+    Tile { tile_idx : i32 },
+    //an area effect; the centre is where falloff is measured from
+    Area { centre : i32, tiles : Vec<i32> }
+}
+
+pub struct EffectSpawner {
+    pub creator : Option<Entity>,
+    pub effect_type : EffectType,
+    pub targets : Targets
+}
+
+pub fn add_effect(creator : Option<Entity>, effect_type: EffectType, targets : Targets) {
+    EFFECT_QUEUE
+        .lock()
+        .unwrap()
+        .push_back(EffectSpawner{
+            creator,
+            effect_type,
+            targets
+        });
+}
+
+//effects can queue more effects (e.g. an item use), so keep going until it's empty
+pub fn run_effects_queue(ecs : &mut World) {
+    loop {
+        let effect : Option<EffectSpawner> = EFFECT_QUEUE.lock().unwrap().pop_front();
+        if let Some(effect) = effect {
+            target_applicator(ecs, &effect);
+        } else {
+            break;
+        }
+    }
+}
+
+fn target_applicator(ecs : &mut World, effect : &EffectSpawner) {
+    match &effect.effect_type {
+        EffectType::ItemUse{ item } => triggers::item_trigger(effect.creator, *item, &effect.targets, ecs),
+        EffectType::TriggerFire{ trigger } => triggers::trigger(effect.creator, *trigger, &effect.targets, ecs),
+        _ => {
+            match &effect.targets {
+                Targets::Tile{ tile_idx } => affect_tile(ecs, effect, *tile_idx),
+                Targets::Area{ tiles, .. } => tiles.iter().for_each(|tile_idx| affect_tile(ecs, effect, *tile_idx)),
+                Targets::Single{ target } => affect_entity(ecs, effect, *target),
+                Targets::TargetList{ targets } => targets.iter().for_each(|entity| affect_entity(ecs, effect, *entity)),
+            }
+        }
+    }
+}
+
+//some effects happen to the tile itself, the rest to whoever is standing there
+fn tile_effect(effect : &EffectType) -> bool {
+    match effect {
+        EffectType::Particle{..} | EffectType::Spawn{..} => true,
+        _ => false
+    }
+}
+
+fn affect_tile(ecs : &mut World, effect : &EffectSpawner, tile_idx : i32) {
+    if tile_effect(&effect.effect_type) {
+        match &effect.effect_type {
+            EffectType::Particle{..} => particles::particle_to_tile(ecs, tile_idx, effect),
+            EffectType::Spawn{..} => spawning::spawn_near(ecs, effect, tile_idx),
+            _ => {}
+        }
+    } else {
+        let content = ecs.fetch::<Map>().tile_content[tile_idx as usize].clone();
+        content.iter().for_each(|entity| affect_entity(ecs, effect, *entity));
+    }
+}
+
+fn affect_entity(ecs : &mut World, effect : &EffectSpawner, target : Entity) {
+    match &effect.effect_type {
+        EffectType::Damage{..} => damage::inflict_damage(ecs, effect, target),
+        EffectType::Healing{..} => damage::heal_damage(ecs, effect, target),
+        EffectType::Confusion{..} => status::add_confusion(ecs, effect, target),
+        EffectType::Immobilize{..} => status::add_immobilized(ecs, effect, target),
+        EffectType::Food => hunger::feed(ecs, effect, target),
+        EffectType::Drink => hunger::quench(ecs, effect, target),
+        EffectType::Teleport{..} => movement::teleport(ecs, effect, target),
+        EffectType::Alarm{..} => status::raise_alarm(ecs, effect, target),
+        //tile effects aimed at an entity happen where it stands
+        EffectType::Particle{..} | EffectType::Spawn{..} => {
+            if let Some(tile_idx) = entity_position(ecs, target) {
+                affect_tile(ecs, effect, tile_idx);
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,39 @@
+use specs::prelude::*;
+use super::*;
+use crate::components::{Position, Viewshed};
+use crate::gamelog::{GameLog, LogCategory};
+use crate::map::tile_walkable;
+use rltk::Point;
+
+//teleport to a random walkable spot within range; give up after a few tries
+pub fn teleport(ecs : &mut World, effect : &EffectSpawner, target : Entity) {
+    if let EffectType::Teleport{ range } = effect.effect_type {
+        let player_entity = *ecs.fetch::<Entity>();
+        let map = ecs.fetch::<Map>();
+        let mut rng = ecs.write_resource::<rltk::RandomNumberGenerator>();
+        let mut positions = ecs.write_storage::<Position>();
+        if let Some(pos) = positions.get_mut(target) {
+            for _try in 0..20 {
+                let x = pos.x + rng.range(-range, range+1);
+                let y = pos.y + rng.range(-range, range+1);
+                if x > 0 && x < map.width-1 && y > 0 && y < map.height-1 {
+                    let dest_idx = map.xy_idx(x, y);
+                    if tile_walkable(map.tiles[dest_idx]) && !map.blocked[dest_idx] {
+                        pos.x = x;
+                        pos.y = y;
+                        if let Some(viewshed) = ecs.write_storage::<Viewshed>().get_mut(target) {
+                            viewshed.dirty = true;
+                        }
+                        if target == player_entity {
+                            let mut player_pos = ecs.write_resource::<Point>();
+                            player_pos.x = x;
+                            player_pos.y = y;
+                            ecs.fetch_mut::<GameLog>().add(LogCategory::System, "The world blurs around you...");
+                        }
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
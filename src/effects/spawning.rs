@@ -0,0 +1,42 @@
+use specs::prelude::*;
+use super::*;
+use crate::components::{Pools, Hidden, DeployedBy};
+use crate::map::tile_walkable;
+use crate::spawner;
+
+//spawn on the tile, or next to it if someone is already standing there
+pub fn spawn_near(ecs : &mut World, effect : &EffectSpawner, tile_idx : i32) {
+    if let EffectType::Spawn{ name, deploy } = &effect.effect_type {
+        let spawn_idx : Option<usize>;
+        {
+            let map = ecs.fetch::<Map>();
+            let pools = ecs.read_storage::<Pools>();
+            let is_free = |idx : usize| {
+                tile_walkable(map.tiles[idx]) && !map.blocked[idx] &&
+                    !map.tile_content[idx].iter().any(|e| pools.get(*e).is_some())
+            };
+
+            let (x, y) = (tile_idx % map.width, tile_idx / map.width);
+            if is_free(tile_idx as usize) {
+                spawn_idx = Some(tile_idx as usize);
+            } else {
+                spawn_idx = [(-1,0), (1,0), (0,-1), (0,1), (-1,-1), (1,-1), (-1,1), (1,1)].iter()
+                    .map(|(dx, dy)| (x + dx, y + dy))
+                    .filter(|(sx, sy)| *sx > 0 && *sx < map.width-1 && *sy > 0 && *sy < map.height-1)
+                    .map(|(sx, sy)| map.xy_idx(sx, sy))
+                    .find(|idx| is_free(*idx));
+            }
+        }
+
+        if let Some(spawn_idx) = spawn_idx {
+            let spawned = spawner::spawn_entity(ecs, &(&spawn_idx, name));
+            //you know where you put your own trap, and what it catches is down to you
+            if let (Some(spawned), true) = (spawned, *deploy) {
+                ecs.write_storage::<Hidden>().remove(spawned);
+                if let Some(owner) = effect.creator {
+                    ecs.write_storage::<DeployedBy>().insert(spawned, DeployedBy{ owner }).expect("Unable to insert owner");
+                }
+            }
+        }
+    }
+}
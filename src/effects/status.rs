@@ -0,0 +1,56 @@
+use specs::prelude::*;
+use super::*;
+use crate::components::{Pools, Confusion, Immobilized, Faction, Chasing, Position};
+use crate::gamelog::{GameLog, LogCategory};
+
+//how far an alarm carries - kept under the chase AI's path limit
+const ALARM_RADIUS : f32 = 12.0;
+
+//only living things are affected, not doors and other props
+fn is_alive(ecs : &World, target : Entity) -> bool {
+    ecs.read_storage::<Pools>().get(target).is_some()
+}
+
+pub fn add_confusion(ecs : &mut World, effect : &EffectSpawner, target : Entity) {
+    if let EffectType::Confusion{ turns } = effect.effect_type {
+        if !is_alive(ecs, target) { return; }
+        ecs.write_storage::<Confusion>().insert(target, Confusion{ turns }).expect("Unable to insert status");
+        add_effect(None,
+            EffectType::Particle{ glyph: rltk::to_cp437('?'), fg: rltk::RGB::named(rltk::PINK), bg: rltk::RGB::named(rltk::BLACK), lifespan: 200.0 },
+            Targets::Single{ target });
+    }
+}
+
+pub fn add_immobilized(ecs : &mut World, effect : &EffectSpawner, target : Entity) {
+    if let EffectType::Immobilize{ turns } = effect.effect_type {
+        if !is_alive(ecs, target) { return; }
+        ecs.write_storage::<Immobilized>().insert(target, Immobilized{ turns }).expect("Unable to insert status");
+        if target == *ecs.fetch::<Entity>() {
+            ecs.fetch_mut::<GameLog>().add(LogCategory::Combat, "You are caught in a net!");
+        }
+    }
+}
+
+//every member of the faction nearby comes for the target
+pub fn raise_alarm(ecs : &mut World, effect : &EffectSpawner, target : Entity) {
+    if let EffectType::Alarm{ faction } = &effect.effect_type {
+        if !is_alive(ecs, target) { return; }
+        let positions = ecs.read_storage::<Position>();
+        let target_pt = match positions.get(target) {
+            None => return,
+            Some(pos) => rltk::Point::new(pos.x, pos.y)
+        };
+        ecs.fetch_mut::<GameLog>().add(LogCategory::Combat, "An alarm blares!");
+
+        let entities = ecs.entities();
+        let factions = ecs.read_storage::<Faction>();
+        let mut chasing = ecs.write_storage::<Chasing>();
+        for (responder, responder_faction, pos) in (&entities, &factions, &positions).join() {
+            if responder_faction.name == *faction && responder != target &&
+                rltk::DistanceAlg::Pythagoras.distance2d(target_pt, rltk::Point::new(pos.x, pos.y)) < ALARM_RADIUS
+            {
+                chasing.insert(responder, Chasing{ target }).expect("Unable to insert");
+            }
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use specs::prelude::*;
+use rltk::{Point, DistanceAlg, LineAlg};
+use crate::components::{Position, AreaOfEffect, AoEShape, Pools, Faction};
+use crate::map::{Map, tile_opaque};
+use super::Targets;
+
+//how wide a cone spreads either side of where it's aimed
+const CONE_HALF_ARC : f32 = std::f32::consts::PI / 4.0;
+
+//tile index an entity is standing on, if it has a position
+pub fn entity_position(ecs : &World, target : Entity) -> Option<i32> {
+    if let Some(pos) = ecs.read_storage::<Position>().get(target) {
+        let map = ecs.fetch::<Map>();
+        return Some(map.xy_idx(pos.x, pos.y) as i32);
+    }
+    None
+}
+
+//where falloff is measured from - circles burst at the target, cones and lines spread from the user
+pub fn blast_centre(origin : Point, target : Point, aoe : &AreaOfEffect) -> Point {
+    match aoe.shape {
+        AoEShape::Circle => target,
+        AoEShape::Cone | AoEShape::Line => origin
+    }
+}
+
+//every tile an area effect used from origin and aimed at target covers, minus the map edges
+pub fn aoe_tiles(map : &Map, origin : Point, target : Point, aoe : &AreaOfEffect) -> Vec<i32> {
+    let in_bounds = |p : &Point| p.x > 0 && p.x < map.width-1 && p.y > 0 && p.y < map.height-1;
+    let mut tiles : Vec<Point> = Vec::new();
+
+    match aoe.shape {
+        AoEShape::Circle => {
+            tiles = rltk::field_of_view(target, aoe.radius, &*map);
+        }
+        AoEShape::Cone => {
+            let aim = ((target.y - origin.y) as f32).atan2((target.x - origin.x) as f32);
+            tiles = rltk::field_of_view(origin, aoe.radius, &*map);
+            tiles.retain(|p| {
+                if *p == origin { return false; }
+                let angle = ((p.y - origin.y) as f32).atan2((p.x - origin.x) as f32);
+                let mut diff = (angle - aim).abs();
+                if diff > std::f32::consts::PI { diff = 2.0 * std::f32::consts::PI - diff; }
+                diff <= CONE_HALF_ARC && DistanceAlg::Pythagoras.distance2d(origin, *p) <= aoe.radius as f32
+            });
+        }
+        AoEShape::Line => {
+            //extend the line to its full length, whatever was clicked
+            let length = f32::max(1.0, DistanceAlg::Pythagoras.distance2d(origin, target));
+            let end = Point::new(
+                origin.x + (((target.x - origin.x) as f32 / length) * aoe.radius as f32).round() as i32,
+                origin.y + (((target.y - origin.y) as f32 / length) * aoe.radius as f32).round() as i32
+            );
+            let mut line = rltk::line2d(LineAlg::Bresenham, origin, end);
+            if line.last() != Some(&end) { line.push(end); }
+            for p in line.iter() {
+                if *p == origin { continue; }
+                //walls stop it
+                if !in_bounds(p) || tile_opaque(map.tiles[map.xy_idx(p.x, p.y)]) { break; }
+                tiles.push(*p);
+            }
+        }
+    }
+
+    tiles.retain(|p| in_bounds(p));
+    tiles.iter().map(|p| map.xy_idx(p.x, p.y) as i32).collect()
+}
+
+//who is caught in an area and how far they are from its centre; allies of the user can be spared
+pub fn area_victims(ecs : &World, creator : Option<Entity>, centre : i32, tiles : &[i32], spare_allies : bool) -> Vec<(Entity, f32)> {
+    let map = ecs.fetch::<Map>();
+    let pools = ecs.read_storage::<Pools>();
+    let factions = ecs.read_storage::<Faction>();
+    let creator_faction = creator.and_then(|c| factions.get(c)).map(|f| f.name.clone());
+    let centre_pt = Point::new(centre % map.width, centre / map.width);
+
+    let mut victims = Vec::new();
+    for tile_idx in tiles.iter() {
+        for entity in map.tile_content[*tile_idx as usize].iter() {
+            //only living things get hurt, not doors and other props
+            if pools.get(*entity).is_none() { continue; }
+            if spare_allies {
+                if Some(*entity) == creator { continue; }
+                if let (Some(faction), Some(creator_faction)) = (factions.get(*entity), &creator_faction) {
+                    if faction.name == *creator_faction { continue; }
+                }
+            }
+            let pt = Point::new(*tile_idx % map.width, *tile_idx / map.width);
+            victims.push((*entity, DistanceAlg::Pythagoras.distance2d(centre_pt, pt)));
+        }
+    }
+    victims
+}
+
+//the living things a non-area effect lands on
+pub fn living_targets(ecs : &World, targets : &Targets) -> Vec<Entity> {
+    let map = ecs.fetch::<Map>();
+    let pools = ecs.read_storage::<Pools>();
+    let on_tile = |tile_idx : i32| map.tile_content[tile_idx as usize].clone();
+    let entities = match targets {
+        Targets::Single{ target } => vec![*target],
+        Targets::TargetList{ targets } => targets.clone(),
+        Targets::Tile{ tile_idx } => on_tile(*tile_idx),
+        Targets::Area{ tiles, .. } => tiles.iter().flat_map(|t| on_tile(*t)).collect()
+    };
+    entities.into_iter().filter(|e| pools.get(*e).is_some()).collect()
+}
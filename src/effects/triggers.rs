@@ -0,0 +1,185 @@
+use specs::prelude::*;
+use super::*;
+use crate::components::*;
+use crate::gamelog::{GameLog, LogCategory, LogEntry};
+
+pub fn item_trigger(creator : Option<Entity>, item : Entity, targets : &Targets, ecs : &mut World) {
+    //an empty gun gets reloaded instead of fired
+    let empty = ecs.read_storage::<Charges>().get(item).map(|c| c.current < 1);
+    if empty == Some(true) {
+        reload(creator, item, ecs);
+        return;
+    }
+
+    let did_something = event_trigger(creator, item, targets, ecs);
+
+    if did_something {
+        if let Some(charges) = ecs.write_storage::<Charges>().get_mut(item) {
+            charges.current -= 1;
+        } else if ecs.read_storage::<Consumable>().get(item).is_some() {
+            use_up(item, ecs);
+        }
+    }
+}
+
+//destroy it, or just use one up if it's a stack
+fn use_up(item : Entity, ecs : &mut World) {
+    let mut stacks = ecs.write_storage::<Stackable>();
+    match stacks.get_mut(item) {
+        Some(stack) if stack.quantity > 1 => stack.quantity -= 1,
+        _ => ecs.entities().delete(item).expect("Delete failed")
+    }
+}
+
+fn reload(creator : Option<Entity>, item : Entity, ecs : &mut World) {
+    let player_entity = *ecs.fetch::<Entity>();
+    let (ammo_name, max) = match ecs.read_storage::<Charges>().get(item) {
+        Some(charges) => (charges.ammo.clone(), charges.max),
+        None => return
+    };
+    let ammo = {
+        let entities = ecs.entities();
+        let backpack = ecs.read_storage::<InBackpack>();
+        let names = ecs.read_storage::<Name>();
+        (&entities, &backpack, &names).join()
+            .find(|(_, pack, name)| Some(pack.owner) == creator && name.name == ammo_name)
+            .map(|(e, _, _)| e)
+    };
+    let item_name = ecs.read_storage::<Name>().get(item).map(|n| n.name.clone()).unwrap_or_default();
+
+    match ammo {
+        None => {
+            if creator == Some(player_entity) {
+                ecs.fetch_mut::<GameLog>().push(LogEntry::new(LogCategory::Loot).append("The ").item_name(&item_name)
+                    .append(" is empty, and you have no ").item_name(&ammo_name).append("."));
+            }
+        }
+        Some(ammo) => {
+            use_up(ammo, ecs);
+            if let Some(charges) = ecs.write_storage::<Charges>().get_mut(item) {
+                charges.current = max;
+            }
+            if creator == Some(player_entity) {
+                ecs.fetch_mut::<GameLog>().push(LogEntry::new(LogCategory::Loot).append("You reload the ").item_name(&item_name).append("."));
+            }
+        }
+    }
+}
+
+pub fn trigger(creator : Option<Entity>, trigger : Entity, targets : &Targets, ecs : &mut World) {
+    // The trap is no longer hidden
+    ecs.write_storage::<Hidden>().remove(trigger);
+
+    // A trap someone set up acts for them, so they get the credit (and the loot)
+    let creator = ecs.read_storage::<DeployedBy>().get(trigger).map(|d| d.owner).or(creator);
+
+    let did_something = event_trigger(creator, trigger, targets, ecs);
+
+    // If it is single activation, it needs to be removed
+    if did_something && ecs.read_storage::<SingleActivation>().get(trigger).is_some() {
+        ecs.entities().delete(trigger).expect("Unable to delete trap");
+    }
+}
+
+//turns whatever effect components the entity has into queued effects
+fn event_trigger(creator : Option<Entity>, entity : Entity, targets : &Targets, ecs : &mut World) -> bool {
+    let mut did_something = false;
+
+    // Areas are resolved to whoever is caught in them up front, so allies can be spared and damage can fall off
+    let (hit, victims, spawn_at) = match targets {
+        Targets::Area{ centre, tiles } => {
+            let spare_allies = ecs.read_storage::<SparesAllies>().get(entity).is_some();
+            let victims = area_victims(ecs, creator, *centre, tiles, spare_allies);
+            (Targets::TargetList{ targets: victims.iter().map(|v| v.0).collect() }, Some(victims), Targets::Tile{ tile_idx: *centre })
+        }
+        _ => (targets.clone(), None, targets.clone())
+    };
+
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+    let player_entity = ecs.fetch::<Entity>();
+    //traps don't get "You use the..." even when they're the player's
+    let by_player = creator == Some(*player_entity) && ecs.read_storage::<EntryTrigger>().get(entity).is_none();
+    let names = ecs.read_storage::<Name>();
+    let entity_name = names.get(entity).map(|n| n.name.clone()).unwrap_or_default();
+
+    // Area effects light up the whole area
+    if ecs.read_storage::<AreaOfEffect>().get(entity).is_some() {
+        let fg = if ecs.read_storage::<EntryTrigger>().get(entity).is_some() { rltk::GREEN } else { rltk::ORANGE };
+        add_effect(None,
+            EffectType::Particle{ glyph: rltk::to_cp437('░'), fg: rltk::RGB::named(fg), bg: rltk::RGB::named(rltk::BLACK), lifespan: 200.0 },
+            targets.clone());
+    }
+
+    // It it is edible, eat it!
+    if ecs.read_storage::<ProvidesFood>().get(entity).is_some() {
+        add_effect(creator, EffectType::Food, hit.clone());
+        if by_player { gamelog.push(LogEntry::new(LogCategory::Loot).append("You eat the ").item_name(&entity_name).append(".")); }
+        did_something = true;
+    }
+
+    // It it is drinkable, drink it!
+    if ecs.read_storage::<ProvidesQuench>().get(entity).is_some() {
+        add_effect(creator, EffectType::Drink, hit.clone());
+        if by_player { gamelog.push(LogEntry::new(LogCategory::Loot).append("You drink the ").item_name(&entity_name).append(".")); }
+        did_something = true;
+    }
+
+    //if it's a medkit, heal
+    if let Some(meditem) = ecs.read_storage::<MedItem>().get(entity) {
+        add_effect(creator, EffectType::Healing{ amount: meditem.heal_amount }, hit.clone());
+        if by_player { gamelog.push(LogEntry::new(LogCategory::Loot).append("You use the ").item_name(&entity_name).append(", healing ").healing(meditem.heal_amount).append(" hp.")); }
+        did_something = true;
+    }
+
+    // If it inflicts damage, apply it to the targets
+    if let Some(damage) = ecs.read_storage::<InflictsDamage>().get(entity) {
+        if by_player { gamelog.push(LogEntry::new(LogCategory::Loot).append("You use the ").item_name(&entity_name).append(".")); }
+        let hurt : Vec<(Entity, i32)> = match (&victims, ecs.read_storage::<DamageFalloff>().get(entity), ecs.read_storage::<AreaOfEffect>().get(entity)) {
+            (Some(victims), Some(_), Some(aoe)) => {
+                //full damage at the centre, down to a sliver at the edge
+                victims.iter().map(|(victim, distance)| {
+                    let scale = 1.0 - (*distance / (aoe.radius as f32 + 1.0));
+                    (*victim, i32::max(1, (damage.damage as f32 * scale) as i32))
+                }).collect()
+            }
+            _ => living_targets(ecs, &hit).into_iter().map(|victim| (victim, damage.damage)).collect()
+        };
+        //the damage effect itself is silent, since melee logs its own hits
+        for (victim, amount) in hurt.iter() {
+            if let Some(name) = names.get(*victim) {
+                gamelog.push(LogEntry::new(LogCategory::Combat).npc_name(&name.name).append(" takes ").damage(*amount).append(" damage."));
+            }
+            add_effect(creator, EffectType::Damage{ amount: *amount }, Targets::Single{ target: *victim });
+        }
+        did_something = true;
+    }
+
+    //status effects
+    if let Some(confusion) = ecs.read_storage::<Confusion>().get(entity) {
+        add_effect(creator, EffectType::Confusion{ turns: confusion.turns }, hit.clone());
+        if by_player { gamelog.push(LogEntry::new(LogCategory::Combat).append("You use the ").item_name(&entity_name).append(", confusing your targets.")); }
+        did_something = true;
+    }
+    if let Some(net) = ecs.read_storage::<Immobilized>().get(entity) {
+        add_effect(creator, EffectType::Immobilize{ turns: net.turns }, hit.clone());
+        did_something = true;
+    }
+
+    if let Some(teleporter) = ecs.read_storage::<Teleports>().get(entity) {
+        add_effect(creator, EffectType::Teleport{ range: teleporter.range }, hit.clone());
+        did_something = true;
+    }
+
+    if let Some(alarm) = ecs.read_storage::<Alarm>().get(entity) {
+        add_effect(creator, EffectType::Alarm{ faction: alarm.faction.clone() }, hit.clone());
+        did_something = true;
+    }
+
+    if let Some(spawn) = ecs.read_storage::<SpawnsEntity>().get(entity) {
+        add_effect(creator, EffectType::Spawn{ name: spawn.name.clone(), deploy: spawn.deploy }, spawn_at);
+        if by_player && spawn.deploy { gamelog.push(LogEntry::new(LogCategory::Loot).append("You set up the ").item_name(&spawn.name).append(".")); }
+        did_something = true;
+    }
+
+    did_something
+}
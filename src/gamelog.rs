@@ -1,3 +1,137 @@
+use rltk::RGB;
+use std::collections::VecDeque;
+
+//oldest entries fall off the front past this
+const MAX_LOG_ENTRIES : usize = 500;
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum LogCategory { Combat, Loot, Dialogue, System }
+
+pub const LOG_CATEGORIES : [LogCategory; 4] = [LogCategory::Combat, LogCategory::Loot, LogCategory::Dialogue, LogCategory::System];
+
+impl LogCategory {
+    pub fn label(&self) -> &'static str {
+        match self {
+            LogCategory::Combat => "Combat",
+            LogCategory::Loot => "Loot",
+            LogCategory::Dialogue => "Dialogue",
+            LogCategory::System => "System"
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LogFragment {
+    pub color : RGB,
+    pub text : String
+}
+
+/// One line of the log, built up from colored pieces
+#[derive(Clone, Debug)]
+pub struct LogEntry {
+    pub category : LogCategory,
+    //filled in when it goes into the log
+    pub turn : i32,
+    pub fragments : Vec<LogFragment>
+}
+
+impl LogEntry {
+    pub fn new(category : LogCategory) -> LogEntry {
+        LogEntry{ category, turn : 0, fragments : Vec::new() }
+    }
+
+    pub fn colored<S:ToString>(mut self, text : S, color : RGB) -> LogEntry {
+        self.fragments.push(LogFragment{ color, text : text.to_string() });
+        self
+    }
+
+    pub fn append<S:ToString>(self, text : S) -> LogEntry {
+        self.colored(text, RGB::named(rltk::WHITE))
+    }
+
+    //people and monsters
+    pub fn npc_name<S:ToString>(self, text : S) -> LogEntry {
+        self.colored(text, RGB::named(rltk::YELLOW))
+    }
+
+    pub fn item_name<S:ToString>(self, text : S) -> LogEntry {
+        self.colored(text, RGB::named(rltk::CYAN))
+    }
+
+    pub fn damage(self, amount : i32) -> LogEntry {
+        self.colored(amount, RGB::named(rltk::RED))
+    }
+
+    pub fn healing(self, amount : i32) -> LogEntry {
+        self.colored(amount, RGB::named(rltk::GREEN))
+    }
+
+    pub fn text(&self) -> String {
+        self.fragments.iter().map(|f| f.text.as_str()).collect()
+    }
+}
+
+/// Which categories the history viewer shows
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct LogFilter {
+    pub combat : bool,
+    pub loot : bool,
+    pub dialogue : bool,
+    pub system : bool
+}
+
+impl LogFilter {
+    pub fn all() -> LogFilter {
+        LogFilter{ combat : true, loot : true, dialogue : true, system : true }
+    }
+
+    pub fn shows(&self, category : LogCategory) -> bool {
+        match category {
+            LogCategory::Combat => self.combat,
+            LogCategory::Loot => self.loot,
+            LogCategory::Dialogue => self.dialogue,
+            LogCategory::System => self.system
+        }
+    }
+
+    pub fn toggle(&mut self, category : LogCategory) {
+        match category {
+            LogCategory::Combat => self.combat = !self.combat,
+            LogCategory::Loot => self.loot = !self.loot,
+            LogCategory::Dialogue => self.dialogue = !self.dialogue,
+            LogCategory::System => self.system = !self.system
+        }
+    }
+}
+
 pub struct GameLog {
-    pub entries : Vec<String>
-}
\ No newline at end of file
+    entries : VecDeque<LogEntry>,
+    //counts the player's turns, for stamping entries
+    pub turn : i32,
+    //how many fight-related entries there have been, so travel knows when to stop
+    pub combat_messages : usize
+}
+
+impl GameLog {
+    pub fn new() -> GameLog {
+        GameLog{ entries : VecDeque::new(), turn : 0, combat_messages : 0 }
+    }
+
+    pub fn push(&mut self, mut entry : LogEntry) {
+        entry.turn = self.turn;
+        if entry.category == LogCategory::Combat { self.combat_messages += 1; }
+        self.entries.push_back(entry);
+        while self.entries.len() > MAX_LOG_ENTRIES {
+            self.entries.pop_front();
+        }
+    }
+
+    //a plain line of text
+    pub fn add<S:ToString>(&mut self, category : LogCategory, text : S) {
+        self.push(LogEntry::new(category).append(text));
+    }
+
+    pub fn entries(&self) -> &VecDeque<LogEntry> {
+        &self.entries
+    }
+}
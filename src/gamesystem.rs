@@ -1,3 +1,52 @@
+use super::{Attributes, MeleeWeapon};
+
 pub fn attr_bonus(value: i32) -> i32 {
     (value-10)/2 // See: https://roll20.net/compendium/dnd5e/Ability%20Scores#content
-}
\ No newline at end of file
+}
+
+//what you hit with when nothing is wielded
+pub fn unarmed() -> MeleeWeapon {
+    MeleeWeapon{ damage_n_dice : 1, damage_die_type : 4, damage_bonus : 0 }
+}
+
+//melee is a d100 roll under this, after the bonuses are added to the roll
+pub const MELEE_HIT_TARGET : i32 = 55;
+
+/// Percent chance a melee swing lands, going by the same roll the combat system makes
+pub fn melee_hit_chance(attr : &Attributes) -> i32 {
+    i32::max(0, i32::min(100, MELEE_HIT_TARGET - 1 - attr.strength.bonus))
+}
+
+/// Turns until the next move: 7-12, less the DEX bonus
+pub fn roll_initiative(rng : &mut rltk::RandomNumberGenerator, attr : Option<&Attributes>) -> i32 {
+    6 + rng.roll_dice(1, 6) - attr.map_or(0, |a| a.dexterity.bonus)
+}
+
+/// The lowest and highest roll_initiative can give
+pub fn initiative_range(attr : &Attributes) -> (i32, i32) {
+    (7 - attr.dexterity.bonus, 12 - attr.dexterity.bonus)
+}
+
+pub fn carry_capacity_lbs(attr : &Attributes) -> i32 {
+    (attr.strength.base + attr.strength.modifiers) * 15
+}
+
+/// Weapon dice the way the raws write them: 1d6, 2d4+1
+pub fn damage_label(weapon : &MeleeWeapon) -> String {
+    if weapon.damage_bonus == 0 {
+        format!("{}d{}", weapon.damage_n_dice, weapon.damage_die_type)
+    } else {
+        format!("{}d{}{:+}", weapon.damage_n_dice, weapon.damage_die_type, weapon.damage_bonus)
+    }
+}
+
+//the one number that matters most about an item, for menus; spawned items and raws both go through here
+pub fn summarize_item(weapon : Option<&MeleeWeapon>, defense : Option<f32>) -> String {
+    if let Some(weapon) = weapon {
+        return damage_label(weapon);
+    }
+    if let Some(defense) = defense {
+        return format!("{:+.1} def", defense);
+    }
+    String::new()
+}
@@ -1,11 +1,25 @@
 extern crate rltk;
-use rltk::{ RGB, Rltk, Console, VirtualKeyCode, Point };
-use super::{ Player, Pools, gamelog::GameLog, camera, RunState,
+use rltk::{ RGB, Rltk, Console, Point };
+use super::{ Player, Pools, gamelog::{GameLog, LogEntry, LogFilter, LOG_CATEGORIES}, camera, RunState,
     State, Entity, Name, InBackpack, Equipped, Viewshed, Attributes, Attribute,
-    VendorMode, Item, Vendor};
+    VendorMode, Item, Vendor, Stackable, Map, AreaOfEffect, SparesAllies, effects::aoe_tiles,
+    map_builders::MapSnapshot, Hidden, Faction, Confusion, Immobilized, EquipmentSlot,
+    Equippable, MeleeWeapon, DefenseBonus, Charges, Initiative, MedItem, Consumable, Ranged, InflictsDamage,
+    DamageFalloff, ProvidesFood, ProvidesQuench, Teleports, SpawnsEntity, Fragile, Lockpick,
+    raws::{RAWS, faction_reaction, Reaction}, list_menu::{list_menu, ListMenu, ListMenuResult, MenuRow, ItemCategory},
+    input::{read_command, key_label, Command, InputContext}, web_ui, layout::{layout, Frame},
+    unarmed, damage_label, summarize_item, melee_hit_chance, initiative_range, carry_capacity_lbs, MELEE_HIT_TARGET};
 extern crate specs;
 use specs::prelude::*;
 
+//name with the stack size, if there's more than one
+fn item_display_name(name : &Name, stack : Option<&Stackable>) -> String {
+    match stack {
+        Some(stack) if stack.quantity > 1 => format!("{} (x{})", name.name, stack.quantity),
+        _ => name.name.to_string()
+    }
+}
+
 //main menu
 #[derive(PartialEq, Copy, Clone)]
 pub enum MainMenuSelection { NewGame,
@@ -39,60 +53,23 @@ pub fn main_menu(gs : &mut State, ctx : &mut Rltk) -> MainMenuResult {
             ctx.print_color_centered(26, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), "Quit");
         }
 
-        // New: Handle web buttons
-        if let Some(btn) = &ctx.web_button {
-            match btn.trim() {
-                "go_n" => {
-                    let newselection;
-                    match selection {
-                        MainMenuSelection::NewGame => newselection = MainMenuSelection::Quit,
-                        //MainMenuSelection::LoadGame => newselection = MainMenuSelection::NewGame,
-                        MainMenuSelection::Quit => newselection = MainMenuSelection::NewGame
-                    }
-                    return MainMenuResult::NoSelection{ selected: newselection }
-                }
-                "go_s" => {
-                    let newselection;
-                    match selection {
-                        MainMenuSelection::NewGame => newselection = MainMenuSelection::Quit,
-                        //MainMenuSelection::LoadGame => newselection = MainMenuSelection::Quit,
-                        MainMenuSelection::Quit => newselection = MainMenuSelection::NewGame
-                    }
-                    return MainMenuResult::NoSelection{ selected: newselection }
-                }
-                "confirm" => return MainMenuResult::Selected{ selected : selection },
-                _ => return MainMenuResult::NoSelection{ selected: selection }
-            }
-        }
-        else {
-            match ctx.key {
-                None => return MainMenuResult::NoSelection{ selected: selection },
-                Some(key) => {
-                    match key {
-                        VirtualKeyCode::Escape => { return MainMenuResult::NoSelection{ selected: MainMenuSelection::Quit } }
-                        VirtualKeyCode::Up => {
-                            let newselection;
-                            match selection {
-                                MainMenuSelection::NewGame => newselection = MainMenuSelection::Quit,
-                                //MainMenuSelection::LoadGame => newselection = MainMenuSelection::NewGame,
-                                MainMenuSelection::Quit => newselection = MainMenuSelection::NewGame
-                            }
-                            return MainMenuResult::NoSelection{ selected: newselection }
-                        }
-                        VirtualKeyCode::Down => {
-                            let newselection;
-                            match selection {
-                                MainMenuSelection::NewGame => newselection = MainMenuSelection::Quit,
-                                //MainMenuSelection::LoadGame => newselection = MainMenuSelection::Quit,
-                                MainMenuSelection::Quit => newselection = MainMenuSelection::NewGame
-                            }
-                            return MainMenuResult::NoSelection{ selected: newselection }
-                        }
-                        VirtualKeyCode::Return => return MainMenuResult::Selected{ selected : selection },
-                        _ => return MainMenuResult::NoSelection{ selected: selection }
-                    }
-                }
-            }
+        //only two entries, so up and down both flip between them
+        let other = match selection {
+            MainMenuSelection::NewGame => MainMenuSelection::Quit,
+            //MainMenuSelection::LoadGame => MainMenuSelection::NewGame,
+            MainMenuSelection::Quit => MainMenuSelection::NewGame
+        };
+        web_ui::title("Neon Twilight");
+        web_ui::option(0, "Begin New Game");
+        web_ui::option(1, "Quit");
+        match read_command(ctx, InputContext::Menu) {
+            Some(Command::Cancel) => return MainMenuResult::NoSelection{ selected: MainMenuSelection::Quit },
+            //the web page picks entries directly
+            Some(Command::Choose(0)) => return MainMenuResult::Selected{ selected : MainMenuSelection::NewGame },
+            Some(Command::Choose(1)) => return MainMenuResult::Selected{ selected : MainMenuSelection::Quit },
+            Some(Command::Up) | Some(Command::Down) => return MainMenuResult::NoSelection{ selected: other },
+            Some(Command::Confirm) => return MainMenuResult::Selected{ selected : selection },
+            _ => return MainMenuResult::NoSelection{ selected: selection }
         }
     }
 
@@ -109,21 +86,63 @@ pub fn game_over(ctx : &mut Rltk) -> GameOverResult {
 
     ctx.print_color_centered(20, RGB::named(rltk::MAGENTA), RGB::named(rltk::BLACK), "Press any key to return to the menu.");
 
-    // New: Handle web buttons
-    if let Some(btn) = &ctx.web_button {
-        match btn.trim() {
-            _ => GameOverResult::NoSelection,
-            "go_n" => GameOverResult::QuitToMenu,
-        }
+    web_ui::title("Your journey has ended!");
+    web_ui::offer_labelled(InputContext::Menu, Command::Confirm, "Back to menu");
+    //any key at all, bound or not
+    if ctx.key.is_some() || read_command(ctx, InputContext::Menu).is_some() {
+        GameOverResult::QuitToMenu
+    } else {
+        GameOverResult::NoSelection
     }
-    else {
-        match ctx.key {
-            None => GameOverResult::NoSelection,
-            Some(_) => GameOverResult::QuitToMenu
-        }
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum MapgenAction { NoAction, TogglePause, StepForward, StepBack, Rewind,
+    ToggleRooms, ToggleCorridors, ToggleSubmaps, ToggleSpawns, Export, Skip, Disable }
+
+pub fn draw_mapgen_info(ctx : &mut Rltk, snapshot : &MapSnapshot, index : usize, total : usize, paused : bool, overlays : &camera::DebugOverlays, message : &Option<String>) {
+    let (_, y_chars) = ctx.get_char_size();
+    let state = if paused { "PAUSED" } else { "playing" };
+    ctx.print_color(1, 0, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK),
+        &format!("Step {}/{}: {} ({})", index + 1, total, snapshot.label, state));
+
+    //lit up when the overlay is on
+    let toggle_color = |on : bool| if on { RGB::named(rltk::MAGENTA) } else { RGB::named(rltk::GRAY) };
+    let y = y_chars as i32 - 1;
+    ctx.print_color(1, y, toggle_color(overlays.rooms), RGB::named(rltk::BLACK), "(R)ooms");
+    ctx.print_color(9, y, toggle_color(overlays.corridors), RGB::named(rltk::BLACK), "(C)orridors");
+    ctx.print_color(21, y, toggle_color(overlays.submaps), RGB::named(rltk::BLACK), "(S)ubmaps");
+    ctx.print_color(31, y, toggle_color(overlays.spawns), RGB::named(rltk::BLACK), &format!("s(P)awns: {}", snapshot.spawns.len()));
+    let help = "SPACE pause  </> step  HOME rewind  E export  ENTER skip  V hide";
+    ctx.print_color(1, y - 1, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), help);
+    if let Some(message) = message {
+        ctx.print_color(1, y - 2, RGB::named(rltk::CYAN), RGB::named(rltk::BLACK), message);
     }
 }
 
+//the visualizer controls the web page gets
+const WEB_MAPGEN : [Command; 5] = [Command::Rewind, Command::StepBack, Command::TogglePause, Command::StepForward, Command::Skip];
+
+pub fn mapgen_input(ctx : &mut Rltk) -> MapgenAction {
+    web_ui::title("Map generation");
+    for command in WEB_MAPGEN.iter() {
+        web_ui::offer(InputContext::Mapgen, *command);
+    }
+    match read_command(ctx, InputContext::Mapgen) {
+        Some(Command::TogglePause) => MapgenAction::TogglePause,
+        Some(Command::StepForward) => MapgenAction::StepForward,
+        Some(Command::StepBack) => MapgenAction::StepBack,
+        Some(Command::Rewind) => MapgenAction::Rewind,
+        Some(Command::ToggleRooms) => MapgenAction::ToggleRooms,
+        Some(Command::ToggleCorridors) => MapgenAction::ToggleCorridors,
+        Some(Command::ToggleSubmaps) => MapgenAction::ToggleSubmaps,
+        Some(Command::ToggleSpawns) => MapgenAction::ToggleSpawns,
+        Some(Command::Export) => MapgenAction::Export,
+        Some(Command::Skip) => MapgenAction::Skip,
+        Some(Command::ToggleVisualizer) => MapgenAction::Disable,
+        _ => MapgenAction::NoAction
+    }
+}
 
 //helper
 pub fn draw_hollow_box(
@@ -151,58 +170,112 @@ pub fn draw_hollow_box(
     }
 }
 
-fn draw_attribute(name : &str, attribute : &Attribute, y : i32, ctx: &mut Rltk) {
+fn draw_attribute(name : &str, attribute : &Attribute, x : i32, y : i32, ctx: &mut Rltk) {
     let black = RGB::named(rltk::BLACK);
     let attr_gray : RGB = RGB::from_hex("#CCCCCC").expect("Oops");
-    ctx.print_color(50, y, attr_gray, black, name);
+    ctx.print_color(x, y, attr_gray, black, name);
     let color : RGB =
         if attribute.modifiers < 0 { RGB::from_f32(1.0, 0.0, 0.0) }
         else if attribute.modifiers == 0 { RGB::named(rltk::WHITE) }
         else { RGB::from_f32(0.0, 1.0, 0.0) };
-    ctx.print_color(67, y, color, black, &format!("{}", attribute.base + attribute.modifiers));
-    ctx.print_color(73, y, color, black, &format!("{}", attribute.bonus));
-    if attribute.bonus > 0 { ctx.set(72, y, color, black, rltk::to_cp437('+')); }
+    ctx.print_color(x + 17, y, color, black, &format!("{}", attribute.base + attribute.modifiers));
+    ctx.print_color(x + 23, y, color, black, &format!("{}", attribute.bonus));
+    if attribute.bonus > 0 { ctx.set(x + 22, y, color, black, rltk::to_cp437('+')); }
 }
 
+//the stats take this many rows from the side panel's top border, whatever the panel's size
+const PANEL_DIVIDER : i32 = 11;
+//the minimap takes the bottom part of the side panel, one row in this many
+const MINIMAP_SHARE : i32 = 3;
+//any less than this and the minimap isn't worth drawing
+const MIN_MINIMAP_HEIGHT : i32 = 5;
+//under the map the panel is wide and short, so the minimap goes to the right of a column this wide instead
+const PORTRAIT_STATS_WIDTH : i32 = 30;
+
 pub fn draw_ui(ecs: &World, ctx : &mut Rltk) {
     use rltk::to_cp437;
     let box_gray : RGB = RGB::from_hex("#999999").expect("Oops");
     let black = RGB::named(rltk::BLACK);
-
-    draw_hollow_box(ctx, 0, 0, 79, 59, box_gray, black); // Overall box
-    draw_hollow_box(ctx, 0, 0, 49, 45, box_gray, black); // Map box
-    draw_hollow_box(ctx, 0, 45, 79, 14, box_gray, black); // Log box
-    draw_hollow_box(ctx, 49, 0, 30, 11, box_gray, black); // Top-right panel
+    let layout = layout(ctx);
+    let (map_box, panel, log_box) = (layout.map, layout.panel, layout.log);
+    let screen = layout.screen();
+
+    draw_hollow_box(ctx, screen.x, screen.y, screen.width, screen.height, box_gray, black); // Overall box
+    draw_hollow_box(ctx, map_box.x, map_box.y, map_box.width, map_box.height, box_gray, black); // Map box
+    draw_hollow_box(ctx, log_box.x, log_box.y, log_box.width, log_box.height, box_gray, black); // Log box
+    draw_hollow_box(ctx, panel.x, panel.y, panel.width, panel.height, box_gray, black); // Side panel
     
     // Draw box connectors
-    ctx.set(0, 45, box_gray, black, to_cp437('├'));
-    ctx.set(49, 11, box_gray, black, to_cp437('├'));
-    ctx.set(49, 0, box_gray, black, to_cp437('┬'));
-    ctx.set(49, 45, box_gray, black, to_cp437('┴'));
-    ctx.set(79, 8, box_gray, black, to_cp437('┤'));
-    ctx.set(79, 45, box_gray, black, to_cp437('┤'));
+    ctx.set(log_box.x, log_box.y, box_gray, black, to_cp437('├'));
+    ctx.set(log_box.x + log_box.width, log_box.y, box_gray, black, to_cp437('┤'));
+    if layout.portrait {
+        ctx.set(panel.x, panel.y, box_gray, black, to_cp437('├'));
+        ctx.set(panel.x + panel.width, panel.y, box_gray, black, to_cp437('┤'));
+    } else {
+        ctx.set(panel.x, panel.y, box_gray, black, to_cp437('┬'));
+        ctx.set(panel.x, log_box.y, box_gray, black, to_cp437('┴'));
+    }
+    //where the minimap goes, and how far down the equipment list can run: at the bottom of a tall
+    //panel, or beside the stats in a wide one
+    let minimap = if layout.portrait {
+        let left = panel.x + PORTRAIT_STATS_WIDTH;
+        Frame{ x : left, y : panel.y, width : panel.x + panel.width - left, height : panel.height }
+    } else {
+        let height = i32::max(MIN_MINIMAP_HEIGHT, panel.inner_height() / MINIMAP_SHARE) + 1;
+        Frame{ x : panel.x, y : panel.bottom() - height, width : panel.width, height }
+    };
+    let show_minimap = minimap.inner_height() - 1 >= MIN_MINIMAP_HEIGHT && minimap.inner_width() > 2
+        && (layout.portrait || minimap.y > panel.y + PANEL_DIVIDER + 5);
+    let (stats_right, equipment_end) = match (show_minimap, layout.portrait) {
+        (true, true) => (minimap.x, panel.bottom()),
+        (true, false) => (panel.x + panel.width, minimap.y),
+        _ => (panel.x + panel.width, panel.bottom())
+    };
+    if show_minimap && layout.portrait {
+        for y in panel.inner_y() .. panel.bottom() {
+            ctx.set(minimap.x, y, box_gray, black, to_cp437('│'));
+        }
+        ctx.set(minimap.x, panel.y, box_gray, black, to_cp437('┬'));
+        ctx.set(minimap.x, panel.bottom(), box_gray, black, to_cp437('┴'));
+    }
+
+    //the stats get a box of their own at the top of the panel
+    if panel.height > PANEL_DIVIDER {
+        let y = panel.y + PANEL_DIVIDER;
+        for x in panel.inner_x() .. stats_right {
+            ctx.set(x, y, box_gray, black, to_cp437('─'));
+        }
+        ctx.set(panel.x, y, box_gray, black, to_cp437('├'));
+        ctx.set(stats_right, y, box_gray, black, to_cp437('┤'));
+    }
+
+    //everything in the panel is placed from its top left
+    let (x, top) = (panel.inner_x(), panel.y);
+    //the bars fill what's left of the row after the HP text
+    let bar_x = x + 14;
+    let bar_width = stats_right - 1 - bar_x;
 
     //draw health bar
     let player_entity = ecs.fetch::<Entity>();
     let pools = ecs.read_storage::<Pools>();
     let player_pools = pools.get(*player_entity).unwrap();
     let health = format!(" HP: {} / {} ", player_pools.hit_points.current, player_pools.hit_points.max);
-    ctx.print_color(50, 1, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), &health);
+    ctx.print_color(x, top + 1, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), &health);
 
-    ctx.draw_bar_horizontal(64, 1, 14, player_pools.hit_points.current, player_pools.hit_points.max, RGB::named(rltk::RED), RGB::named(rltk::BLACK));
+    ctx.draw_bar_horizontal(bar_x, top + 1, bar_width, player_pools.hit_points.current, player_pools.hit_points.max, RGB::named(rltk::RED), RGB::named(rltk::BLACK));
 
-    ctx.draw_bar_horizontal(64, 2, 14, player_pools.hunger, 500, RGB::named(rltk::GREEN), RGB::named(rltk::BLACK));
-    ctx.draw_bar_horizontal(64, 3, 14, player_pools.thirst, 300, RGB::named(rltk::BLUE), RGB::named(rltk::BLACK));
+    ctx.draw_bar_horizontal(bar_x, top + 2, bar_width, player_pools.hunger, 500, RGB::named(rltk::GREEN), RGB::named(rltk::BLACK));
+    ctx.draw_bar_horizontal(bar_x, top + 3, bar_width, player_pools.thirst, 300, RGB::named(rltk::BLUE), RGB::named(rltk::BLACK));
 
     //draw attributes
     let attributes = ecs.read_storage::<Attributes>();
     let attr = attributes.get(*player_entity).unwrap();
-    draw_attribute("STR:", &attr.strength, 4, ctx);
-    draw_attribute("DEX:", &attr.dexterity, 5, ctx);
-    draw_attribute("CON:", &attr.constitution, 6, ctx);
-    draw_attribute("INT:", &attr.intelligence, 7, ctx);
-    draw_attribute("WIS:", &attr.wisdom, 8, ctx);
-    draw_attribute("CHA:", &attr.charisma, 9, ctx);
+    draw_attribute("STR:", &attr.strength, x, top + 4, ctx);
+    draw_attribute("DEX:", &attr.dexterity, x, top + 5, ctx);
+    draw_attribute("CON:", &attr.constitution, x, top + 6, ctx);
+    draw_attribute("INT:", &attr.intelligence, x, top + 7, ctx);
+    draw_attribute("WIS:", &attr.wisdom, x, top + 8, ctx);
+    draw_attribute("CHA:", &attr.charisma, x, top + 9, ctx);
 
     //basic info
     //let player_entity = ecs.fetch::<Entity>();
@@ -210,7 +283,9 @@ pub fn draw_ui(ecs: &World, ctx : &mut Rltk) {
     //let viewsheds = ecs.read_storage::<Viewshed>();
 
     let pos = format!("Player: {:?} ", *player_pos);
-    ctx.print_color(50, 12, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), &pos);
+    ctx.print_color(x, top + 12, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), &pos);
+    let depth = format!("Depth: {}", ecs.fetch::<Map>().depth);
+    ctx.print_color(x, top + 13, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), &depth);
 
     // let (min_x, max_x, min_y, max_y) = camera::get_screen_bounds(ecs, ctx);
     // let x_str = format!("X: {:?}-{:?}", min_x, max_x);
@@ -219,230 +294,735 @@ pub fn draw_ui(ecs: &World, ctx : &mut Rltk) {
     // ctx.print_color(50, 12, RGB::named(rltk::LIGHT_BLUE), RGB::named(rltk::BLACK), &y_str);
 
     // Item weight
-    ctx.print_color(50, 14, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), 
+    ctx.print_color(x, top + 14, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), 
         &format!("{:.0} lbs ({} lbs max)", 
             player_pools.total_weight, 
-            (attr.strength.base + attr.strength.modifiers) * 15
+            carry_capacity_lbs(attr)
         )
     );
-    ctx.print_color(50,15, rltk::RGB::named(rltk::CYAN), RGB::named(rltk::BLACK), &format!("Credits: {:.1} cr", player_pools.money));
+    ctx.print_color(x, top + 15, rltk::RGB::named(rltk::CYAN), RGB::named(rltk::BLACK), &format!("Credits: {:.1} cr", player_pools.money));
 
-    // Equipped
-    let mut y = 17;
+    // Equipped, in slot order, for as long as there's room
+    let mut y = top + 17;
     let equipped = ecs.read_storage::<Equipped>();
     let name = ecs.read_storage::<Name>();
-    for (equipped_by, item_name) in (&equipped, &name).join() {
-        if equipped_by.owner == *player_entity {
-            ctx.print_color(50, y, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), &item_name.name);
-            y += 1;
+    for (slot, _label) in EQUIPMENT_SLOTS.iter() {
+        for (equipped_by, item_name) in (&equipped, &name).join() {
+            if equipped_by.owner == *player_entity && equipped_by.slot == *slot && y < equipment_end {
+                let item_name : String = item_name.name.chars().take((stats_right - 1 - x) as usize).collect();
+                ctx.print_color(x, y, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), &item_name);
+                y += 1;
+            }
         }
     }
 
+    //minimap, the whole map squeezed into its part of the panel, under a label
+    if show_minimap {
+        let (minimap_x, minimap_y) = (minimap.inner_x(), minimap.inner_y() + 1);
+        let (minimap_width, minimap_height) = (minimap.inner_width() - 1, minimap.bottom() - minimap_y);
+        ctx.print_color(minimap_x, minimap.inner_y(), RGB::named(rltk::GRAY), black, &format!("Map ({}: full view)", key_label(InputContext::Game, Command::Overview)));
+        let (width, height, scale) = {
+            let map = ecs.fetch::<Map>();
+            let scale = camera::overview_scale(&*map, minimap_width, minimap_height);
+            ((map.width + scale - 1) / scale, (map.height + scale - 1) / scale, scale)
+        };
+        camera::render_overview(ecs, ctx, (minimap_x + (minimap_width - width) / 2, minimap_y, width, height), 0, 0, scale);
+    }
+
     //log
     let log = ecs.fetch::<GameLog>();
+    ctx.print_color(log_box.x + 2, log_box.y, RGB::named(rltk::GRAY), black, &format!(" {}: message history ", key_label(InputContext::Game, Command::Log)));
+
+    let mut y = log_box.inner_y();
+    //as many of the newest as fit in the box
+    let rows = log_box.inner_height() as usize;
+    let entries = log.entries();
+    for entry in entries.iter().skip(entries.len().saturating_sub(rows)) {
+        draw_log_entry(ctx, 2, y, entry, log_box.inner_width() - 2);
+        y += 1;
+    }
 
-    let mut y = 46;
-    //slice to get last x
-    if log.entries.len() > 13 {
-        let slice = log.entries[log.entries.len()-13 .. log.entries.len()].to_vec();
-        for s in slice.iter() {
-            if y < 59 { ctx.print(2, y, &s.to_string()); }
-            y += 1;
+    draw_tooltips(ecs, ctx);
+}
+
+//prints the pieces one after another, cut off at max_width
+fn draw_log_entry(ctx : &mut Rltk, x : i32, y : i32, entry : &LogEntry, max_width : i32) {
+    let mut x_pos = x;
+    for fragment in entry.fragments.iter() {
+        let room = (x + max_width - x_pos) as usize;
+        if room == 0 { break; }
+        let text : String = fragment.text.chars().take(room).collect();
+        ctx.print_color(x_pos, y, fragment.color, RGB::named(rltk::BLACK), &text);
+        x_pos += text.chars().count() as i32;
+    }
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum LogViewResult { Close, Browse { scroll : usize, filter : LogFilter } }
+
+//scroll counts lines up from the newest entry
+pub fn show_log(gs : &mut State, ctx : &mut Rltk, scroll : usize, filter : LogFilter) -> LogViewResult {
+    let layout = layout(ctx);
+    let body = layout.screen_body();
+    let rows = body.inner_height() as usize;
+    let log = gs.ecs.fetch::<GameLog>();
+    let entries : Vec<&LogEntry> = log.entries().iter().filter(|e| filter.shows(e.category)).collect();
+    let max_scroll = entries.len().saturating_sub(rows);
+    let scroll = usize::min(scroll, max_scroll);
+
+    ctx.cls();
+    let box_gray : RGB = RGB::from_hex("#999999").expect("Oops");
+    let black = RGB::named(rltk::BLACK);
+    let screen = layout.screen();
+    draw_hollow_box(ctx, screen.x, screen.y, screen.width, screen.height, box_gray, black);
+    ctx.print_color(2, 1, RGB::named(rltk::YELLOW), black, "Message history");
+    let mut x = 20;
+    for (i, category) in LOG_CATEGORIES.iter().enumerate() {
+        let color = if filter.shows(*category) { RGB::named(rltk::WHITE) } else { RGB::named(rltk::DARK_GRAY) };
+        let label = format!("[{}] {}", i+1, category.label());
+        ctx.print_color(x, 1, color, black, &label);
+        x += label.len() as i32 + 2;
+    }
+
+    let end = entries.len() - scroll;
+    let start = end.saturating_sub(rows);
+    for (row, entry) in entries[start .. end].iter().enumerate() {
+        let y = body.inner_y() + row as i32;
+        ctx.print_color(2, y, RGB::named(rltk::GRAY), black, &format!("{:>5}", entry.turn));
+        draw_log_entry(ctx, 8, y, entry, body.inner_width() - 8);
+    }
+    let help = format!("{}/{}: scroll  {}/{}: page  {}/{}  1-{}: filter  {}: close",
+        key_label(InputContext::Menu, Command::Up), key_label(InputContext::Menu, Command::Down),
+        key_label(InputContext::Menu, Command::PageUp), key_label(InputContext::Menu, Command::PageDown),
+        key_label(InputContext::Menu, Command::Home), key_label(InputContext::Menu, Command::End),
+        LOG_CATEGORIES.len(), key_label(InputContext::Menu, Command::Cancel));
+    ctx.print_color(2, layout.help_row(), RGB::named(rltk::GRAY), black, &help);
+
+    web_ui::title("Message history");
+    web_ui::offer(InputContext::Menu, Command::Cancel);
+    web_ui::offer_labelled(InputContext::Menu, Command::PageUp, "Older");
+    web_ui::offer_labelled(InputContext::Menu, Command::PageDown, "Newer");
+    for (i, category) in LOG_CATEGORIES.iter().enumerate() {
+        let state = if filter.shows(*category) { "on" } else { "off" };
+        web_ui::option(i, &format!("{}: {}", category.label(), state));
+    }
+
+    let mut new_filter = filter;
+    match read_command(ctx, InputContext::Menu) {
+        Some(Command::Cancel) => return LogViewResult::Close,
+        Some(Command::Up) => return LogViewResult::Browse{ scroll : usize::min(scroll + 1, max_scroll), filter },
+        Some(Command::Down) => return LogViewResult::Browse{ scroll : scroll.saturating_sub(1), filter },
+        Some(Command::PageUp) => return LogViewResult::Browse{ scroll : usize::min(scroll + rows, max_scroll), filter },
+        Some(Command::PageDown) => return LogViewResult::Browse{ scroll : scroll.saturating_sub(rows), filter },
+        Some(Command::Home) => return LogViewResult::Browse{ scroll : max_scroll, filter },
+        Some(Command::End) => return LogViewResult::Browse{ scroll : 0, filter },
+        //numbers on the keyboard, the letter buttons on the web
+        Some(Command::Number(n)) if n >= 1 && n <= LOG_CATEGORIES.len() as i32 => new_filter.toggle(LOG_CATEGORIES[n as usize - 1]),
+        Some(Command::Choose(n)) if n < LOG_CATEGORIES.len() as i32 => new_filter.toggle(LOG_CATEGORIES[n as usize]),
+        _ => {}
+    }
+    //a different filter means different lines, so start again from the newest
+    if new_filter != filter {
+        return LogViewResult::Browse{ scroll : 0, filter : new_filter };
+    }
+    LogViewResult::Browse{ scroll, filter }
+}
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum OverviewResult { Close, Browse { x : i32, y : i32, zoomed_out : bool } }
+
+//(x, y) is the map tile in the middle of the view
+pub fn show_map_overview(gs : &mut State, ctx : &mut Rltk, x : i32, y : i32, zoomed_out : bool) -> OverviewResult {
+    let layout = layout(ctx);
+    let body = layout.screen_body();
+    let (view_width, view_height) = (body.inner_width(), body.inner_height());
+    let (map_width, map_height, scale) = {
+        let map = gs.ecs.fetch::<Map>();
+        let scale = if zoomed_out { camera::overview_scale(&*map, view_width, view_height) } else { 1 };
+        (map.width, map.height, scale)
+    };
+    let x = i32::max(0, i32::min(x, map_width - 1));
+    let y = i32::max(0, i32::min(y, map_height - 1));
+    //keep the view on the map where it can be
+    let left = i32::max(0, i32::min(x - view_width * scale / 2, map_width - view_width * scale));
+    let top = i32::max(0, i32::min(y - view_height * scale / 2, map_height - view_height * scale));
+
+    ctx.cls();
+    let box_gray : RGB = RGB::from_hex("#999999").expect("Oops");
+    let black = RGB::named(rltk::BLACK);
+    let screen = layout.screen();
+    draw_hollow_box(ctx, screen.x, screen.y, screen.width, screen.height, box_gray, black);
+    ctx.print_color(2, 1, RGB::named(rltk::YELLOW), black, "Map");
+    let view = if zoomed_out { format!("Whole map, 1:{}", scale) } else { "Close up".to_string() };
+    ctx.print_color(8, 1, RGB::named(rltk::GRAY), black, &view);
+    //the legend sits against the right border, and is left out if it would run into the title
+    let legend = [("@", RGB::named(rltk::YELLOW), "you"), ("<>", RGB::named(rltk::MAGENTA), "stairs"), ("$", RGB::named(rltk::GOLD), "vendor")];
+    let legend_width : i32 = legend.iter().map(|(mark, _, label)| (mark.len() + label.len()) as i32 + 3).sum();
+    let mut legend_x = screen.x + screen.width - legend_width;
+    if legend_x > 8 + view.len() as i32 + 2 {
+        for (mark, color, label) in legend.iter() {
+            ctx.print_color(legend_x, 1, *color, black, mark);
+            ctx.print_color(legend_x + mark.len() as i32 + 1, 1, RGB::named(rltk::GRAY), black, label);
+            legend_x += (mark.len() + label.len()) as i32 + 3;
         }
     }
-    else {
-        for s in log.entries.iter() {
-            if y < 59 { ctx.print(2, y, &s.to_string()); }
-            y += 1;
+
+    camera::render_overview(&gs.ecs, ctx, (body.inner_x(), body.inner_y(), view_width, view_height), left, top, scale);
+    let help = format!("Arrows: pan  {}/{}: pan a screen  {}: find yourself  {}: zoom  {}: close",
+        key_label(InputContext::Menu, Command::PageUp), key_label(InputContext::Menu, Command::PageDown),
+        key_label(InputContext::Menu, Command::Home), key_label(InputContext::Menu, Command::Cycle),
+        key_label(InputContext::Menu, Command::Cancel));
+    ctx.print_color(2, layout.help_row(), RGB::named(rltk::GRAY), black, &help);
+
+    web_ui::title("Map");
+    web_ui::offer(InputContext::Menu, Command::Cancel);
+    web_ui::offer_labelled(InputContext::Menu, Command::Cycle, if zoomed_out { "Zoom in" } else { "Zoom out" });
+    web_ui::offer_labelled(InputContext::Menu, Command::Home, "Find me");
+    web_ui::offer_labelled(InputContext::Menu, Command::Up, "▲");
+    web_ui::offer_labelled(InputContext::Menu, Command::Down, "▼");
+    web_ui::offer_labelled(InputContext::Menu, Command::Left, "◀");
+    web_ui::offer_labelled(InputContext::Menu, Command::Right, "▶");
+
+    //panning goes a few cells at a time, however many tiles that is
+    let step = 4 * scale;
+    let browse = |x : i32, y : i32| OverviewResult::Browse{ x, y, zoomed_out };
+    match read_command(ctx, InputContext::Menu) {
+        Some(Command::Cancel) => OverviewResult::Close,
+        Some(Command::Up) => browse(x, y - step),
+        Some(Command::Down) => browse(x, y + step),
+        Some(Command::Left) => browse(x - step, y),
+        Some(Command::Right) => browse(x + step, y),
+        Some(Command::PageUp) => browse(x, y - view_height * scale),
+        Some(Command::PageDown) => browse(x, y + view_height * scale),
+        Some(Command::Home) => {
+            let player_pos = gs.ecs.fetch::<Point>();
+            browse(player_pos.x, player_pos.y)
         }
+        Some(Command::Cycle) => OverviewResult::Browse{ x, y, zoomed_out : !zoomed_out },
+        _ => browse(x, y)
     }
 }
 
-#[derive(PartialEq, Copy, Clone)]
-pub enum ItemMenuResult { Cancel, NoResponse, Selected }
+struct Tooltip {
+    lines : Vec<(String, RGB)>
+}
 
-//menus
-pub fn show_inventory(gs : &mut State, ctx : &mut Rltk) -> (ItemMenuResult,  Option<Entity>) {
-    let player_entity = gs.ecs.fetch::<Entity>();
-    let names = gs.ecs.read_storage::<Name>();
-    let backpack = gs.ecs.read_storage::<InBackpack>();
-    let entities = gs.ecs.entities();
+impl Tooltip {
+    fn new() -> Tooltip {
+        Tooltip { lines : Vec::new() }
+    }
 
-    let inventory = (&backpack, &names).join().filter(|item| item.0.owner == *player_entity );
-    let count = inventory.count();
-
-    let mut y = (25 - (count / 2)) as i32;
-    let mut x = 15;
-    ctx.draw_box(x, y-2, 31, (count+3) as i32, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK));
-    ctx.print_color(x+3, y-2, RGB::named(rltk::CYAN), RGB::named(rltk::BLACK), "Inventory");
-    ctx.print_color(x+3, y+count as i32+1, RGB::named(rltk::CYAN), RGB::named(rltk::BLACK), "ESCAPE to cancel");
-
-    let mut equippable : Vec<Entity> = Vec::new();
-    let mut j = 0;
-    //item.1. is backpack
-    for (entity, _pack, name) in (&entities, &backpack, &names).join().filter(|item| item.1.owner == *player_entity ) {
-        //draw item letter
-        ctx.set(x+2, y, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), rltk::to_cp437('('));
-        ctx.set(x+3, y, RGB::named(rltk::CYAN), RGB::named(rltk::BLACK), 97+j as u8);
-        ctx.set(x+4, y, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), rltk::to_cp437(')'));
-
-        ctx.print(x+6, y, &name.name.to_string());
-        equippable.push(entity);
-        y += 1;
-        j += 1;
+    fn add<S:ToString>(&mut self, line : S, color : RGB) {
+        self.lines.push((line.to_string(), color));
     }
 
+    fn width(&self) -> i32 {
+        self.lines.iter().map(|(line, _)| line.chars().count() as i32).max().unwrap_or(0) + 2
+    }
 
-    // New: Handle web buttons
-    if let Some(btn) = &ctx.web_button {
-        match btn.trim() {
-            "escape" => { (ItemMenuResult::Cancel, None) },
-            "a" => {
-                //select and return
-                let selection = 0;
-                if selection > -1 && selection < count as i32 {
-                    return (ItemMenuResult::Selected, Some(equippable[selection as usize]));
-                }
-                (ItemMenuResult::NoResponse, None)
-            },
-            "b" => {
-                //select and return
-                let selection = 1;
-                if selection > -1 && selection < count as i32 {
-                    return (ItemMenuResult::Selected, Some(equippable[selection as usize]));
-                }
-                (ItemMenuResult::NoResponse, None)
-            },
-            "c" => {
-                //select and return
-                let selection = 2;
-                if selection > -1 && selection < count as i32 {
-                    return (ItemMenuResult::Selected, Some(equippable[selection as usize]));
-                }
-                (ItemMenuResult::NoResponse, None)
-            },
-            "d" => {
-                //select and return
-                let selection = 3;
-                if selection > -1 && selection < count as i32 {
-                    return (ItemMenuResult::Selected, Some(equippable[selection as usize]));
-                }
-                (ItemMenuResult::NoResponse, None)
-            },
-            _ => (ItemMenuResult::NoResponse, None),
+    fn height(&self) -> i32 { self.lines.len() as i32 + 2 }
+
+    fn render(&self, ctx : &mut Rltk, x : i32, y : i32) {
+        let box_gray : RGB = RGB::from_hex("#999999").expect("Oops");
+        let black = RGB::named(rltk::BLACK);
+        let inner = self.width() - 2;
+        draw_hollow_box(ctx, x, y, self.width()-1, self.height()-1, box_gray, black);
+        for (i, (line, color)) in self.lines.iter().enumerate() {
+            ctx.print_color(x+1, y+1+i as i32, *color, black, &format!("{:width$}", line, width = inner as usize));
         }
     }
-    else {
-        match ctx.key {
-            None => (ItemMenuResult::NoResponse, None),
-            Some(key) => {
-                match key {
-                    VirtualKeyCode::Escape => { (ItemMenuResult::Cancel, None) }
-                    _ => {
-                        //select item and return it
-                        let selection = rltk::letter_to_option(key);
-                        if selection > -1 && selection < count as i32 {
-                            return (ItemMenuResult::Selected, Some(equippable[selection as usize]));
-                        }
-                        (ItemMenuResult::NoResponse, None)
+}
+
+//how hurt something looks, without giving away the numbers
+fn health_description(pools : &Pools) -> (&'static str, RGB) {
+    let ratio = pools.hit_points.current as f32 / pools.hit_points.max as f32;
+    if ratio >= 1.0 { ("Unhurt", RGB::named(rltk::GREEN)) }
+    else if ratio > 0.66 { ("Scratched", RGB::named(rltk::YELLOW)) }
+    else if ratio > 0.33 { ("Wounded", RGB::named(rltk::ORANGE)) }
+    else { ("Near death", RGB::named(rltk::RED)) }
+}
+
+fn draw_tooltips(ecs: &World, ctx : &mut Rltk) {
+    let (min_x, _max_x, min_y, _max_y) = camera::get_screen_bounds(ecs, ctx);
+    let map = ecs.fetch::<Map>();
+    let player_entity = ecs.fetch::<Entity>();
+    let names = ecs.read_storage::<Name>();
+    let pools = ecs.read_storage::<Pools>();
+    let factions = ecs.read_storage::<Faction>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let confusion = ecs.read_storage::<Confusion>();
+    let immobilized = ecs.read_storage::<Immobilized>();
+    let hidden = ecs.read_storage::<Hidden>();
+
+    let mouse_pos = ctx.mouse_pos();
+    let layout = layout(ctx);
+    if !layout.in_view(mouse_pos.0, mouse_pos.1) { return; }
+    let (map_x, map_y) = (mouse_pos.0 + min_x, mouse_pos.1 + min_y);
+    if map_x < 1 || map_x >= map.width-1 || map_y < 1 || map_y >= map.height-1 { return; }
+    let idx = map.xy_idx(map_x, map_y);
+    if !map.visible_tiles[idx] { return; }
+
+    let mut tips : Vec<Tooltip> = Vec::new();
+    let raws = RAWS.lock().unwrap();
+    for entity in map.tile_content[idx].iter() {
+        if hidden.get(*entity).is_some() { continue; }
+        if let Some(name) = names.get(*entity) {
+            let mut tip = Tooltip::new();
+            tip.add(&name.name, RGB::named(rltk::WHITE));
+
+            if let Some(pools) = pools.get(*entity) {
+                let (health, color) = health_description(pools);
+                tip.add(health, color);
+            }
+            if let Some(faction) = factions.get(*entity) {
+                if *entity != *player_entity {
+                    match faction_reaction(&faction.name, "Player", &raws) {
+                        Reaction::Attack => tip.add("Hostile", RGB::named(rltk::RED)),
+                        Reaction::Flee => tip.add("Afraid of you", RGB::named(rltk::YELLOW)),
+                        Reaction::Ignore => tip.add("Indifferent", RGB::named(rltk::GRAY))
                     }
                 }
             }
+            for (eq, weapon_name) in (&equipped, &names).join() {
+                if eq.owner == *entity && eq.slot == EquipmentSlot::Melee {
+                    tip.add(format!("Wielding {}", weapon_name.name), RGB::named(rltk::LIGHT_BLUE));
+                }
+            }
+            if let Some(confused) = confusion.get(*entity) {
+                tip.add(format!("Confused ({})", confused.turns), RGB::named(rltk::MAGENTA));
+            }
+            if let Some(held) = immobilized.get(*entity) {
+                tip.add(format!("Held fast ({})", held.turns), RGB::named(rltk::MAGENTA));
+            }
+            tips.push(tip);
         }
     }
+    if tips.is_empty() { return; }
+
+    //to the right of the cursor if it fits, otherwise to the left; nudged up so it stays in the map box
+    let width = tips.iter().map(|t| t.width()).max().unwrap_or(0);
+    let height : i32 = tips.iter().map(|t| t.height()).sum();
+    let (view_width, view_height) = layout.view_size();
+    let (x, arrow_x, arrow) = if mouse_pos.0 + 2 + width <= view_width + 1 {
+        (mouse_pos.0 + 2, mouse_pos.0 + 1, '←')
+    } else {
+        (i32::max(1, mouse_pos.0 - 1 - width), mouse_pos.0 - 1, '→')
+    };
+    let mut y = i32::max(1, i32::min(mouse_pos.1, view_height + 1 - height));
+
+    ctx.set_bg(mouse_pos.0, mouse_pos.1, RGB::named(rltk::DARK_GRAY));
+    ctx.set(arrow_x, mouse_pos.1, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), rltk::to_cp437(arrow));
+    for tip in tips.iter() {
+        tip.render(ctx, x, y);
+        y += tip.height();
+    }
+}
 
+#[derive(PartialEq, Copy, Clone)]
+pub enum ItemMenuResult { Cancel, NoResponse, Selected }
+
+//the list menu's view of a carried item
+fn item_category(ecs : &World, item : Entity) -> ItemCategory {
+    if ecs.read_storage::<MeleeWeapon>().get(item).is_some() || ecs.read_storage::<Charges>().get(item).is_some() { ItemCategory::Weapons }
+    else if ecs.read_storage::<Equippable>().get(item).is_some() { ItemCategory::Armor }
+    else if ecs.read_storage::<Consumable>().get(item).is_some() { ItemCategory::Consumables }
+    else { ItemCategory::Other }
 }
 
-pub fn drop_item_menu(gs : &mut State, ctx : &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
+fn item_row(ecs : &World, entity : Entity, name : String) -> MenuRow<Entity> {
+    let items = ecs.read_storage::<Item>();
+    let item = items.get(entity);
+    MenuRow{
+        value : entity,
+        name,
+        summary : item_summary(ecs, entity),
+        extra : item.map_or(String::new(), |i| format!("{:.1} lbs", i.weight_lbs)),
+        weight : item.map_or(0.0, |i| i.weight_lbs),
+        cost : item.map_or(0.0, |i| i.base_value),
+        category : item_category(ecs, entity)
+    }
+}
+
+//what the player is carrying
+fn backpack_rows(ecs : &World) -> Vec<MenuRow<Entity>> {
+    let player_entity = ecs.fetch::<Entity>();
+    let names = ecs.read_storage::<Name>();
+    let backpack = ecs.read_storage::<InBackpack>();
+    let entities = ecs.entities();
+    let stacks = ecs.read_storage::<Stackable>();
+
+    (&entities, &backpack, &names).join()
+        .filter(|(_, pack, _)| pack.owner == *player_entity)
+        .map(|(entity, _, name)| item_row(ecs, entity, item_display_name(name, stacks.get(entity))))
+        .collect()
+}
+
+//what the player has on
+fn equipped_rows(ecs : &World) -> Vec<MenuRow<Entity>> {
+    let player_entity = ecs.fetch::<Entity>();
+    let names = ecs.read_storage::<Name>();
+    let equipped = ecs.read_storage::<Equipped>();
+    let entities = ecs.entities();
+
+    (&entities, &equipped, &names).join()
+        .filter(|(_, worn, _)| worn.owner == *player_entity)
+        .map(|(entity, _, name)| item_row(ecs, entity, name.name.to_string()))
+        .collect()
+}
+
+//menus
+//using, dropping and throwing all pick from the same list; only the title differs
+pub fn backpack_menu(gs : &mut State, ctx : &mut Rltk, title : &str, menu : ListMenu) -> ListMenuResult<Entity> {
+    list_menu(ctx, title, "ESCAPE to cancel", menu, backpack_rows(&gs.ecs))
+}
+
+pub fn remove_item_menu(gs : &mut State, ctx : &mut Rltk, menu : ListMenu) -> ListMenuResult<Entity> {
+    list_menu(ctx, "Remove Which Item?", "ESCAPE to cancel", menu, equipped_rows(&gs.ecs))
+}
+
+//opened from the character sheet, so it only lists what can be worn or wielded
+pub fn equip_item_menu(gs : &mut State, ctx : &mut Rltk, menu : ListMenu) -> ListMenuResult<Entity> {
+    let equippable = gs.ecs.read_storage::<Equippable>();
+    let rows = backpack_rows(&gs.ecs).into_iter().filter(|row| equippable.get(row.value).is_some()).collect();
+    list_menu(ctx, "Put On Which Item?", "ESCAPE to go back", menu, rows)
+}
+
+//targeting
+//the paperdoll, top to bottom
+const EQUIPMENT_SLOTS : [(EquipmentSlot, &str); 7] = [
+    (EquipmentSlot::Melee, "Melee"),
+    (EquipmentSlot::Shield, "Shield"),
+    (EquipmentSlot::Head, "Head"),
+    (EquipmentSlot::Torso, "Torso"),
+    (EquipmentSlot::Legs, "Legs"),
+    (EquipmentSlot::Feet, "Feet"),
+    (EquipmentSlot::Hands, "Hands"),
+];
+
+fn slot_label(slot : EquipmentSlot) -> &'static str {
+    EQUIPMENT_SLOTS.iter().find(|(s, _)| *s == slot).map_or("?", |(_, label)| *label)
+}
+
+fn signed(n : i32) -> String {
+    if n < 0 { format!("{}", n) } else { format!("+{}", n) }
+}
+
+//the character sheet's right hand column, as wide as the attributes header
+const SHEET_NUMBERS_WIDTH : i32 = 35;
+//room for a worn item's damage or defense, like "+1.5 def"
+const SHEET_STAT_WIDTH : i32 = 8;
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum CharacterSheetResult { Cancel, NoResponse, PutOn, Unequip { item : Entity } }
+
+pub fn character_sheet(gs : &mut State, ctx : &mut Rltk) -> CharacterSheetResult {
     let player_entity = gs.ecs.fetch::<Entity>();
     let names = gs.ecs.read_storage::<Name>();
-    let backpack = gs.ecs.read_storage::<InBackpack>();
     let entities = gs.ecs.entities();
+    let equipped = gs.ecs.read_storage::<Equipped>();
+    let equippable = gs.ecs.read_storage::<Equippable>();
+    let backpack = gs.ecs.read_storage::<InBackpack>();
+    let melee_weapons = gs.ecs.read_storage::<MeleeWeapon>();
+    let defense_bonuses = gs.ecs.read_storage::<DefenseBonus>();
+    let pools = gs.ecs.read_storage::<Pools>();
+    let attributes = gs.ecs.read_storage::<Attributes>();
+    let initiatives = gs.ecs.read_storage::<Initiative>();
+    let attr = attributes.get(*player_entity).unwrap();
+    let player_pools = pools.get(*player_entity).unwrap();
 
-    let inventory = (&backpack, &names).join().filter(|item| item.0.owner == *player_entity );
-    let count = inventory.count();
+    let white = RGB::named(rltk::WHITE);
+    let black = RGB::named(rltk::BLACK);
+    let gray = RGB::named(rltk::GRAY);
+    let cyan = RGB::named(rltk::CYAN);
+    ctx.cls();
+    let layout = layout(ctx);
+    let screen = layout.screen();
+    draw_hollow_box(ctx, screen.x, screen.y, screen.width, screen.height, RGB::from_hex("#999999").expect("Oops"), black);
+    ctx.print_color(2, 1, RGB::named(rltk::YELLOW), black, "Character Sheet");
+    ctx.print_color(2, layout.help_row(), gray, black, &format!("Letter: take off   {}: put something on   {}: close",
+        key_label(InputContext::Menu, Command::SwitchMode), key_label(InputContext::Menu, Command::Cancel)));
+
+    //the numbers go against the right hand border, the stats of worn things just left of them
+    let x = screen.x + screen.width - SHEET_NUMBERS_WIDTH - 1;
+    let stat_x = x - SHEET_STAT_WIDTH - 2;
+    let fit = |text : &str, from : i32| -> String { text.chars().take(i32::max(0, stat_x - 1 - from) as usize).collect() };
+
+    //what can be picked: the slots first (taking things off), then whatever could go on
+    let mut options : Vec<Option<Entity>> = Vec::new();
+    let draw_option = |ctx : &mut Rltk, n : usize, y : i32| {
+        ctx.set(2, y, white, black, rltk::to_cp437('('));
+        ctx.set(3, y, cyan, black, 97 + n as u8);
+        ctx.set(4, y, white, black, rltk::to_cp437(')'));
+    };
+
+    ctx.print_color(2, 3, cyan, black, "Equipment");
+    let mut y = 4;
+    for (slot, label) in EQUIPMENT_SLOTS.iter() {
+        let worn = (&entities, &equipped).join().find(|(_, e)| e.owner == *player_entity && e.slot == *slot).map(|(e, _)| e);
+        draw_option(ctx, options.len(), y);
+        ctx.print_color(6, y, gray, black, &format!("{:<7}", label));
+        match worn {
+            None => ctx.print_color(14, y, gray, black, "-"),
+            Some(item) => {
+                let item_name = names.get(item).map_or("?".to_string(), |n| n.name.to_string());
+                ctx.print_color(14, y, white, black, &fit(&item_name, 14));
+                web_ui::option(options.len(), &format!("Take off {}", item_name));
+                if let Some(weapon) = melee_weapons.get(item) {
+                    ctx.print_color(stat_x, y, RGB::named(rltk::RED), black, &damage_label(weapon));
+                }
+                if let Some(defense) = defense_bonuses.get(item) {
+                    ctx.print_color(stat_x, y, RGB::named(rltk::LIGHT_BLUE), black, &format!("{:+.1} def", defense.defense));
+                }
+            }
+        }
+        options.push(worn);
+        y += 1;
+    }
 
-    let mut y = (25 - (count / 2)) as i32;
-    ctx.draw_box(15, y-2, 31, (count+3) as i32, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK));
-    ctx.print_color(18, y-2, RGB::named(rltk::CYAN), RGB::named(rltk::BLACK), "Drop Which Item?");
-    ctx.print_color(18, y+count as i32+1, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), "ESCAPE to cancel");
+    //putting things on goes through the list menu; this is just what's there to choose from
+    y += 1;
+    ctx.print_color(2, y, cyan, black, "Carried equipment");
+    y += 1;
+    let spare : Vec<(Entity, &Equippable)> = (&entities, &backpack, &equippable).join()
+        .filter(|(_, pack, _)| pack.owner == *player_entity)
+        .map(|(entity, _, equip)| (entity, equip))
+        .collect();
+    let room = i32::max(1, layout.help_row() - 1 - y) as usize;
+    for (i, (entity, equip)) in spare.iter().enumerate() {
+        if i + 1 == room && spare.len() > room {
+            ctx.print_color(6, y, gray, black, &format!("...and {} more", spare.len() - i));
+            break;
+        }
+        let item_name = names.get(*entity).map_or("?".to_string(), |n| n.name.to_string());
+        ctx.print_color(6, y, white, black, &fit(&item_name, 6));
+        ctx.print_color(stat_x, y, gray, black, slot_label(equip.slot));
+        y += 1;
+    }
+    if spare.is_empty() {
+        ctx.print_color(6, y, gray, black, "nothing");
+    }
 
-    let mut equippable : Vec<Entity> = Vec::new();
-    let mut j = 0;
-    for (entity, _pack, name) in (&entities, &backpack, &names).join().filter(|item| item.1.owner == *player_entity ) {
-        ctx.set(17, y, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), rltk::to_cp437('('));
-        ctx.set(18, y, RGB::named(rltk::CYAN), RGB::named(rltk::BLACK), 97+j as u8);
-        ctx.set(19, y, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), rltk::to_cp437(')'));
+    //numbers, on the right
+    ctx.print_color(x, 3, cyan, black, "Attributes   Base  Mods Total Bonus");
+    let rows = [("STR", &attr.strength), ("DEX", &attr.dexterity), ("CON", &attr.constitution),
+        ("INT", &attr.intelligence), ("WIS", &attr.wisdom), ("CHA", &attr.charisma)];
+    for (i, (label, a)) in rows.iter().enumerate() {
+        let y = 4 + i as i32;
+        ctx.print_color(x, y, gray, black, label);
+        ctx.print_color(x+13, y, white, black, &format!("{:>4}  {:>4}  {:>4}  {:>4}", a.base, signed(a.modifiers), a.base + a.modifiers, signed(a.bonus)));
+    }
 
-        ctx.print(21, y, &name.name.to_string());
-        equippable.push(entity);
-        y += 1;
-        j += 1;
-    }
-
-    match ctx.key {
-        None => (ItemMenuResult::NoResponse, None),
-        Some(key) => {
-            match key {
-                VirtualKeyCode::Escape => { (ItemMenuResult::Cancel, None) }
-                _ => { 
-                    let selection = rltk::letter_to_option(key);
-                    if selection > -1 && selection < count as i32 {
-                        return (ItemMenuResult::Selected, Some(equippable[selection as usize]));
-                    }  
-                    (ItemMenuResult::NoResponse, None)
-                }
+    //the same numbers melee combat uses
+    let weapon = (&equipped, &melee_weapons).join()
+        .find(|(e, _)| e.owner == *player_entity && e.slot == EquipmentSlot::Melee)
+        .map_or(unarmed(), |(_, w)| w.clone());
+    let defense : f32 = (&equipped, &defense_bonuses).join().filter(|(e, _)| e.owner == *player_entity).map(|(_, d)| d.defense).sum();
+    let capacity = carry_capacity_lbs(attr);
+    let (initiative_min, initiative_max) = initiative_range(attr);
+
+    ctx.print_color(x, 11, cyan, black, "Combat");
+    ctx.print_color(x, 12, white, black, &format!("Damage     {} {} STR", damage_label(&weapon), signed(attr.strength.bonus)));
+    ctx.print_color(x, 13, white, black, &format!("Hit chance {}% (d100 under {})", melee_hit_chance(attr), MELEE_HIT_TARGET));
+    ctx.print_color(x, 14, white, black, &format!("Defense    {} (from items)", defense as i32));
+    ctx.print_color(x, 15, white, black, &format!("Initiative {}-{} turns", initiative_min, initiative_max));
+    if let Some(initiative) = initiatives.get(*player_entity) {
+        ctx.print_color(x, 16, gray, black, &format!("           next turn in {}", initiative.current));
+    }
+
+    ctx.print_color(x, 18, cyan, black, "Burden");
+    let burden_color = if player_pools.total_weight as i32 > capacity { RGB::named(rltk::RED) } else { white };
+    ctx.print_color(x, 19, burden_color, black, &format!("Carrying   {:.0} / {} lbs", player_pools.total_weight, capacity));
+
+    web_ui::title("Character Sheet");
+    web_ui::offer(InputContext::Menu, Command::Cancel);
+    if !spare.is_empty() {
+        web_ui::offer_labelled(InputContext::Menu, Command::SwitchMode, "Put on...");
+    }
+    match read_command(ctx, InputContext::Menu) {
+        Some(Command::Cancel) => CharacterSheetResult::Cancel,
+        Some(Command::SwitchMode) if !spare.is_empty() => CharacterSheetResult::PutOn,
+        Some(Command::Choose(selection)) if selection >= 0 && (selection as usize) < options.len() => {
+            match options[selection as usize] {
+                Some(item) => CharacterSheetResult::Unequip{ item },
+                None => CharacterSheetResult::NoResponse
             }
         }
+        _ => CharacterSheetResult::NoResponse
     }
 }
 
-pub fn remove_item_menu(gs : &mut State, ctx : &mut Rltk) -> (ItemMenuResult, Option<Entity>) {
-    let player_entity = gs.ecs.fetch::<Entity>();
-    let names = gs.ecs.read_storage::<Name>();
-    let backpack = gs.ecs.read_storage::<Equipped>();
-    let entities = gs.ecs.entities();
+fn average_damage(weapon : &MeleeWeapon) -> f32 {
+    weapon.damage_n_dice as f32 * (weapon.damage_die_type as f32 + 1.0) / 2.0 + weapon.damage_bonus as f32
+}
 
-    let inventory = (&backpack, &names).join().filter(|item| item.0.owner == *player_entity );
-    let count = inventory.count();
+//the menu summary for an item already spawned
+fn item_summary(ecs : &World, item : Entity) -> String {
+    summarize_item(ecs.read_storage::<MeleeWeapon>().get(item), ecs.read_storage::<DefenseBonus>().get(item).map(|d| d.defense))
+}
 
-    let mut y = (25 - (count / 2)) as i32;
-    ctx.draw_box(15, y-2, 31, (count+3) as i32, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK));
-    ctx.print_color(18, y-2, RGB::named(rltk::CYAN), RGB::named(rltk::BLACK), "Remove Which Item?");
-    ctx.print_color(18, y+count as i32+1, RGB::named(rltk::YELLOW), RGB::named(rltk::BLACK), "ESCAPE to cancel");
+//everything the components say about an item, a line each
+fn item_details(ecs : &World, item : Entity) -> Vec<(String, RGB)> {
+    let white = RGB::named(rltk::WHITE);
+    let mut lines : Vec<(String, RGB)> = Vec::new();
 
-    let mut equippable : Vec<Entity> = Vec::new();
-    let mut j = 0;
-    for (entity, _pack, name) in (&entities, &backpack, &names).join().filter(|item| item.1.owner == *player_entity ) {
-        ctx.set(17, y, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), rltk::to_cp437('('));
-        ctx.set(18, y, RGB::named(rltk::CYAN), RGB::named(rltk::BLACK), 97+j as u8);
-        ctx.set(19, y, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), rltk::to_cp437(')'));
+    if let Some(equip) = ecs.read_storage::<Equippable>().get(item) {
+        lines.push((format!("Worn on: {}", slot_label(equip.slot)), RGB::named(rltk::CYAN)));
+    }
+    if let Some(weapon) = ecs.read_storage::<MeleeWeapon>().get(item) {
+        lines.push((format!("Damage: {} (avg {:.1})", damage_label(weapon), average_damage(weapon)), RGB::named(rltk::RED)));
+    }
+    if let Some(defense) = ecs.read_storage::<DefenseBonus>().get(item) {
+        lines.push((format!("Defense: {:+.1}", defense.defense), RGB::named(rltk::LIGHT_BLUE)));
+    }
+    if ecs.read_storage::<Consumable>().get(item).is_some() {
+        lines.push(("Used up when used".to_string(), white));
+    }
+    if let Some(charges) = ecs.read_storage::<Charges>().get(item) {
+        lines.push((format!("Loaded: {}/{}, reloads with {}", charges.current, charges.max, charges.ammo), white));
+    }
+    if let Some(med) = ecs.read_storage::<MedItem>().get(item) {
+        lines.push((format!("Heals {} hp", med.heal_amount), RGB::named(rltk::GREEN)));
+    }
+    if ecs.read_storage::<ProvidesFood>().get(item).is_some() {
+        lines.push(("Food".to_string(), RGB::named(rltk::GREEN)));
+    }
+    if ecs.read_storage::<ProvidesQuench>().get(item).is_some() {
+        lines.push(("Drink".to_string(), RGB::named(rltk::GREEN)));
+    }
+    if let Some(damage) = ecs.read_storage::<InflictsDamage>().get(item) {
+        lines.push((format!("Deals {} damage", damage.damage), RGB::named(rltk::RED)));
+    }
+    if let Some(ranged) = ecs.read_storage::<Ranged>().get(item) {
+        lines.push((format!("Range: {}", ranged.range), white));
+    }
+    if let Some(aoe) = ecs.read_storage::<AreaOfEffect>().get(item) {
+        lines.push((format!("Area: {:?}, radius {}", aoe.shape, aoe.radius), white));
+    }
+    if ecs.read_storage::<DamageFalloff>().get(item).is_some() {
+        lines.push(("Weaker away from the centre".to_string(), white));
+    }
+    if ecs.read_storage::<SparesAllies>().get(item).is_some() {
+        lines.push(("Spares you and your friends".to_string(), white));
+    }
+    if let Some(confusion) = ecs.read_storage::<Confusion>().get(item) {
+        lines.push((format!("Confuses for {} turns", confusion.turns), RGB::named(rltk::MAGENTA)));
+    }
+    if let Some(held) = ecs.read_storage::<Immobilized>().get(item) {
+        lines.push((format!("Holds fast for {} turns", held.turns), RGB::named(rltk::MAGENTA)));
+    }
+    if let Some(teleport) = ecs.read_storage::<Teleports>().get(item) {
+        lines.push((format!("Teleports up to {} tiles", teleport.range), white));
+    }
+    if let Some(spawns) = ecs.read_storage::<SpawnsEntity>().get(item) {
+        lines.push((format!("Sets up a {}", spawns.name), white));
+    }
+    if ecs.read_storage::<Fragile>().get(item).is_some() {
+        lines.push(("Breaks when thrown".to_string(), white));
+    }
+    if ecs.read_storage::<Lockpick>().get(item).is_some() {
+        lines.push(("Opens locked doors".to_string(), white));
+    }
+    if let Some(stack) = ecs.read_storage::<Stackable>().get(item) {
+        lines.push((format!("Stack of {}", stack.quantity), white));
+    }
+    if let Some(i) = ecs.read_storage::<Item>().get(item) {
+        lines.push((format!("Weight: {:.1} lbs", i.weight_lbs), white));
+        lines.push((format!("Value: {:.1} cr", i.base_value), white));
+    }
+    lines
+}
 
-        ctx.print(21, y, &name.name.to_string());
-        equippable.push(entity);
-        y += 1;
-        j += 1;
-    }
-
-    match ctx.key {
-        None => (ItemMenuResult::NoResponse, None),
-        Some(key) => {
-            match key {
-                VirtualKeyCode::Escape => { (ItemMenuResult::Cancel, None) }
-                _ => { 
-                    let selection = rltk::letter_to_option(key);
-                    if selection > -1 && selection < count as i32 {
-                        return (ItemMenuResult::Selected, Some(equippable[selection as usize]));
-                    }  
-                    (ItemMenuResult::NoResponse, None)
+//green if the new one is better, red if it's worse
+fn difference_line(label : &str, new : f32, old : f32, higher_is_better : bool) -> (String, RGB) {
+    let diff = new - old;
+    let color = if diff.abs() < 0.05 { RGB::named(rltk::GRAY) }
+        else if (diff > 0.0) == higher_is_better { RGB::named(rltk::GREEN) }
+        else { RGB::named(rltk::RED) };
+    (format!("{}: {:.1} vs {:.1} ({:+.1})", label, new, old, diff), color)
+}
+
+fn compare_items(ecs : &World, item : Entity, worn : Entity) -> Vec<(String, RGB)> {
+    let weapons = ecs.read_storage::<MeleeWeapon>();
+    let defenses = ecs.read_storage::<DefenseBonus>();
+    let items = ecs.read_storage::<Item>();
+    let mut lines = Vec::new();
+    if weapons.get(item).is_some() || weapons.get(worn).is_some() {
+        let avg = |e : Entity| weapons.get(e).map_or(0.0, average_damage);
+        lines.push(difference_line("Avg damage", avg(item), avg(worn), true));
+    }
+    if defenses.get(item).is_some() || defenses.get(worn).is_some() {
+        let def = |e : Entity| defenses.get(e).map_or(0.0, |d| d.defense);
+        lines.push(difference_line("Defense", def(item), def(worn), true));
+    }
+    let weight = |e : Entity| items.get(e).map_or(0.0, |i| i.weight_lbs);
+    lines.push(difference_line("Weight", weight(item), weight(worn), false));
+    lines
+}
+
+//anything carried or worn
+pub fn examine_menu(gs : &mut State, ctx : &mut Rltk, menu : ListMenu) -> ListMenuResult<Entity> {
+    let mut rows = backpack_rows(&gs.ecs);
+    for mut row in equipped_rows(&gs.ecs) {
+        row.name = format!("{} (worn)", row.name);
+        rows.push(row);
+    }
+    list_menu(ctx, "Examine Which Item?", "ESCAPE to cancel", menu, rows)
+}
+
+//the item on the left, and what it would replace on the right
+//the examine box at its roomiest
+const EXAMINE_WIDTH : i32 = 69;
+const EXAMINE_HEIGHT : i32 = 30;
+
+pub fn examine_item(gs : &mut State, ctx : &mut Rltk, item : Entity) -> ItemMenuResult {
+    let player_entity = *gs.ecs.fetch::<Entity>();
+    let black = RGB::named(rltk::BLACK);
+    let item_name = gs.ecs.read_storage::<Name>().get(item).map_or("?".to_string(), |n| n.name.to_string());
+
+    //as big as it used to be, but never bigger than the screen; the comparison takes the right half
+    let screen = layout(ctx).screen();
+    let (width, height) = (i32::min(EXAMINE_WIDTH, screen.width - 2), i32::min(EXAMINE_HEIGHT, screen.height - 2));
+    let x = screen.x + (screen.width - width) / 2;
+    let y = screen.y + i32::max(1, i32::min(screen.height / 6, screen.height - height - 1));
+    let column = x + width / 2 + 1;
+    let clip = |text : &str| -> String { text.chars().take(i32::max(0, width / 2 - 3) as usize).collect() };
+    ctx.draw_box(x, y, width, height, RGB::named(rltk::WHITE), black);
+    ctx.print_color(x + 3, y, RGB::named(rltk::CYAN), black, &item_name);
+    ctx.print_color(x + 3, y + height, RGB::named(rltk::CYAN), black, &format!("{} to go back", key_label(InputContext::Menu, Command::Cancel)));
+    for (i, (line, color)) in item_details(&gs.ecs, item).iter().enumerate().take(i32::max(0, height - 3) as usize) {
+        ctx.print_color(x + 2, y + 2 + i as i32, *color, black, &clip(line));
+    }
+
+    //only worth comparing with something else in the same slot
+    let slot = gs.ecs.read_storage::<Equippable>().get(item).map(|e| e.slot);
+    if let Some(slot) = slot {
+        let worn = {
+            let entities = gs.ecs.entities();
+            let equipped = gs.ecs.read_storage::<Equipped>();
+            (&entities, &equipped).join().find(|(e, eq)| eq.owner == player_entity && eq.slot == slot && *e != item).map(|(e, _)| e)
+        };
+        match worn {
+            None => ctx.print_color(column, y + 2, RGB::named(rltk::GRAY), black, &clip(&format!("Nothing worn on {}", slot_label(slot)))),
+            Some(worn) => {
+                let worn_name = gs.ecs.read_storage::<Name>().get(worn).map_or("?".to_string(), |n| n.name.to_string());
+                ctx.print_color(column, y + 2, RGB::named(rltk::GRAY), black, &clip(&format!("Instead of {}", worn_name)));
+                for (i, (line, color)) in compare_items(&gs.ecs, item, worn).iter().enumerate().take(i32::max(0, height - 5) as usize) {
+                    ctx.print_color(column, y + 4 + i as i32, *color, black, &clip(line));
                 }
             }
         }
     }
+
+    web_ui::title(&item_name);
+    web_ui::offer_labelled(InputContext::Menu, Command::Cancel, "Back");
+    match read_command(ctx, InputContext::Menu) {
+        Some(Command::Cancel) => ItemMenuResult::Cancel,
+        _ => ItemMenuResult::NoResponse
+    }
 }
 
-//targeting
-pub fn ranged_target(gs : &mut State, ctx : &mut Rltk, range : i32) -> (ItemMenuResult, Option<Point>) {
+//thrown items only hit the tile they land on, so throwing skips the blast preview
+pub fn ranged_target(gs : &mut State, ctx : &mut Rltk, range : i32, item : Entity, show_area : bool) -> (ItemMenuResult, Option<Point>) {
     let (min_x, max_x, min_y, max_y) = camera::get_screen_bounds(&gs.ecs, ctx);
     let player_entity = gs.ecs.fetch::<Entity>();
     let player_pos = gs.ecs.fetch::<Point>();
@@ -478,6 +1058,32 @@ pub fn ranged_target(gs : &mut State, ctx : &mut Rltk, range : i32) -> (ItemMenu
 
     let mut valid_target = false;
     for idx in available_cells.iter() { if idx.x == mouse_map_pos.0 && idx.y == mouse_map_pos.1 { valid_target = true; } }
+
+    // Preview what an area effect would cover
+    if valid_target && show_area {
+        if let Some(aoe) = gs.ecs.read_storage::<AreaOfEffect>().get(item) {
+            let map = gs.ecs.fetch::<Map>();
+            let player_idx = map.xy_idx(player_pos.x, player_pos.y) as i32;
+            let tiles = aoe_tiles(&*map, *player_pos, Point::new(mouse_map_pos.0, mouse_map_pos.1), aoe);
+            for idx in tiles.iter() {
+                let screen_x = (idx % map.width) - min_x;
+                let screen_y = (idx / map.width) - min_y;
+                if screen_x > 0 && screen_x < (max_x - min_x)-1 && screen_y > 0 && screen_y < (max_y - min_y)-1 {
+                    ctx.set_bg(screen_x, screen_y, RGB::named(rltk::ORANGE));
+                }
+            }
+            if tiles.contains(&player_idx) && gs.ecs.read_storage::<SparesAllies>().get(item).is_none() {
+                ctx.print_color(5, 2, RGB::named(rltk::RED), RGB::named(rltk::BLACK), "You are in the blast!");
+            }
+        }
+    }
+
+    web_ui::title("Select Target: tap the map");
+    web_ui::offer(InputContext::Menu, Command::Cancel);
+    if read_command(ctx, InputContext::Menu) == Some(Command::Cancel) {
+        return (ItemMenuResult::Cancel, None);
+    }
+
     if valid_target {
         ctx.set_bg(mouse_pos.0, mouse_pos.1, RGB::named(rltk::CYAN));
         if ctx.left_click {
@@ -494,184 +1100,61 @@ pub fn ranged_target(gs : &mut State, ctx : &mut Rltk, range : i32) -> (ItemMenu
 }
 
 #[derive(PartialEq, Copy, Clone)]
-pub enum VendorResult { NoResponse, Cancel, Sell, BuyMode, SellMode, Buy }
+pub enum VendorResult { NoResponse, Cancel, Sell, BuyMode, SellMode, Buy, Browse { menu : ListMenu } }
 
-fn vendor_sell_menu(gs : &mut State, ctx : &mut Rltk, _vendor : Entity, _mode : VendorMode) -> (VendorResult, Option<Entity>, Option<String>, Option<f32>) {
-    let player_entity = gs.ecs.fetch::<Entity>();
-    let names = gs.ecs.read_storage::<Name>();
-    let backpack = gs.ecs.read_storage::<InBackpack>();
-    let items = gs.ecs.read_storage::<Item>();
-    let entities = gs.ecs.entities();
-
-    let inventory = (&backpack, &names).join().filter(|item| item.0.owner == *player_entity );
-    let count = inventory.count();
-
-    let mut y = (25 - (count / 2)) as i32;
-    ctx.draw_box(15, y-2, 51, (count+3) as i32, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK));
-    ctx.print_color(18, y-2, RGB::named(rltk::CYAN), RGB::named(rltk::BLACK), "Sell Which Item? (tab to switch to buy mode)");
-    ctx.print_color(18, y+count as i32+1, RGB::named(rltk::CYAN), RGB::named(rltk::BLACK), "ESCAPE to cancel");
+//tab flips between buying and selling, before the list gets a look at the input
+fn vendor_tab_pressed(ctx : &Rltk, other_mode : &str) -> bool {
+    web_ui::offer_labelled(InputContext::Menu, Command::SwitchMode, other_mode);
+    read_command(ctx, InputContext::Menu) == Some(Command::SwitchMode)
+}
 
-    let mut equippable : Vec<Entity> = Vec::new();
-    let mut j = 0;
-    for (entity, _pack, name, item) in (&entities, &backpack, &names, &items).join().filter(|item| item.1.owner == *player_entity ) {
-        ctx.set(17, y, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), rltk::to_cp437('('));
-        ctx.set(18, y, RGB::named(rltk::CYAN), RGB::named(rltk::BLACK), 97+j as u8);
-        ctx.set(19, y, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), rltk::to_cp437(')'));
+fn vendor_sell_menu(gs : &mut State, ctx : &mut Rltk, _vendor : Entity, menu : ListMenu) -> (VendorResult, Option<Entity>, Option<String>, Option<f32>) {
+    if vendor_tab_pressed(ctx, "Buy") { return (VendorResult::BuyMode, None, None, None); }
 
-        ctx.print(21, y, &name.name.to_string());
-        ctx.print(50, y, &format!("{:.1} gp", item.base_value * 0.8));
-        equippable.push(entity);
-        y += 1;
-        j += 1;
-    }
-
-    // New: Handle web buttons
-    if let Some(btn) = &ctx.web_button {
-        match btn.trim() {
-            "escape" => { (VendorResult::Cancel, None, None, None) },
-            "a" => {
-                //select and return
-                let selection = 0;
-                if selection > -1 && selection < count as i32 {
-                    return (VendorResult::Sell, Some(equippable[selection as usize]), None, None);
-                }
-                (VendorResult::NoResponse, None, None, None)
-            },
-            "b" => {
-                //select and return
-                let selection = 1;
-                if selection > -1 && selection < count as i32 {
-                    return (VendorResult::Sell, Some(equippable[selection as usize]), None, None);
-                }
-                (VendorResult::NoResponse, None, None, None)
-            },
-            "c" => {
-                //select and return
-                let selection = 2;
-                if selection > -1 && selection < count as i32 {
-                    return (VendorResult::Sell, Some(equippable[selection as usize]), None, None);
-                }
-                (VendorResult::NoResponse, None, None, None)
-            },
-            "d" => {
-                //select and return
-                let selection = 3;
-                if selection > -1 && selection < count as i32 {
-                    return (VendorResult::Sell, Some(equippable[selection as usize]), None, None);
-                }
-                (VendorResult::NoResponse, None, None, None)
-            },
-            "tab" => { (VendorResult::BuyMode, None, None, None) }
-            _ => (VendorResult::NoResponse, None, None, None),
-        }
+    let mut rows = backpack_rows(&gs.ecs);
+    for row in rows.iter_mut() {
+        row.extra = format!("{:.1} gp ea", row.cost * 0.8);
     }
-    else {
-        match ctx.key {
-            None => (VendorResult::NoResponse, None, None, None),
-            Some(key) => {
-                match key {
-                    VirtualKeyCode::Tab => { (VendorResult::BuyMode, None, None, None) }
-                    VirtualKeyCode::Escape => { (VendorResult::Cancel, None, None, None) }
-                    _ => { 
-                        let selection = rltk::letter_to_option(key);
-                        if selection > -1 && selection < count as i32 {
-                            return (VendorResult::Sell, Some(equippable[selection as usize]), None, None);
-                        }
-                        (VendorResult::NoResponse, None, None, None)
-                    }
-                }
-            }
-        }
+    match list_menu(ctx, "Sell Which Item? (tab to switch to buy mode)", "ESCAPE to cancel", menu, rows) {
+        ListMenuResult::Cancel => (VendorResult::Cancel, None, None, None),
+        ListMenuResult::NoResponse => (VendorResult::NoResponse, None, None, None),
+        ListMenuResult::Browse(menu) => (VendorResult::Browse{ menu }, None, None, None),
+        ListMenuResult::Selected(item) => (VendorResult::Sell, Some(item), None, None)
     }
 }
 
-fn vendor_buy_menu(gs : &mut State, ctx : &mut Rltk, vendor : Entity, _mode : VendorMode) -> (VendorResult, Option<Entity>, Option<String>, Option<f32>) {
+fn vendor_buy_menu(gs : &mut State, ctx : &mut Rltk, vendor : Entity, menu : ListMenu) -> (VendorResult, Option<Entity>, Option<String>, Option<f32>) {
     use crate::raws::*;
 
-    let vendors = gs.ecs.read_storage::<Vendor>();
-
-    let inventory = crate::raws::get_vendor_items(&vendors.get(vendor).unwrap().categories, &RAWS.lock().unwrap());
-    let count = inventory.len();
-
-    let mut y = (25 - (count / 2)) as i32;
-    ctx.draw_box(15, y-2, 51, (count+3) as i32, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK));
-    ctx.print_color(18, y-2, RGB::named(rltk::CYAN), RGB::named(rltk::BLACK), "Buy Which Item? (tab to switch to sell mode)");
-    ctx.print_color(18, y+count as i32+1, RGB::named(rltk::CYAN), RGB::named(rltk::BLACK), "ESCAPE to cancel");
-
-    for (j,sale) in inventory.iter().enumerate() {
-        ctx.set(17, y, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), rltk::to_cp437('('));
-        ctx.set(18, y, RGB::named(rltk::CYAN), RGB::named(rltk::BLACK), 97+j as u8);
-        ctx.set(19, y, RGB::named(rltk::WHITE), RGB::named(rltk::BLACK), rltk::to_cp437(')'));
-
-        ctx.print(21, y, &sale.0);
-        ctx.print(50, y, &format!("{:.1} gp", sale.1 * 1.2));
-        y += 1;
-    }
-
-    // New: Handle web buttons
-    if let Some(btn) = &ctx.web_button {
-        match btn.trim() {
-            "escape" => { (VendorResult::Cancel, None, None, None) },
-            "a" => {
-                //select and return
-                let selection = 0;
-                if selection > -1 && selection < count as i32 {
-                    return (VendorResult::Buy, None, Some(inventory[selection as usize].0.clone()), Some(inventory[selection as usize].1));
-                }
-                (VendorResult::NoResponse, None, None, None)
-            },
-            "b" => {
-                //select and return
-                let selection = 1;
-                if selection > -1 && selection < count as i32 {
-                    return (VendorResult::Buy, None, Some(inventory[selection as usize].0.clone()), Some(inventory[selection as usize].1));
-                }
-                (VendorResult::NoResponse, None, None, None)
-            },
-            "c" => {
-                //select and return
-                let selection = 2;
-                if selection > -1 && selection < count as i32 {
-                    return (VendorResult::Buy, None, Some(inventory[selection as usize].0.clone()), Some(inventory[selection as usize].1));
-                }
-                (VendorResult::NoResponse, None, None, None)
-            },
-            "d" => {
-                //select and return
-                let selection = 3;
-                if selection > -1 && selection < count as i32 {
-                    return (VendorResult::Buy, None, Some(inventory[selection as usize].0.clone()), Some(inventory[selection as usize].1));
-                }
-                (VendorResult::NoResponse, None, None, None)
-            },
-            "tab" => { (VendorResult::SellMode, None, None, None) }
-            _ => (VendorResult::NoResponse, None, None, None),
-        }
-    }
-    else {
-        match ctx.key {
-            None => (VendorResult::NoResponse, None, None, None),
-            Some(key) => {
-                match key {
-                    VirtualKeyCode::Tab => { (VendorResult::SellMode, None, None, None) }
-                    VirtualKeyCode::Escape => { (VendorResult::Cancel, None, None, None) }
-                    _ => {
-                        let selection = rltk::letter_to_option(key);
-                        if selection > -1 && selection < count as i32 {
-                            return (VendorResult::Buy, None, Some(inventory[selection as usize].0.clone()), Some(inventory[selection as usize].1));
-                        }
-                        (VendorResult::NoResponse, None, None, None)
-                    }
-                }
-            }
-        }
+    if vendor_tab_pressed(ctx, "Sell") { return (VendorResult::SellMode, None, None, None); }
+
+    let rows : Vec<MenuRow<(String, f32)>> = {
+        let vendors = gs.ecs.read_storage::<Vendor>();
+        let raws = RAWS.lock().unwrap();
+        get_vendor_items(&vendors.get(vendor).unwrap().categories, &raws).into_iter()
+            .map(|(name, price)| MenuRow{
+                summary : crate::raws::item_summary(&name, &raws),
+                extra : format!("{:.1} gp", price * 1.2),
+                weight : item_weight(&name, &raws),
+                cost : price,
+                category : crate::raws::item_category(&name, &raws),
+                name : name.clone(),
+                value : (name, price)
+            })
+            .collect()
+    };
+    match list_menu(ctx, "Buy Which Item? (tab to switch to sell mode)", "ESCAPE to cancel", menu, rows) {
+        ListMenuResult::Cancel => (VendorResult::Cancel, None, None, None),
+        ListMenuResult::NoResponse => (VendorResult::NoResponse, None, None, None),
+        ListMenuResult::Browse(menu) => (VendorResult::Browse{ menu }, None, None, None),
+        ListMenuResult::Selected((name, price)) => (VendorResult::Buy, None, Some(name), Some(price))
     }
 }
 
 
-pub fn show_vendor_menu(gs : &mut State, ctx : &mut Rltk, vendor : Entity, mode : VendorMode) -> (VendorResult, Option<Entity>, Option<String>, Option<f32>) {
+pub fn show_vendor_menu(gs : &mut State, ctx : &mut Rltk, vendor : Entity, mode : VendorMode, menu : ListMenu) -> (VendorResult, Option<Entity>, Option<String>, Option<f32>) {
     match mode {
-        VendorMode::Buy => vendor_buy_menu(gs, ctx, vendor, mode),
-        VendorMode::Sell => vendor_sell_menu(gs, ctx, vendor, mode)
-    }    
-}
\ No newline at end of file
+        VendorMode::Buy => vendor_buy_menu(gs, ctx, vendor, menu),
+        VendorMode::Sell => vendor_sell_menu(gs, ctx, vendor, menu)
+    }
+}
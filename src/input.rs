@@ -0,0 +1,244 @@
+use rltk::{Rltk, VirtualKeyCode};
+use serde::{Deserialize};
+use std::collections::HashMap;
+use std::sync::RwLock;
+//console is RLTK's wrapper around either println or the web console macro
+use rltk::{console};
+
+rltk::embedded_resource!(BINDINGS_FILE, "../data/bindings.json");
+
+//next to the executable, on top of the defaults; there's no file system on the web
+#[cfg(not(target_arch = "wasm32"))]
+const PLAYER_BINDINGS_FILE : &str = "bindings.json";
+
+/// Everything a key or a web button can ask for
+#[derive(PartialEq, Copy, Clone, Debug, Deserialize)]
+pub enum Command {
+    //playing
+    MoveN, MoveS, MoveE, MoveW, MoveNE, MoveNW, MoveSE, MoveSW, Wait,
+    PickUp, Inventory, Drop, Remove, Throw, Disarm, Descend, Explore, Log, Character, Examine, Overview, ToggleVisualizer,
+    //menus
+    Up, Down, Left, Right, PageUp, PageDown, Home, End, Confirm, Cancel, SwitchMode,
+    //steps through a setting, like the list menu's sort
+    Cycle,
+    //mapgen visualizer
+    TogglePause, StepForward, StepBack, Rewind, ToggleRooms, ToggleCorridors, ToggleSubmaps, ToggleSpawns, Export, Skip,
+    //menus get these for any letter or number, so they can't be bound
+    #[serde(skip)]
+    Choose(i32),
+    #[serde(skip)]
+    Number(i32)
+}
+
+impl Command {
+    pub fn direction(&self) -> Option<(i32, i32)> {
+        match self {
+            Command::MoveN => Some((0, -1)),
+            Command::MoveS => Some((0, 1)),
+            Command::MoveE => Some((1, 0)),
+            Command::MoveW => Some((-1, 0)),
+            Command::MoveNE => Some((1, -1)),
+            Command::MoveNW => Some((-1, -1)),
+            Command::MoveSE => Some((1, 1)),
+            Command::MoveSW => Some((-1, 1)),
+            _ => None
+        }
+    }
+}
+
+/// Which set of bindings applies; the same key means different things on different screens
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum InputContext { Game, Menu, Mapgen }
+
+#[derive(Deserialize, Default)]
+struct ContextRaw {
+    #[serde(default)]
+    keys : HashMap<String, Command>,
+    #[serde(default)]
+    buttons : HashMap<String, Command>
+}
+
+#[derive(Deserialize, Default)]
+struct BindingsRaw {
+    #[serde(default)]
+    game : ContextRaw,
+    #[serde(default)]
+    menu : ContextRaw,
+    #[serde(default)]
+    mapgen : ContextRaw
+}
+
+#[derive(Default)]
+struct ContextBindings {
+    keys : Vec<(VirtualKeyCode, Command)>,
+    buttons : Vec<(String, Command)>
+}
+
+impl ContextBindings {
+    //later bindings for the same key replace earlier ones
+    fn load(&mut self, raw : &ContextRaw) {
+        for (name, command) in raw.keys.iter() {
+            match key_from_name(name) {
+                None => console::log(&format!("Unknown key {} in bindings", name)),
+                Some(key) => {
+                    self.keys.retain(|(k, _)| *k != key);
+                    self.keys.push((key, *command));
+                }
+            }
+        }
+        for (button, command) in raw.buttons.iter() {
+            self.buttons.retain(|(b, _)| b != button);
+            self.buttons.push((button.to_string(), *command));
+        }
+    }
+}
+
+#[derive(Default)]
+struct Bindings {
+    game : ContextBindings,
+    menu : ContextBindings,
+    mapgen : ContextBindings
+}
+
+impl Bindings {
+    fn load(&mut self, raw : &BindingsRaw) {
+        self.game.load(&raw.game);
+        self.menu.load(&raw.menu);
+        self.mapgen.load(&raw.mapgen);
+    }
+
+    fn context(&self, context : InputContext) -> &ContextBindings {
+        match context {
+            InputContext::Game => &self.game,
+            InputContext::Menu => &self.menu,
+            InputContext::Mapgen => &self.mapgen
+        }
+    }
+}
+
+lazy_static! {
+    static ref BINDINGS : RwLock<Bindings> = RwLock::new(Bindings::default());
+}
+
+//the names the bindings file uses, which are the VirtualKeyCode names
+const KEY_NAMES : [(&str, VirtualKeyCode); 64] = [
+    ("A", VirtualKeyCode::A), ("B", VirtualKeyCode::B), ("C", VirtualKeyCode::C), ("D", VirtualKeyCode::D),
+    ("E", VirtualKeyCode::E), ("F", VirtualKeyCode::F), ("G", VirtualKeyCode::G), ("H", VirtualKeyCode::H),
+    ("I", VirtualKeyCode::I), ("J", VirtualKeyCode::J), ("K", VirtualKeyCode::K), ("L", VirtualKeyCode::L),
+    ("M", VirtualKeyCode::M), ("N", VirtualKeyCode::N), ("O", VirtualKeyCode::O), ("P", VirtualKeyCode::P),
+    ("Q", VirtualKeyCode::Q), ("R", VirtualKeyCode::R), ("S", VirtualKeyCode::S), ("T", VirtualKeyCode::T),
+    ("U", VirtualKeyCode::U), ("V", VirtualKeyCode::V), ("W", VirtualKeyCode::W), ("X", VirtualKeyCode::X),
+    ("Y", VirtualKeyCode::Y), ("Z", VirtualKeyCode::Z),
+    ("Key0", VirtualKeyCode::Key0), ("Key1", VirtualKeyCode::Key1), ("Key2", VirtualKeyCode::Key2), ("Key3", VirtualKeyCode::Key3),
+    ("Key4", VirtualKeyCode::Key4), ("Key5", VirtualKeyCode::Key5), ("Key6", VirtualKeyCode::Key6), ("Key7", VirtualKeyCode::Key7),
+    ("Key8", VirtualKeyCode::Key8), ("Key9", VirtualKeyCode::Key9),
+    ("Numpad0", VirtualKeyCode::Numpad0), ("Numpad1", VirtualKeyCode::Numpad1), ("Numpad2", VirtualKeyCode::Numpad2), ("Numpad3", VirtualKeyCode::Numpad3),
+    ("Numpad4", VirtualKeyCode::Numpad4), ("Numpad5", VirtualKeyCode::Numpad5), ("Numpad6", VirtualKeyCode::Numpad6), ("Numpad7", VirtualKeyCode::Numpad7),
+    ("Numpad8", VirtualKeyCode::Numpad8), ("Numpad9", VirtualKeyCode::Numpad9),
+    ("Up", VirtualKeyCode::Up), ("Down", VirtualKeyCode::Down), ("Left", VirtualKeyCode::Left), ("Right", VirtualKeyCode::Right),
+    ("Escape", VirtualKeyCode::Escape), ("Return", VirtualKeyCode::Return), ("Tab", VirtualKeyCode::Tab), ("Space", VirtualKeyCode::Space),
+    ("PageUp", VirtualKeyCode::PageUp), ("PageDown", VirtualKeyCode::PageDown), ("Home", VirtualKeyCode::Home), ("End", VirtualKeyCode::End),
+    ("Period", VirtualKeyCode::Period), ("Comma", VirtualKeyCode::Comma), ("Slash", VirtualKeyCode::Slash), ("Back", VirtualKeyCode::Back),
+    ("Delete", VirtualKeyCode::Delete), ("Insert", VirtualKeyCode::Insert),
+];
+
+const NUMBER_KEYS : [VirtualKeyCode; 10] = [VirtualKeyCode::Key0, VirtualKeyCode::Key1, VirtualKeyCode::Key2, VirtualKeyCode::Key3,
+    VirtualKeyCode::Key4, VirtualKeyCode::Key5, VirtualKeyCode::Key6, VirtualKeyCode::Key7, VirtualKeyCode::Key8, VirtualKeyCode::Key9];
+
+fn key_from_name(name : &str) -> Option<VirtualKeyCode> {
+    KEY_NAMES.iter().find(|(n, _)| *n == name).map(|(_, key)| *key)
+}
+
+fn parse_bindings(text : &str, source : &str) -> Option<BindingsRaw> {
+    match serde_json::from_str(text) {
+        Ok(raw) => Some(raw),
+        Err(e) => {
+            console::log(&format!("Could not read bindings from {}: {}", source, e));
+            None
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn player_bindings() -> Option<BindingsRaw> {
+    let text = std::fs::read_to_string(PLAYER_BINDINGS_FILE).ok()?;
+    parse_bindings(&text, PLAYER_BINDINGS_FILE)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn player_bindings() -> Option<BindingsRaw> {
+    None
+}
+
+/// The default bindings, then whatever the player changed
+pub fn load_bindings() {
+    rltk::link_resource!(BINDINGS_FILE, "../data/bindings.json");
+    let raw_data = rltk::embedding::EMBED
+    .lock()
+    .unwrap()
+    .get_resource("../data/bindings.json".to_string())
+    .unwrap();
+    let raw_string = std::str::from_utf8(&raw_data).expect("Unable to convert to a valid UTF-8 string.");
+
+    let mut bindings = BINDINGS.write().unwrap();
+    if let Some(defaults) = parse_bindings(raw_string, "defaults") {
+        bindings.load(&defaults);
+    }
+    if let Some(changes) = player_bindings() {
+        bindings.load(&changes);
+    }
+}
+
+/// What the player asked for this frame, if anything
+pub fn read_command(ctx : &Rltk, context : InputContext) -> Option<Command> {
+    let bindings = BINDINGS.read().unwrap();
+    let bindings = bindings.context(context);
+
+    if let Some(btn) = &ctx.web_button {
+        let btn = btn.trim();
+        if let Some((_, command)) = bindings.buttons.iter().find(|(b, _)| b == btn) {
+            return Some(*command);
+        }
+        //the letter buttons are named after their letter
+        if context == InputContext::Menu && btn.len() == 1 {
+            let c = btn.chars().next().unwrap();
+            if c.is_ascii_lowercase() { return Some(Command::Choose(c as i32 - 'a' as i32)); }
+        }
+        return None;
+    }
+
+    let key = ctx.key?;
+    //letters pick options in menus, whatever they're bound to while playing
+    if context == InputContext::Menu {
+        let letter = rltk::letter_to_option(key);
+        if letter > -1 { return Some(Command::Choose(letter)); }
+        if let Some(n) = NUMBER_KEYS.iter().position(|k| *k == key) { return Some(Command::Number(n as i32)); }
+    }
+    bindings.keys.iter().find(|(k, _)| *k == key).map(|(_, command)| *command)
+}
+
+/// The key bound to a command, the way help text shows it
+pub fn key_label(context : InputContext, command : Command) -> String {
+    let bindings = BINDINGS.read().unwrap();
+    let keys = &bindings.context(context).keys;
+    //KEY_NAMES order, so the same key is shown every time, and the numpad only if nothing else does it
+    let bound = KEY_NAMES.iter()
+        .filter(|(_, key)| keys.iter().any(|(k, c)| k == key && *c == command))
+        .min_by_key(|(name, _)| name.starts_with("Numpad"));
+    match bound {
+        None => "?".to_string(),
+        Some((name, _)) => match *name {
+            "Escape" => "ESC".to_string(),
+            "Slash" => "/".to_string(),
+            "Period" => ".".to_string(),
+            "Comma" => ",".to_string(),
+            name => name.trim_start_matches("Key").to_string()
+        }
+    }
+}
+
+/// The web button bound to a command, for offering it on the page
+pub fn button_for(context : InputContext, command : Command) -> Option<String> {
+    let bindings = BINDINGS.read().unwrap();
+    bindings.context(context).buttons.iter().find(|(_, c)| *c == command).map(|(button, _)| button.to_string())
+}
@@ -1,7 +1,7 @@
 pub struct ItemCollectionSystem {}
 
 use specs::prelude::*;
-use super::{WantsToPickupItem, Name, InBackpack, Position, gamelog::GameLog, EquipmentChanged };
+use super::{WantsToPickupItem, Name, InBackpack, Position, gamelog::{GameLog, LogCategory, LogEntry}, EquipmentChanged, merge_stack };
 
 
 impl<'a> System<'a> for ItemCollectionSystem {
@@ -12,20 +12,26 @@ impl<'a> System<'a> for ItemCollectionSystem {
                         WriteStorage<'a, Position>,
                         ReadStorage<'a, Name>,
                         WriteStorage<'a, InBackpack>,
-                        WriteStorage<'a, EquipmentChanged>
+                        WriteStorage<'a, EquipmentChanged>,
+                        Read<'a, LazyUpdate>
                       );
 
     fn run(&mut self, data : Self::SystemData) {
-        let (player_entity, mut gamelog, mut wants_pickup, mut positions, names, mut backpack, mut dirty) = data;
+        let (player_entity, mut gamelog, mut wants_pickup, mut positions, names, mut backpack, mut dirty,
+            lazy) = data;
 
         for pickup in wants_pickup.join() {
             positions.remove(pickup.item);
+
             backpack.insert(pickup.item, InBackpack{ owner: pickup.collected_by }).expect("Unable to insert backpack entry");
+            // Merge it into a stack of the same thing if we already carry one; that needs the whole world, so it waits for maintain
+            let item = pickup.item;
+            lazy.exec_mut(move |world| merge_stack(world, item));
             dirty.insert(pickup.collected_by, EquipmentChanged{}).expect("Unable to insert");
 
             if pickup.collected_by == *player_entity {
                 // the tutorial inserts at 0, so the latest is at the top. we do what is more usual, append, so the latest is at bottom
-                gamelog.entries.push(format!("You pick up the {}.", names.get(pickup.item).unwrap().name));
+                gamelog.push(LogEntry::new(LogCategory::Loot).append("You pick up the ").item_name(&names.get(pickup.item).unwrap().name).append("."));
             }
         }
 
@@ -1,5 +1,5 @@
 use specs::prelude::*;
-use super::{WantsToDropItem, Name, InBackpack, Position, gamelog::GameLog, EquipmentChanged };
+use super::{WantsToDropItem, Name, InBackpack, Position, gamelog::{GameLog, LogCategory, LogEntry}, EquipmentChanged };
 
 pub struct ItemDropSystem {}
 
@@ -30,7 +30,7 @@ impl<'a> System<'a> for ItemDropSystem {
             dirty.insert(entity, EquipmentChanged{}).expect("Unable to insert");
 
             if entity == *player_entity {
-                gamelog.entries.insert(0, format!("You drop up the {}.", names.get(to_drop.item).unwrap().name));
+                gamelog.push(LogEntry::new(LogCategory::Loot).append("You drop the ").item_name(&names.get(to_drop.item).unwrap().name).append("."));
             }
         }
 
@@ -1,8 +1,10 @@
 extern crate specs;
 use specs::prelude::*;
 use super::{WantsToPickupItem, Name, InBackpack, Position, gamelog, Map,
-    WantsToUseItem, MedItem, Pools, WantsToDropItem, Consumable, InflictsDamage, SufferDamage, AreaOfEffect, Confusion, ProvidesFood, ProvidesQuench,
-    Equippable, Equipped, EquipmentChanged, WantsToRemoveItem, particle_system};
+    WantsToUseItem, WantsToDropItem, AreaOfEffect,
+    Equippable, Equipped, EquipmentChanged, WantsToRemoveItem, WantsToThrowItem, Item, Fragile, Pools,
+    Stackable, particle_system, effects, Charges};
+use crate::raws::{RAWS, SpawnType, spawn_named_item};
 
 
 mod collection_system;
@@ -12,4 +14,74 @@ pub use use_system::ItemUseSystem;
 mod drop_system;
 pub use drop_system::ItemDropSystem;
 mod remove_system;
-pub use remove_system::ItemRemoveSystem;
\ No newline at end of file
+pub use remove_system::ItemRemoveSystem;
+mod throw_system;
+pub use throw_system::{ItemThrowSystem, THROW_RANGE};
+
+/// Takes a single item off a stack, so it can be dropped, thrown or sold on its own.
+/// The single is spawned fresh from the raws, so it gets everything the item is made of;
+/// only what changes in play is copied across
+pub fn split_stack(ecs : &mut World, item : Entity) -> Entity {
+    let quantity = ecs.read_storage::<Stackable>().get(item).map_or(1, |s| s.quantity);
+    if quantity < 2 { return item; }
+
+    let name = match ecs.read_storage::<Name>().get(item) {
+        None => return item,
+        Some(name) => name.name.clone()
+    };
+    let pos = match (ecs.read_storage::<InBackpack>().get(item), ecs.read_storage::<Position>().get(item)) {
+        (Some(pack), _) => SpawnType::Carried{ by: pack.owner },
+        (None, Some(pos)) => SpawnType::AtPosition{ x: pos.x, y: pos.y },
+        (None, None) => return item
+    };
+    //not in the raws, so there's nothing to make another from; the whole stack goes
+    let single = match spawn_named_item(&RAWS.lock().unwrap(), ecs, &name, pos) {
+        None => return item,
+        Some(single) => single
+    };
+
+    let charges = ecs.read_storage::<Charges>().get(item).cloned();
+    if let Some(charges) = charges {
+        ecs.write_storage::<Charges>().insert(single, charges).expect("Insert failed");
+    }
+    ecs.write_storage::<Stackable>().insert(single, Stackable{ quantity: 1 }).expect("Insert failed");
+    ecs.write_storage::<Stackable>().get_mut(item).unwrap().quantity -= 1;
+    single
+}
+
+/// Whether two items are the same thing, and so can share a stack
+pub fn stacks_with(names : &ReadStorage<Name>, items : &ReadStorage<Item>, a : Entity, b : Entity) -> bool {
+    match (names.get(a), names.get(b), items.get(a), items.get(b)) {
+        (Some(name_a), Some(name_b), Some(item_a), Some(item_b)) => {
+            name_a.name == name_b.name
+                && item_a.weight_lbs == item_b.weight_lbs
+                && item_a.base_value == item_b.base_value
+        }
+        _ => false
+    }
+}
+
+/// Folds a newly carried item into a matching stack, if its owner already has one
+pub fn merge_stack(ecs : &mut World, item : Entity) {
+    let existing;
+    {
+        let stacks = ecs.read_storage::<Stackable>();
+        if stacks.get(item).is_none() { return; }
+        let backpack = ecs.read_storage::<InBackpack>();
+        let owner = match backpack.get(item) {
+            None => return,
+            Some(pack) => pack.owner
+        };
+        let names = ecs.read_storage::<Name>();
+        let items = ecs.read_storage::<Item>();
+        let entities = ecs.entities();
+        existing = (&entities, &backpack, &stacks).join()
+            .find(|(entity, pack, _stack)| *entity != item && pack.owner == owner && stacks_with(&names, &items, *entity, item))
+            .map(|(entity, ..)| entity);
+    }
+    if let Some(existing) = existing {
+        let quantity = ecs.read_storage::<Stackable>().get(item).unwrap().quantity;
+        ecs.write_storage::<Stackable>().get_mut(existing).unwrap().quantity += quantity;
+        ecs.delete_entity(item).expect("Delete failed");
+    }
+}
\ No newline at end of file
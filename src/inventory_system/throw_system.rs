@@ -0,0 +1,92 @@
+use specs::prelude::*;
+use super::{WantsToThrowItem, Name, InBackpack, Position, gamelog::{GameLog, LogCategory, LogEntry}, EquipmentChanged, Map,
+    Item, Fragile, Pools, particle_system::ParticleBuilder, effects::{add_effect, EffectType, Targets}};
+use crate::map::tile_walkable;
+use rltk::{Point, LineAlg};
+
+//how far anything can be thrown
+pub const THROW_RANGE : i32 = 6;
+
+pub struct ItemThrowSystem {}
+
+impl<'a> System<'a> for ItemThrowSystem {
+    #[allow(clippy::type_complexity)]
+    type SystemData = ( ReadExpect<'a, Entity>,
+                        WriteExpect<'a, GameLog>,
+                        ReadExpect<'a, Map>,
+                        Entities<'a>,
+                        WriteStorage<'a, WantsToThrowItem>,
+                        ReadStorage<'a, Name>,
+                        WriteStorage<'a, Position>,
+                        WriteStorage<'a, InBackpack>,
+                        WriteStorage<'a, EquipmentChanged>,
+                        ReadStorage<'a, Item>,
+                        ReadStorage<'a, Fragile>,
+                        ReadStorage<'a, Pools>,
+                        WriteExpect<'a, ParticleBuilder>
+                      );
+
+    fn run(&mut self, data : Self::SystemData) {
+        let (player_entity, mut gamelog, map, entities, mut wants_throw, names, mut positions, mut backpack, mut dirty,
+            items, fragile, pools, mut particle_builder) = data;
+
+        for (entity, to_throw) in (&entities, &wants_throw).join() {
+            let origin = match positions.get(entity) {
+                None => continue,
+                Some(pos) => Point::new(pos.x, pos.y)
+            };
+            let item_name = names.get(to_throw.item).map_or("item".to_string(), |n| n.name.clone());
+            if entity == *player_entity {
+                gamelog.push(LogEntry::new(LogCategory::Combat).append("You throw the ").item_name(&item_name).append("."));
+            }
+
+            // Follow the line until something gets in the way
+            let mut line = rltk::line2d(LineAlg::Bresenham, origin, to_throw.target);
+            if line.last() != Some(&to_throw.target) { line.push(to_throw.target); }
+            let mut landing = origin;
+            let mut hit : Option<Entity> = None;
+            for p in line.iter() {
+                if *p == origin { continue; }
+                //the edge of the map is always solid
+                if p.x < 1 || p.x >= map.width-1 || p.y < 1 || p.y >= map.height-1 { break; }
+                let idx = map.xy_idx(p.x, p.y);
+                //walls stop it short
+                if !tile_walkable(map.tiles[idx]) { break; }
+                if let Some(victim) = map.tile_content[idx].iter().find(|e| **e != entity && pools.get(**e).is_some()) {
+                    //it drops at their feet
+                    hit = Some(*victim);
+                    landing = *p;
+                    break;
+                }
+                //so do closed doors and anything else that blocks the way
+                if map.blocked[idx] { break; }
+                landing = *p;
+            }
+
+            // Heavier things hurt more
+            if let Some(victim) = hit {
+                let weight = items.get(to_throw.item).map_or(0.0, |i| i.weight_lbs);
+                let damage = 1 + (weight / 2.0) as i32;
+                if let Some(victim_name) = names.get(victim) {
+                    gamelog.push(LogEntry::new(LogCategory::Combat).append("The ").item_name(&item_name).append(" hits ").npc_name(&victim_name.name)
+                        .append(", for ").damage(damage).append(" hp."));
+                }
+                add_effect(Some(entity), EffectType::Damage{ amount: damage }, Targets::Single{ target: victim });
+            }
+
+            backpack.remove(to_throw.item);
+            dirty.insert(entity, EquipmentChanged{}).expect("Unable to insert");
+
+            // Bottles and the like don't survive the landing
+            if fragile.get(to_throw.item).is_some() {
+                gamelog.push(LogEntry::new(LogCategory::Combat).append("The ").item_name(&item_name).append(" shatters!"));
+                particle_builder.request(landing.x, landing.y, rltk::RGB::named(rltk::CYAN), rltk::RGB::named(rltk::BLACK), rltk::to_cp437('*'), 200.0);
+                entities.delete(to_throw.item).expect("Delete failed");
+            } else {
+                positions.insert(to_throw.item, Position{ x : landing.x, y : landing.y }).expect("Unable to insert position");
+            }
+        }
+
+        wants_throw.clear();
+    }
+}
@@ -0,0 +1,100 @@
+use rltk::{Rltk, Console};
+
+//the side panel's width, borders included
+const PANEL_WIDTH : i32 = 30;
+//the log takes a quarter of the screen, within these
+const MIN_LOG_HEIGHT : i32 = 6;
+const MAX_LOG_HEIGHT : i32 = 14;
+//any narrower than this next to the panel and the panel goes under the map instead
+const MIN_MAP_WIDTH : i32 = 40;
+//under the map there's room for the stats and equipment, but not the minimap
+const PORTRAIT_PANEL_HEIGHT : i32 = 25;
+
+/// A box on screen, measured the way draw_hollow_box takes it: the border is at
+/// x and x + width, so the inside is width - 1 across
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct Frame {
+    pub x : i32,
+    pub y : i32,
+    pub width : i32,
+    pub height : i32
+}
+
+impl Frame {
+    fn new(x : i32, y : i32, width : i32, height : i32) -> Frame {
+        Frame{ x, y, width, height }
+    }
+
+    //the first cell inside the border
+    pub fn inner_x(&self) -> i32 { self.x + 1 }
+    pub fn inner_y(&self) -> i32 { self.y + 1 }
+
+    pub fn inner_width(&self) -> i32 { self.width - 1 }
+    pub fn inner_height(&self) -> i32 { self.height - 1 }
+
+    //the last row inside, exclusive
+    pub fn bottom(&self) -> i32 { self.y + self.height }
+}
+
+/// Where the map, side panel and log go, worked out from the console size.
+/// The map always sits at the top left, so map tiles land at their offset from the camera
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct Layout {
+    pub width : i32,
+    pub height : i32,
+    pub map : Frame,
+    pub panel : Frame,
+    pub log : Frame,
+    //the panel is under the map rather than beside it
+    pub portrait : bool
+}
+
+impl Layout {
+    pub fn new(width : i32, height : i32) -> Layout {
+        let log_height = i32::max(MIN_LOG_HEIGHT, i32::min(MAX_LOG_HEIGHT, height / 4));
+        let log = Frame::new(0, height - 1 - log_height, width - 1, log_height);
+
+        if width - PANEL_WIDTH >= MIN_MAP_WIDTH {
+            let map = Frame::new(0, 0, width - 1 - PANEL_WIDTH, log.y);
+            let panel = Frame::new(map.width, 0, PANEL_WIDTH, log.y);
+            Layout{ width, height, map, panel, log, portrait : false }
+        } else {
+            let panel_height = i32::min(PORTRAIT_PANEL_HEIGHT, log.y / 2);
+            let map = Frame::new(0, 0, width - 1, log.y - panel_height);
+            let panel = Frame::new(0, map.height, width - 1, panel_height);
+            Layout{ width, height, map, panel, log, portrait : true }
+        }
+    }
+
+    /// How many map tiles the camera shows across and down
+    pub fn view_size(&self) -> (i32, i32) {
+        (self.map.inner_width(), self.map.inner_height())
+    }
+
+    /// Whether a screen cell is on the map view
+    pub fn in_view(&self, x : i32, y : i32) -> bool {
+        let (width, height) = self.view_size();
+        x >= 1 && x < width && y >= 1 && y < height
+    }
+
+    /// Full-screen views (the log, the map overview...) draw inside this, with a title
+    /// row at the top and a help row at the bottom
+    pub fn screen(&self) -> Frame {
+        Frame::new(0, 0, self.width - 1, self.height - 1)
+    }
+
+    /// The part of a full-screen view between the title and the help row
+    pub fn screen_body(&self) -> Frame {
+        Frame::new(0, 2, self.width - 1, self.height - 5)
+    }
+
+    pub fn help_row(&self) -> i32 {
+        self.height - 2
+    }
+}
+
+/// The layout for the console as it is now
+pub fn layout(ctx : &Rltk) -> Layout {
+    let (width, height) = ctx.get_char_size();
+    Layout::new(width as i32, height as i32)
+}
@@ -0,0 +1,232 @@
+use rltk::{RGB, Rltk, Console};
+use super::input::{read_command, Command, InputContext};
+use super::web_ui;
+use super::layout::layout;
+use std::cmp::Ordering;
+
+//rows on a page, lettered a to t
+pub const PAGE_SIZE : usize = 20;
+const MENU_WIDTH : i32 = 51;
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum ItemSort { Name, Weight, Value, Type }
+
+const SORTS : [(ItemSort, &str); 4] = [
+    (ItemSort::Name, "Name"),
+    (ItemSort::Weight, "Weight"),
+    (ItemSort::Value, "Value"),
+    (ItemSort::Type, "Type"),
+];
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum ItemCategory { All, Weapons, Armor, Consumables, Other }
+
+const CATEGORY_TABS : [(ItemCategory, &str); 5] = [
+    (ItemCategory::All, "All"),
+    (ItemCategory::Weapons, "Weapons"),
+    (ItemCategory::Armor, "Armor"),
+    (ItemCategory::Consumables, "Consumables"),
+    (ItemCategory::Other, "Other"),
+];
+
+/// Where a list menu is up to; lives in the RunState so it lasts between frames
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub struct ListMenu {
+    pub page : usize,
+    pub sort : ItemSort,
+    pub tab : ItemCategory
+}
+
+impl ListMenu {
+    pub fn new() -> ListMenu {
+        ListMenu{ page : 0, sort : ItemSort::Name, tab : ItemCategory::All }
+    }
+
+    fn with_tab(self, offset : i32) -> ListMenu {
+        let current = CATEGORY_TABS.iter().position(|(tab, _)| *tab == self.tab).unwrap_or(0) as i32;
+        let count = CATEGORY_TABS.len() as i32;
+        let next = (current + offset + count) % count;
+        ListMenu{ page : 0, tab : CATEGORY_TABS[next as usize].0, ..self }
+    }
+
+    fn with_sort(self, sort : ItemSort) -> ListMenu {
+        ListMenu{ page : 0, sort, ..self }
+    }
+
+    fn next_sort(self) -> ListMenu {
+        let current = SORTS.iter().position(|(sort, _)| *sort == self.sort).unwrap_or(0);
+        self.with_sort(SORTS[(current + 1) % SORTS.len()].0)
+    }
+
+    fn with_page(self, page : usize) -> ListMenu {
+        ListMenu{ page, ..self }
+    }
+}
+
+/// One line in a list menu
+pub struct MenuRow<T> {
+    pub value : T,
+    pub name : String,
+    //short stats, in gray
+    pub summary : String,
+    //the right hand column: weight, or price at a vendor
+    pub extra : String,
+    pub weight : f32,
+    pub cost : f32,
+    pub category : ItemCategory
+}
+
+pub enum ListMenuResult<T> { Cancel, NoResponse, Browse(ListMenu), Selected(T) }
+
+fn compare_rows<T>(sort : ItemSort, a : &MenuRow<T>, b : &MenuRow<T>) -> Ordering {
+    let by_name = a.name.cmp(&b.name);
+    match sort {
+        ItemSort::Name => by_name,
+        //heaviest and priciest first, since those are what you're looking for
+        ItemSort::Weight => b.weight.partial_cmp(&a.weight).unwrap_or(Ordering::Equal).then(by_name),
+        ItemSort::Value => b.cost.partial_cmp(&a.cost).unwrap_or(Ordering::Equal).then(by_name),
+        ItemSort::Type => {
+            let order = |c : ItemCategory| CATEGORY_TABS.iter().position(|(tab, _)| *tab == c);
+            order(a.category).cmp(&order(b.category)).then(by_name)
+        }
+    }
+}
+
+//prints labels in a row, the chosen one highlighted, and returns where each one ended up
+fn draw_choices(ctx : &mut Rltk, x : i32, y : i32, labels : &[String], chosen : usize) -> Vec<(i32, i32)> {
+    let mut spans = Vec::new();
+    let mut x = x;
+    for (i, label) in labels.iter().enumerate() {
+        let fg = if i == chosen { RGB::named(rltk::YELLOW) } else { RGB::named(rltk::GRAY) };
+        ctx.print_color(x, y, fg, RGB::named(rltk::BLACK), label);
+        let width = label.chars().count() as i32;
+        spans.push((x, x + width));
+        x += width + 1;
+    }
+    spans
+}
+
+/// The one menu all the item screens share: category tabs, sorting, pages,
+/// and picking by letter, mouse or web button
+pub fn list_menu<T : Clone>(ctx : &mut Rltk, title : &str, footer : &str, menu : ListMenu, rows : Vec<MenuRow<T>>) -> ListMenuResult<T> {
+    let white = RGB::named(rltk::WHITE);
+    let black = RGB::named(rltk::BLACK);
+
+    let mut rows : Vec<MenuRow<T>> = rows.into_iter().filter(|r| menu.tab == ItemCategory::All || r.category == menu.tab).collect();
+    rows.sort_by(|a, b| compare_rows(menu.sort, a, b));
+    let pages = usize::max(1, (rows.len() + PAGE_SIZE - 1) / PAGE_SIZE);
+    let page = usize::min(menu.page, pages - 1);
+    let first = page * PAGE_SIZE;
+    let shown = &rows[first .. usize::min(first + PAGE_SIZE, rows.len())];
+    let height = usize::max(1, shown.len()) as i32;
+
+    //centred across, and a little above the middle
+    let layout = layout(ctx);
+    let menu_x = (layout.width - MENU_WIDTH + 1) / 2;
+    let top = layout.height / 2 - 5 - (height + 4) / 2;
+    ctx.draw_box(menu_x, top, MENU_WIDTH, height + 4, white, black);
+    ctx.print_color(menu_x + 3, top, RGB::named(rltk::CYAN), black, title);
+    ctx.print_color(menu_x + 3, top + height + 4, RGB::named(rltk::CYAN), black, footer);
+
+    let tab_labels : Vec<String> = CATEGORY_TABS.iter().map(|(_, label)| label.to_string()).collect();
+    let chosen_tab = CATEGORY_TABS.iter().position(|(tab, _)| *tab == menu.tab).unwrap_or(0);
+    let tab_spans = draw_choices(ctx, menu_x + 2, top + 1, &tab_labels, chosen_tab);
+
+    ctx.print_color(menu_x + 2, top + 2, white, black, "Sort:");
+    let sort_labels : Vec<String> = SORTS.iter().enumerate().map(|(i, (_, label))| format!("{}:{}", i + 1, label)).collect();
+    let chosen_sort = SORTS.iter().position(|(sort, _)| *sort == menu.sort).unwrap_or(0);
+    let sort_spans = draw_choices(ctx, menu_x + 8, top + 2, &sort_labels, chosen_sort);
+
+    let first_row = top + 3;
+    let mouse_pos = ctx.mouse_pos();
+    let hovered = if mouse_pos.0 > menu_x && mouse_pos.0 < menu_x + MENU_WIDTH && mouse_pos.1 >= first_row && mouse_pos.1 < first_row + shown.len() as i32 {
+        Some((mouse_pos.1 - first_row) as usize)
+    } else { None };
+
+    if shown.is_empty() {
+        ctx.print_color(menu_x + 6, first_row, RGB::named(rltk::GRAY), black, "(nothing)");
+    }
+    for (j, row) in shown.iter().enumerate() {
+        let y = first_row + j as i32;
+        if hovered == Some(j) {
+            for x in menu_x + 1 .. menu_x + MENU_WIDTH {
+                ctx.set_bg(x, y, RGB::named(rltk::DARK_GRAY));
+            }
+        }
+        let bg = if hovered == Some(j) { RGB::named(rltk::DARK_GRAY) } else { black };
+        ctx.set(menu_x + 2, y, white, bg, rltk::to_cp437('('));
+        ctx.set(menu_x + 3, y, RGB::named(rltk::CYAN), bg, 97 + j as u8);
+        ctx.set(menu_x + 4, y, white, bg, rltk::to_cp437(')'));
+        let name : String = row.name.chars().take(18).collect();
+        ctx.print_color(menu_x + 6, y, white, bg, &name);
+        ctx.print_color(menu_x + 25, y, RGB::named(rltk::GRAY), bg, &row.summary);
+        ctx.print_color(menu_x + 35, y, white, bg, &row.extra);
+    }
+
+    let page_y = first_row + height;
+    let page_label = format!("Page {}/{}", page + 1, pages);
+    let next_x = menu_x + 6 + page_label.len() as i32;
+    ctx.print_color(menu_x + 2, page_y, white, black, "<<");
+    ctx.print_color(menu_x + 5, page_y, RGB::named(rltk::GRAY), black, &page_label);
+    ctx.print_color(next_x, page_y, white, black, ">>");
+    ctx.print_color(next_x + 4, page_y, RGB::named(rltk::DARK_GRAY), black, "arrows: tab/page");
+
+    let previous_page = menu.with_page(page.saturating_sub(1));
+    let next_page = menu.with_page(usize::min(page + 1, pages - 1));
+
+    //mouse first, since a click is the most deliberate thing that can happen
+    if ctx.left_click {
+        if let Some(j) = hovered {
+            return ListMenuResult::Selected(shown[j].value.clone());
+        }
+        let clicked = |spans : &[(i32, i32)], y : i32| {
+            if mouse_pos.1 != y { return None; }
+            spans.iter().position(|(start, end)| mouse_pos.0 >= *start && mouse_pos.0 < *end)
+        };
+        if let Some(i) = clicked(&tab_spans, top + 1) {
+            return ListMenuResult::Browse(ListMenu{ page : 0, tab : CATEGORY_TABS[i].0, ..menu });
+        }
+        if let Some(i) = clicked(&sort_spans, top + 2) {
+            return ListMenuResult::Browse(menu.with_sort(SORTS[i].0));
+        }
+        if mouse_pos.1 == page_y {
+            if mouse_pos.0 >= menu_x + 2 && mouse_pos.0 < menu_x + 4 {
+                return ListMenuResult::Browse(previous_page);
+            }
+            if mouse_pos.0 >= next_x && mouse_pos.0 < next_x + 2 {
+                return ListMenuResult::Browse(next_page);
+            }
+        }
+    }
+
+    //the same menu, as buttons on the web page
+    web_ui::title(title);
+    for (j, row) in shown.iter().enumerate() {
+        web_ui::option(j, &format!("{} {}", row.name, row.extra));
+    }
+    web_ui::offer(InputContext::Menu, Command::Cancel);
+    web_ui::offer_labelled(InputContext::Menu, Command::Left, "◀ Tab");
+    web_ui::offer_labelled(InputContext::Menu, Command::Right, "Tab ▶");
+    if pages > 1 {
+        web_ui::offer_labelled(InputContext::Menu, Command::PageUp, "Previous page");
+        web_ui::offer_labelled(InputContext::Menu, Command::PageDown, "Next page");
+    }
+    web_ui::offer_labelled(InputContext::Menu, Command::Cycle, &format!("Sort: {}", SORTS[chosen_sort].1));
+
+    match read_command(ctx, InputContext::Menu) {
+        Some(Command::Cancel) => return ListMenuResult::Cancel,
+        Some(Command::Left) => return ListMenuResult::Browse(menu.with_tab(-1)),
+        Some(Command::Right) => return ListMenuResult::Browse(menu.with_tab(1)),
+        Some(Command::Up) | Some(Command::PageUp) => return ListMenuResult::Browse(previous_page),
+        Some(Command::Down) | Some(Command::PageDown) => return ListMenuResult::Browse(next_page),
+        Some(Command::Home) => return ListMenuResult::Browse(menu.with_page(0)),
+        Some(Command::End) => return ListMenuResult::Browse(menu.with_page(pages - 1)),
+        Some(Command::Cycle) => return ListMenuResult::Browse(menu.next_sort()),
+        Some(Command::Number(n)) if n >= 1 && n <= SORTS.len() as i32 => return ListMenuResult::Browse(menu.with_sort(SORTS[n as usize - 1].0)),
+        Some(Command::Choose(selection)) if selection < shown.len() as i32 => {
+            return ListMenuResult::Selected(shown[selection as usize].value.clone());
+        }
+        _ => {}
+    }
+    if page != menu.page { ListMenuResult::Browse(menu.with_page(page)) } else { ListMenuResult::NoResponse }
+}
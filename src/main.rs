@@ -12,7 +12,11 @@ extern crate console_error_panic_hook;
 use std::panic;
 
 pub mod camera;
+mod layout;
 mod gui;
+mod list_menu;
+mod input;
+mod web_ui;
 mod gamelog;
 use gamelog::GameLog;
 
@@ -22,6 +26,7 @@ mod map;
 pub use map::*;
 mod player;
 use player::*;
+mod travel;
 mod rect;
 pub use rect::Rect;
 mod visibility_system;
@@ -34,10 +39,10 @@ use map_indexing_system::MapIndexingSystem;
 mod melee_combat_system;
 use melee_combat_system::MeleeCombatSystem;
 mod damage_system;
-use damage_system::DamageSystem;
 mod inventory_system;
 use inventory_system::*;
 mod trigger_system;
+mod effects;
 use trigger_system::TriggerSystem;
 pub mod hunger_system;
 pub mod random_table;
@@ -61,7 +66,19 @@ use rltk::{console};
 
 rltk::add_wasm_support!();
 
+//whether the visualizer starts on; V toggles it while playing
 const SHOW_MAPGEN_VISUALIZER : bool = true;
+//run with MAPGEN_BATCH_STATS=<n> to print generation stats for n maps of every builder chain at startup
+#[cfg(not(target_arch = "wasm32"))]
+fn mapgen_batch_stats() -> i32 {
+    std::env::var("MAPGEN_BATCH_STATS").ok().and_then(|n| n.parse().ok()).unwrap_or(0)
+}
+
+//no environment on the web
+#[cfg(target_arch = "wasm32")]
+fn mapgen_batch_stats() -> i32 {
+    0
+}
 
 #[derive(PartialEq, Copy, Clone)]
 pub enum VendorMode { Buy, Sell }
@@ -72,14 +89,23 @@ pub enum RunState {
     AwaitingInput, 
     PreRun, 
     Ticking,
-    ShowInventory,
-    ShowDropItem,
+    ShowInventory { menu : list_menu::ListMenu },
+    ShowDropItem { menu : list_menu::ListMenu },
+    ShowThrowItem { menu : list_menu::ListMenu },
+    ShowThrowTarget { item : Entity },
     ShowTargeting { range : i32, item : Entity},
-    ShowRemoveItem,
+    ShowRemoveItem { menu : list_menu::ListMenu },
     MainMenu { menu_selection : gui::MainMenuSelection },
     GameOver,
-    ShowVendor { vendor: Entity, mode : VendorMode },
-    MapGeneration
+    ShowVendor { vendor: Entity, mode : VendorMode, menu : list_menu::ListMenu },
+    ShowLog { scroll : usize, filter : gamelog::LogFilter },
+    ShowCharacterSheet,
+    ShowEquipItem { menu : list_menu::ListMenu },
+    ShowExamineMenu { menu : list_menu::ListMenu },
+    ExamineItem { item : Entity, menu : list_menu::ListMenu },
+    ShowOverview { x : i32, y : i32, zoomed_out : bool },
+    MapGeneration,
+    NextLevel
 }
 
 // We're extending State to include the ECS world.
@@ -87,15 +113,22 @@ pub struct State {
     pub ecs: World,
     //mapgen visualizer stuff that has nowhere else to go
     mapgen_next_state : Option<RunState>,
-    mapgen_history : Vec<Map>,
+    mapgen_history : Vec<map_builders::MapSnapshot>,
     mapgen_index : usize,
-    mapgen_timer : f32
+    mapgen_timer : f32,
+    mapgen_paused : bool,
+    //how the last export went, shown until the next one
+    mapgen_message : Option<String>,
+    mapgen_overlays : camera::DebugOverlays,
+    //click-to-travel or auto-explore in progress
+    travel : Option<travel::Travel>
 }
 
 impl GameState for State {
     fn tick(&mut self, ctx: &mut Rltk) {
         // Clear the screen
         ctx.cls();
+        web_ui::begin_frame(ctx);
         //Kill particles
         particle_system::cull_dead_particles(&mut self.ecs, ctx);
 
@@ -166,17 +199,18 @@ impl GameState for State {
             //     self.ecs.maintain();
             //     newrunstate = RunState::AwaitingInput;
             // }
-            RunState::ShowInventory => {
-                let result = gui::show_inventory(self, ctx);
-                match result.0 {
-                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
-                    gui::ItemMenuResult::NoResponse => {}
-                    gui::ItemMenuResult::Selected => {
-                        let item_entity = result.1.unwrap();
+            RunState::ShowInventory{menu} => {
+                match gui::backpack_menu(self, ctx, "Inventory", menu) {
+                    list_menu::ListMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    list_menu::ListMenuResult::NoResponse => {}
+                    list_menu::ListMenuResult::Browse(menu) => newrunstate = RunState::ShowInventory{ menu },
+                    list_menu::ListMenuResult::Selected(item_entity) => {
 
                         let is_ranged = self.ecs.read_storage::<Ranged>();
                         let is_item_ranged = is_ranged.get(item_entity);
-                        if let Some(is_item_ranged) = is_item_ranged {
+                        //an empty gun just gets reloaded, so there's nothing to aim
+                        let is_empty = self.ecs.read_storage::<Charges>().get(item_entity).map_or(false, |c| c.current < 1);
+                        if let (Some(is_item_ranged), false) = (is_item_ranged, is_empty) {
                             newrunstate = RunState::ShowTargeting{ range: is_item_ranged.range, item: item_entity };
                         } else {
                             let mut intent = self.ecs.write_storage::<WantsToUseItem>();
@@ -186,21 +220,45 @@ impl GameState for State {
                     }
                 }
             }
-            RunState::ShowDropItem => {
-                let result = gui::drop_item_menu(self, ctx);
+            RunState::ShowDropItem{menu} => {
+                match gui::backpack_menu(self, ctx, "Drop Which Item?", menu) {
+                    list_menu::ListMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    list_menu::ListMenuResult::NoResponse => {}
+                    list_menu::ListMenuResult::Browse(menu) => newrunstate = RunState::ShowDropItem{ menu },
+                    list_menu::ListMenuResult::Selected(item) => {
+                        //drop one at a time off a stack
+                        let item_entity = inventory_system::split_stack(&mut self.ecs, item);
+                        let mut intent = self.ecs.write_storage::<WantsToDropItem>();
+                        intent.insert(*self.ecs.fetch::<Entity>(), WantsToDropItem{ item: item_entity }).expect("Unable to insert intent");
+                        newrunstate = RunState::Ticking;
+                    }
+                }
+            }
+            RunState::ShowThrowItem{menu} => {
+                match gui::backpack_menu(self, ctx, "Throw Which Item?", menu) {
+                    list_menu::ListMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    list_menu::ListMenuResult::NoResponse => {}
+                    list_menu::ListMenuResult::Browse(menu) => newrunstate = RunState::ShowThrowItem{ menu },
+                    list_menu::ListMenuResult::Selected(item) => {
+                        newrunstate = RunState::ShowThrowTarget{ item };
+                    }
+                }
+            }
+            RunState::ShowThrowTarget{item} => {
+                let result = gui::ranged_target(self, ctx, THROW_RANGE, item, false);
                 match result.0 {
                     gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
                     gui::ItemMenuResult::NoResponse => {}
                     gui::ItemMenuResult::Selected => {
-                        let item_entity = result.1.unwrap();
-                        let mut intent = self.ecs.write_storage::<WantsToDropItem>();
-                        intent.insert(*self.ecs.fetch::<Entity>(), WantsToDropItem{ item: item_entity }).expect("Unable to insert intent");
+                        let item = inventory_system::split_stack(&mut self.ecs, item);
+                        let mut intent = self.ecs.write_storage::<WantsToThrowItem>();
+                        intent.insert(*self.ecs.fetch::<Entity>(), WantsToThrowItem{ item, target: result.1.unwrap() }).expect("Unable to insert intent");
                         newrunstate = RunState::Ticking;
                     }
                 }
             }
             RunState::ShowTargeting{range, item} => {
-                let result = gui::ranged_target(self, ctx, range);
+                let result = gui::ranged_target(self, ctx, range, item, true);
                 match result.0 {
                     gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
                     gui::ItemMenuResult::NoResponse => {}
@@ -211,74 +269,171 @@ impl GameState for State {
                     }
                 }
             }
-            RunState::ShowRemoveItem => {
-                let result = gui::remove_item_menu(self, ctx);
-                match result.0 {
-                    gui::ItemMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
-                    gui::ItemMenuResult::NoResponse => {}
-                    gui::ItemMenuResult::Selected => {
-                        let item_entity = result.1.unwrap();
+            RunState::ShowRemoveItem{menu} => {
+                match gui::remove_item_menu(self, ctx, menu) {
+                    list_menu::ListMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    list_menu::ListMenuResult::NoResponse => {}
+                    list_menu::ListMenuResult::Browse(menu) => newrunstate = RunState::ShowRemoveItem{ menu },
+                    list_menu::ListMenuResult::Selected(item_entity) => {
                         let mut intent = self.ecs.write_storage::<WantsToRemoveItem>();
                         intent.insert(*self.ecs.fetch::<Entity>(), WantsToRemoveItem{ item: item_entity }).expect("Unable to insert intent");
                         newrunstate = RunState::Ticking;
                     }
                 }
             }
-            RunState::ShowVendor{vendor, mode} => {
-                let result = gui::show_vendor_menu(self, ctx, vendor, mode);
+            RunState::ShowVendor{vendor, mode, menu} => {
+                let result = gui::show_vendor_menu(self, ctx, vendor, mode, menu);
                 match result.0 {
                     gui::VendorResult::Cancel => newrunstate = RunState::AwaitingInput,
                     gui::VendorResult::NoResponse => {}
                     gui::VendorResult::Sell => {
-                        let price = self.ecs.read_storage::<Item>().get(result.1.unwrap()).unwrap().base_value * 0.8;
+                        //sell one at a time off a stack
+                        let item_entity = inventory_system::split_stack(&mut self.ecs, result.1.unwrap());
+                        let price = self.ecs.read_storage::<Item>().get(item_entity).unwrap().base_value * 0.8;
                         self.ecs.write_storage::<Pools>().get_mut(*self.ecs.fetch::<Entity>()).unwrap().money += price;
-                        self.ecs.delete_entity(result.1.unwrap()).expect("Unable to delete");
+                        self.ecs.delete_entity(item_entity).expect("Unable to delete");
                     }
                     gui::VendorResult::Buy => {
                         let tag = result.2.unwrap();
                         let price = result.3.unwrap();
-                        let mut pools = self.ecs.write_storage::<Pools>();
-                        let player_pools = pools.get_mut(*self.ecs.fetch::<Entity>()).unwrap();
-                        if player_pools.money >= price {
-                            player_pools.money -= price;
-                            std::mem::drop(pools);
-                            let player_entity = *self.ecs.fetch::<Entity>();
+                        let player_entity = *self.ecs.fetch::<Entity>();
+                        let paid = {
+                            let mut pools = self.ecs.write_storage::<Pools>();
+                            let player_pools = pools.get_mut(player_entity).unwrap();
+                            if player_pools.money >= price { player_pools.money -= price; true } else { false }
+                        };
+                        if paid {
                             let item = crate::raws::spawn_named_item(&RAWS.lock().unwrap(), &mut self.ecs, &tag, SpawnType::Carried{ by: player_entity });
-                            //log message
-                            if item.is_some()  {
-                                let ent = item.unwrap();
-                                let mut gamelog = self.ecs.write_resource::<GameLog>();
-                                let names = self.ecs.read_storage::<Name>();
-                                let item_name = names.get(ent);
-                                if let Some(item_name) = item_name {
-                                    gamelog.entries.push(format!("You bought the {}.", &item_name.name));
+                            if let Some(item) = item {
+                                //log message
+                                {
+                                    let mut gamelog = self.ecs.write_resource::<GameLog>();
+                                    if let Some(item_name) = self.ecs.read_storage::<Name>().get(item) {
+                                        gamelog.push(gamelog::LogEntry::new(gamelog::LogCategory::Loot).append("You bought the ").item_name(&item_name.name).append("."));
+                                    }
                                 }
+                                inventory_system::merge_stack(&mut self.ecs, item);
+                            } else {
+                                //nothing to hand over, so give the money back
+                                self.ecs.write_storage::<Pools>().get_mut(player_entity).unwrap().money += price;
                             }
                         }
                     }
-                    gui::VendorResult::BuyMode => newrunstate = RunState::ShowVendor{ vendor, mode: VendorMode::Buy },
-                    gui::VendorResult::SellMode => newrunstate = RunState::ShowVendor{ vendor, mode: VendorMode::Sell }
+                    gui::VendorResult::BuyMode => newrunstate = RunState::ShowVendor{ vendor, mode: VendorMode::Buy, menu: list_menu::ListMenu::new() },
+                    gui::VendorResult::SellMode => newrunstate = RunState::ShowVendor{ vendor, mode: VendorMode::Sell, menu: list_menu::ListMenu::new() },
+                    gui::VendorResult::Browse{ menu } => newrunstate = RunState::ShowVendor{ vendor, mode, menu }
+                }
+            }
+            RunState::ShowExamineMenu{menu} => {
+                match gui::examine_menu(self, ctx, menu) {
+                    list_menu::ListMenuResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    list_menu::ListMenuResult::NoResponse => {}
+                    list_menu::ListMenuResult::Browse(menu) => newrunstate = RunState::ShowExamineMenu{ menu },
+                    list_menu::ListMenuResult::Selected(item) => newrunstate = RunState::ExamineItem{ item, menu }
+                }
+            }
+            RunState::ExamineItem{item, menu} => {
+                match gui::examine_item(self, ctx, item) {
+                    gui::ItemMenuResult::Cancel => newrunstate = RunState::ShowExamineMenu{ menu },
+                    _ => {}
+                }
+            }
+            RunState::ShowCharacterSheet => {
+                match gui::character_sheet(self, ctx) {
+                    gui::CharacterSheetResult::Cancel => newrunstate = RunState::AwaitingInput,
+                    gui::CharacterSheetResult::NoResponse => {}
+                    gui::CharacterSheetResult::PutOn => newrunstate = RunState::ShowEquipItem{ menu: list_menu::ListMenu::new() },
+                    gui::CharacterSheetResult::Unequip{ item } => {
+                        let mut intent = self.ecs.write_storage::<WantsToRemoveItem>();
+                        intent.insert(*self.ecs.fetch::<Entity>(), WantsToRemoveItem{ item }).expect("Unable to insert intent");
+                        newrunstate = RunState::Ticking;
+                    }
+                }
+            }
+            RunState::ShowEquipItem{menu} => {
+                match gui::equip_item_menu(self, ctx, menu) {
+                    list_menu::ListMenuResult::Cancel => newrunstate = RunState::ShowCharacterSheet,
+                    list_menu::ListMenuResult::NoResponse => {}
+                    list_menu::ListMenuResult::Browse(menu) => newrunstate = RunState::ShowEquipItem{ menu },
+                    list_menu::ListMenuResult::Selected(item) => {
+                        let mut intent = self.ecs.write_storage::<WantsToUseItem>();
+                        intent.insert(*self.ecs.fetch::<Entity>(), WantsToUseItem{ item, target: None }).expect("Unable to insert intent");
+                        newrunstate = RunState::Ticking;
+                    }
+                }
+            }
+            RunState::ShowLog{scroll, filter} => {
+                match gui::show_log(self, ctx, scroll, filter) {
+                    gui::LogViewResult::Close => newrunstate = RunState::AwaitingInput,
+                    gui::LogViewResult::Browse{ scroll, filter } => newrunstate = RunState::ShowLog{ scroll, filter }
                 }
             }
+            RunState::ShowOverview{x, y, zoomed_out} => {
+                match gui::show_map_overview(self, ctx, x, y, zoomed_out) {
+                    gui::OverviewResult::Close => newrunstate = RunState::AwaitingInput,
+                    gui::OverviewResult::Browse{ x, y, zoomed_out } => newrunstate = RunState::ShowOverview{ x, y, zoomed_out }
+                }
+            }
+            RunState::NextLevel => {
+                self.goto_next_level();
+                self.mapgen_next_state = Some(RunState::PreRun);
+                newrunstate = RunState::MapGeneration;
+            }
             RunState::MapGeneration => {
-                if !SHOW_MAPGEN_VISUALIZER {
+                if !map_builders::mapgen_snapshots() || self.mapgen_history.is_empty() {
                     newrunstate = self.mapgen_next_state.unwrap();
-                }
-                ctx.cls();                
-    
-                //paranoia
-                if self.mapgen_history.len() > 0 {
+                } else {
+                    ctx.cls();
                     //draw mapgen
-                    camera::render_debug_map(&self.mapgen_history[self.mapgen_index], ctx);
-    
-                    self.mapgen_timer += ctx.frame_time_ms;
-                    if self.mapgen_timer > 300.0 {
-                        self.mapgen_timer = 0.0;
-                        self.mapgen_index += 1;
-                        if self.mapgen_index == self.mapgen_history.len() {
+                    let snapshot = &self.mapgen_history[self.mapgen_index];
+                    camera::render_debug_map(&snapshot.map, ctx);
+                    camera::render_debug_overlays(snapshot, &self.mapgen_overlays, ctx);
+                    gui::draw_mapgen_info(ctx, snapshot, self.mapgen_index, self.mapgen_history.len(), self.mapgen_paused, &self.mapgen_overlays, &self.mapgen_message);
+
+                    let last = self.mapgen_history.len() - 1;
+                    match gui::mapgen_input(ctx) {
+                        gui::MapgenAction::NoAction => {}
+                        gui::MapgenAction::TogglePause => self.mapgen_paused = !self.mapgen_paused,
+                        gui::MapgenAction::StepForward => {
+                            self.mapgen_paused = true;
+                            if self.mapgen_index < last { self.mapgen_index += 1; }
+                        }
+                        gui::MapgenAction::StepBack => {
+                            self.mapgen_paused = true;
+                            if self.mapgen_index > 0 { self.mapgen_index -= 1; }
+                        }
+                        gui::MapgenAction::Rewind => {
+                            self.mapgen_index = 0;
+                            self.mapgen_timer = 0.0;
+                        }
+                        gui::MapgenAction::ToggleRooms => self.mapgen_overlays.rooms = !self.mapgen_overlays.rooms,
+                        gui::MapgenAction::ToggleCorridors => self.mapgen_overlays.corridors = !self.mapgen_overlays.corridors,
+                        gui::MapgenAction::ToggleSubmaps => self.mapgen_overlays.submaps = !self.mapgen_overlays.submaps,
+                        gui::MapgenAction::ToggleSpawns => self.mapgen_overlays.spawns = !self.mapgen_overlays.spawns,
+                        gui::MapgenAction::Export => {
+                            let snapshot = &self.mapgen_history[self.mapgen_index];
+                            let result = map_builders::export_map(&snapshot.map, &snapshot.spawns, "exported_map");
+                            self.mapgen_message = Some(match result { Ok(msg) | Err(msg) => msg });
+                        }
+                        gui::MapgenAction::Skip => newrunstate = self.mapgen_next_state.unwrap(),
+                        gui::MapgenAction::Disable => {
+                            //no more snapshots for future maps either
+                            map_builders::set_mapgen_snapshots(false);
                             newrunstate = self.mapgen_next_state.unwrap();
                         }
                     }
+
+                    if !self.mapgen_paused {
+                        self.mapgen_timer += ctx.frame_time_ms;
+                        if self.mapgen_timer > 300.0 {
+                            self.mapgen_timer = 0.0;
+                            if self.mapgen_index == last {
+                                newrunstate = self.mapgen_next_state.unwrap();
+                            } else {
+                                self.mapgen_index += 1;
+                            }
+                        }
+                    }
                 }
             }
         }
@@ -289,6 +444,7 @@ impl GameState for State {
         }
 
         damage_system::delete_the_dead(&mut self.ecs);
+        web_ui::end_frame();
     }
 }
 
@@ -328,8 +484,6 @@ impl State {
         triggers.run_now(&self.ecs);
         let mut melee = MeleeCombatSystem{};
         melee.run_now(&self.ecs);
-        let mut damage = DamageSystem{};
-        damage.run_now(&self.ecs);
         //items
         let mut pickup = ItemCollectionSystem{};
         pickup.run_now(&self.ecs);
@@ -339,6 +493,10 @@ impl State {
         drop_items.run_now(&self.ecs);
         let mut item_remove = ItemRemoveSystem{};
         item_remove.run_now(&self.ecs);
+        let mut throw_items = ItemThrowSystem{};
+        throw_items.run_now(&self.ecs);
+        //applies whatever the triggers and items above queued up; needs the whole World, so it can't be a system
+        effects::run_effects_queue(&mut self.ecs);
         let mut hunger = hunger_system::HungerSystem{};
         hunger.run_now(&self.ecs);
         //goes last because nearly anything can in theory produce one of those
@@ -351,14 +509,17 @@ impl State {
 }
 
 impl State {
-    fn generate_world(&mut self) {
+    fn generate_world(&mut self, depth : i32) {
         self.mapgen_index = 0;
         self.mapgen_timer = 0.0;
         self.mapgen_history.clear();
+        self.mapgen_paused = false;
+        self.mapgen_message = None;
         let mut rng = self.ecs.write_resource::<rltk::RandomNumberGenerator>();
-        let mut builder = map_builders::random_builder(&mut rng, 160, 120); //80,60 usually unless testing
         console::log("Generating world...");
-        builder.build_map(&mut rng);
+        //the town is always on top; below it, any kind of level that fits the depth
+        let level_type = if depth == 1 { Some("town") } else { None };
+        let mut builder = map_builders::build_valid_map(level_type, depth, &mut rng, 160, 120); //80,60 usually unless testing
 
         //prevent borrow checker errors
         std::mem::drop(rng);
@@ -379,16 +540,62 @@ impl State {
 
         //spawn monsters
         builder.spawn_entities(&mut self.ecs);
-        //spawn player
+        //spawn player, or move them if they came down the stairs
         let (player_x, player_y) = (player_start.x, player_start.y);
-        let player_entity = spawner::player(&mut self.ecs, player_x, player_y);
-        self.ecs.insert(player_entity);
+        let existing = self.ecs.try_fetch::<Entity>().map(|e| *e).filter(|e| self.ecs.is_alive(*e));
+        match existing {
+            None => {
+                let player_entity = spawner::player(&mut self.ecs, player_x, player_y);
+                self.ecs.insert(player_entity);
+            }
+            Some(player_entity) => {
+                if let Some(pos) = self.ecs.write_storage::<Position>().get_mut(player_entity) {
+                    pos.x = player_x;
+                    pos.y = player_y;
+                }
+                if let Some(vs) = self.ecs.write_storage::<Viewshed>().get_mut(player_entity) {
+                    vs.dirty = true;
+                }
+            }
+        }
         //special treatment for player location
         self.ecs.insert(Point::new(player_x, player_y));
 
     }
 
+    //everything but the player and what they carry stays behind
+    fn entities_to_remove_on_level_change(&self) -> Vec<Entity> {
+        let entities = self.ecs.entities();
+        let player_entity = *self.ecs.fetch::<Entity>();
+        let backpack = self.ecs.read_storage::<InBackpack>();
+        let equipped = self.ecs.read_storage::<Equipped>();
+
+        let mut to_delete = Vec::new();
+        for entity in entities.join() {
+            let carried = backpack.get(entity).map_or(false, |b| b.owner == player_entity)
+                || equipped.get(entity).map_or(false, |e| e.owner == player_entity);
+            if entity != player_entity && !carried {
+                to_delete.push(entity);
+            }
+        }
+        to_delete
+    }
+
+    fn goto_next_level(&mut self) {
+        self.travel = None;
+        for target in self.entities_to_remove_on_level_change().iter() {
+            self.ecs.delete_entity(*target).expect("Unable to delete entity");
+        }
+
+        let depth = self.ecs.fetch::<Map>().depth + 1;
+        self.generate_world(depth);
+
+        let mut gamelog = self.ecs.fetch_mut::<GameLog>();
+        gamelog.add(gamelog::LogCategory::System, format!("You descend to level {}.", depth));
+    }
+
     fn game_over_cleanup(&mut self) {
+        self.travel = None;
         // Delete everything
         let mut to_delete = Vec::new();
         for e in self.ecs.entities().join() {
@@ -398,18 +605,15 @@ impl State {
             self.ecs.delete_entity(*del).expect("Deletion failed");
         }
     
-        // Spawn a new player
-        {
-            let player_entity = spawner::player(&mut self.ecs, 0, 0);
-            let mut player_entity_writer = self.ecs.write_resource::<Entity>();
-            *player_entity_writer = player_entity;
-        }
-    
-        // Build a new map and place the player
-        self.generate_world();                                          
+        // Build a new map and a new player on it
+        self.generate_world(1);                                          
     }
 }
 
+//in characters; the screen layout is worked out from whatever these are
+const CONSOLE_WIDTH : u32 = 80;
+const CONSOLE_HEIGHT : u32 = 60;
+
 // Auto-starts on page load
 //start section of the executable may not literally point to main
 //#[wasm_bindgen(start)]
@@ -418,7 +622,7 @@ pub fn main() {
 
     panic::set_hook(Box::new(console_error_panic_hook::hook));
     
-    let mut context = Rltk::init_simple8x8(80, 60, "RLTK Web roguelike", "resources");
+    let mut context = Rltk::init_simple8x8(CONSOLE_WIDTH, CONSOLE_HEIGHT, "RLTK Web roguelike", "resources");
     context.with_post_scanlines(true);
 
     //ECS takes more lines to set up
@@ -428,7 +632,11 @@ pub fn main() {
         mapgen_next_state : Some(RunState::MainMenu{ menu_selection: gui::MainMenuSelection::NewGame}),
         mapgen_index : 0,
         mapgen_history: Vec::new(),
-        mapgen_timer: 0.0
+        mapgen_timer: 0.0,
+        mapgen_paused: false,
+        mapgen_message: None,
+        mapgen_overlays: camera::DebugOverlays::default(),
+        travel: None
     };
     gs.ecs.register::<Position>();
     gs.ecs.register::<Renderable>();
@@ -439,15 +647,17 @@ pub fn main() {
     gs.ecs.register::<Pools>();
     gs.ecs.register::<Attributes>();
     gs.ecs.register::<WantsToMelee>();
-    gs.ecs.register::<SufferDamage>();
     gs.ecs.register::<Item>();
     gs.ecs.register::<MedItem>();
     gs.ecs.register::<Consumable>();
+    gs.ecs.register::<Charges>();
     gs.ecs.register::<InBackpack>();
     gs.ecs.register::<EquipmentChanged>();
     gs.ecs.register::<Ranged>();
     gs.ecs.register::<InflictsDamage>();
     gs.ecs.register::<AreaOfEffect>();
+    gs.ecs.register::<DamageFalloff>();
+    gs.ecs.register::<SparesAllies>();
     gs.ecs.register::<Confusion>();
     gs.ecs.register::<ProvidesFood>();
     gs.ecs.register::<ProvidesQuench>();
@@ -458,6 +668,9 @@ pub fn main() {
     gs.ecs.register::<WantsToPickupItem>();
     gs.ecs.register::<WantsToUseItem>();
     gs.ecs.register::<WantsToDropItem>();
+    gs.ecs.register::<WantsToThrowItem>();
+    gs.ecs.register::<Fragile>();
+    gs.ecs.register::<Stackable>();
     gs.ecs.register::<WantsToRemoveItem>();
     gs.ecs.register::<Equippable>();
     gs.ecs.register::<Equipped>();
@@ -466,9 +679,16 @@ pub fn main() {
     gs.ecs.register::<Hidden>();
     gs.ecs.register::<EntryTrigger>();
     gs.ecs.register::<SingleActivation>();
+    gs.ecs.register::<Teleports>();
+    gs.ecs.register::<Alarm>();
+    gs.ecs.register::<SpawnsEntity>();
+    gs.ecs.register::<DeployedBy>();
+    gs.ecs.register::<Immobilized>();
     gs.ecs.register::<EntityMoved>();
     gs.ecs.register::<BlocksVisibility>();
     gs.ecs.register::<Door>();
+    gs.ecs.register::<Locked>();
+    gs.ecs.register::<Lockpick>();
     gs.ecs.register::<LightSource>();
     gs.ecs.register::<Faction>();
     gs.ecs.register::<Quips>();
@@ -480,40 +700,24 @@ pub fn main() {
 
     //load JSON data
     raws::load_raws();
+    input::load_bindings();
 
     //placeholders so that generate_world has stuff to fill
     gs.ecs.insert(Map::new(80,50));
     gs.ecs.insert(rltk::RandomNumberGenerator::new());
 
-    gs.generate_world();
+    let batch_stats = mapgen_batch_stats();
+    if batch_stats > 0 {
+        let mut rng = gs.ecs.write_resource::<rltk::RandomNumberGenerator>();
+        map_builders::batch_stats(batch_stats, &mut rng, 160, 120);
+    }
+    gs.generate_world(1);
 
-    gs.ecs.insert(gamelog::GameLog{ entries : vec!["Welcome to Neon Twilight!".to_string()] });
+    let mut log = gamelog::GameLog::new();
+    log.add(gamelog::LogCategory::System, "Welcome to Neon Twilight!");
+    gs.ecs.insert(log);
     gs.ecs.insert(particle_system::ParticleBuilder::new());
 
-    //register html buttons
-    rltk::register_html_button("go_nw");
-    rltk::register_html_button("go_n");
-    rltk::register_html_button("go_ne");
-    rltk::register_html_button("go_w");
-    rltk::register_html_button("go_e");
-    rltk::register_html_button("go_sw");
-    rltk::register_html_button("go_s");
-    rltk::register_html_button("go_se");
-    rltk::register_html_button("go_wait");
-    //non-movement
-    rltk::register_html_button("confirm");
-    rltk::register_html_button("get");
-    rltk::register_html_button("inven");
-    rltk::register_html_button("drop");
-    rltk::register_html_button("remove");
-    //inventory
-    rltk::register_html_button("escape");
-    rltk::register_html_button("tab");
-    rltk::register_html_button("a");
-    rltk::register_html_button("b");
-    rltk::register_html_button("c");
-    rltk::register_html_button("d");
-
-
+    //no html buttons to register; the web page builds its own from web_ui::web_ui_state
     rltk::main_loop(context, gs);
 } 
@@ -3,6 +3,7 @@ use std::cmp::{max, min};
 extern crate specs;
 use specs::prelude::*;
 use std::collections::HashSet;
+use std::sync::RwLock;
 
 // We'll allow map tiles to be either a wall or a floor. We're deriving PartialEq so we don't
 // have to match on it every time. We'll make it a copy type because it's really just an int.
@@ -13,6 +14,61 @@ pub enum TileType {
     Floor,
     FloorIndoor,
     DownStairs,
+    UpStairs,
+    Road,
+    Sidewalk,
+    Bridge,
+    Grass,
+    Rubble,
+    ShallowWater,
+    DeepWater,
+    Window,
+    Fence,
+}
+
+const ALL_TILE_TYPES : [TileType; 15] = [TileType::Wall, TileType::Tree, TileType::Floor, TileType::FloorIndoor,
+    TileType::DownStairs, TileType::UpStairs, TileType::Road, TileType::Sidewalk, TileType::Bridge, TileType::Grass,
+    TileType::Rubble, TileType::ShallowWater, TileType::DeepWater, TileType::Window, TileType::Fence];
+
+/// How a tile type behaves and looks, as set out in data/tiles.json
+#[derive(Copy, Clone)]
+pub struct TileProperties {
+    pub walkable : bool,
+    pub opaque : bool,
+    pub cost : f32,
+    pub glyph : u8,
+    pub template : char,
+    pub fg : RGB
+}
+
+//anything tiles.json forgot about is solid and stands out
+const UNKNOWN_TILE : TileProperties = TileProperties{ walkable : false, opaque : true, cost : 1.0, glyph : b'?', template : '?', fg : RGB{ r : 1.0, g : 0.0, b : 1.0 } };
+
+//indexed by tile type; the map asks for these constantly, so they're kept out of RAWS
+lazy_static! {
+    static ref TILE_PROPERTIES : RwLock<Vec<Option<TileProperties>>> = RwLock::new(Vec::new());
+}
+
+pub fn set_tile_properties(tiles : Vec<(TileType, TileProperties)>) {
+    let mut table = TILE_PROPERTIES.write().unwrap();
+    table.clear();
+    for (tile_type, properties) in tiles.into_iter() {
+        let idx = tile_type as usize;
+        if table.len() <= idx { table.resize(idx + 1, None); }
+        table[idx] = Some(properties);
+    }
+}
+
+pub fn tile_properties(tt : TileType) -> TileProperties {
+    TILE_PROPERTIES.read().unwrap().get(tt as usize).copied().flatten().unwrap_or(UNKNOWN_TILE)
+}
+
+/// The template glyph of every tile type in data/tiles.json, for prefabs and the map exporter
+pub fn template_glyphs() -> Vec<(char, TileType)> {
+    let table = TILE_PROPERTIES.read().unwrap();
+    ALL_TILE_TYPES.iter()
+        .filter_map(|tt| table.get(*tt as usize).copied().flatten().map(|p| (p.template, *tt)))
+        .collect()
 }
 
 //After the refactor, this is just the data structure
@@ -28,7 +84,8 @@ pub struct Map {
     pub blocked : Vec<bool>,
     pub view_blocked : HashSet<usize>,
     pub light : Vec<rltk::RGB>,
-    pub tile_content : Vec<Vec<Entity>>
+    pub tile_content : Vec<Vec<Entity>>,
+    pub depth : i32
 }
 
 impl Map {
@@ -58,6 +115,7 @@ impl Map {
             view_blocked : HashSet::new(),
             light: vec![rltk::RGB::from_f32(0.0, 0.0, 0.0); map_count],
             tile_content : vec![Vec::new(); map_count],
+            depth : 1
         }
     }
 
@@ -82,6 +140,11 @@ impl Map {
         !self.blocked[idx as usize]
     }
 
+    //what pathfinding pays to step onto a tile
+    fn move_cost(&self, idx:i32) -> f32 {
+        tile_cost(self.tiles[idx as usize])
+    }
+
 }
 
     /// Makes a map with solid boundaries and 400 randomly placed walls. No guarantees that it won't
@@ -138,17 +201,17 @@ impl BaseMap for Map {
         let x = idx % self.width;
         let y = idx / self.width;
     
-        // Cardinal directions
-        if self.is_exit_valid(x-1, y) { exits.push((idx-1, 1.0)) };
-        if self.is_exit_valid(x+1, y) { exits.push((idx+1, 1.0)) };
-        if self.is_exit_valid(x, y-1) { exits.push((idx-self.width, 1.0)) };
-        if self.is_exit_valid(x, y+1) { exits.push((idx+self.width, 1.0)) };
-
-        // Diagonals
-        if self.is_exit_valid(x-1, y-1) { exits.push(((idx-self.width)-1, 1.45)); }
-        if self.is_exit_valid(x+1, y-1) { exits.push(((idx-self.width)+1, 1.45)); }
-        if self.is_exit_valid(x-1, y+1) { exits.push(((idx+self.width)-1, 1.45)); }
-        if self.is_exit_valid(x+1, y+1) { exits.push(((idx+self.width)+1, 1.45)); }
+        // Cardinal directions cost whatever the tile we step onto costs
+        if self.is_exit_valid(x-1, y) { exits.push((idx-1, self.move_cost(idx-1))) };
+        if self.is_exit_valid(x+1, y) { exits.push((idx+1, self.move_cost(idx+1))) };
+        if self.is_exit_valid(x, y-1) { exits.push((idx-self.width, self.move_cost(idx-self.width))) };
+        if self.is_exit_valid(x, y+1) { exits.push((idx+self.width, self.move_cost(idx+self.width))) };
+
+        // Diagonals, a bit more
+        if self.is_exit_valid(x-1, y-1) { exits.push(((idx-self.width)-1, self.move_cost((idx-self.width)-1) * 1.45)); }
+        if self.is_exit_valid(x+1, y-1) { exits.push(((idx-self.width)+1, self.move_cost((idx-self.width)+1) * 1.45)); }
+        if self.is_exit_valid(x-1, y+1) { exits.push(((idx+self.width)-1, self.move_cost((idx+self.width)-1) * 1.45)); }
+        if self.is_exit_valid(x+1, y+1) { exits.push(((idx+self.width)+1, self.move_cost((idx+self.width)+1) * 1.45)); }
     
         exits
     }
@@ -172,17 +235,13 @@ impl Algorithm2D for Map {
 
 //helpers
 pub fn tile_walkable(tt : TileType) -> bool {
-    match tt {
-        TileType::Floor | TileType::FloorIndoor | TileType::DownStairs 
-            => true,
-        _ => false        
-    }
+    tile_properties(tt).walkable
 }
 
 pub fn tile_opaque(tt : TileType) -> bool {
-    match tt {
-        TileType::Wall | TileType::Tree 
-            => true,
-        _ => false
-    }
+    tile_properties(tt).opaque
+}
+
+pub fn tile_cost(tt : TileType) -> f32 {
+    tile_properties(tt).cost
 }
\ No newline at end of file
@@ -28,7 +28,7 @@ impl BSPInteriorBuilder {
         self.rects.clear();
         self.rects.push( Rect::new(1, 1, build_data.map.width-2, build_data.map.height-2) ); // Start with a single map-sized rectangle
         let first_room = self.rects[0];
-        self.add_subrects(first_room, rng); // Divide the first room
+        subdivide(&mut self.rects, first_room, MIN_ROOM_SIZE, rng); // Divide the first room
 
         let rooms_copy = self.rects.clone();
         for r in rooms_copy.iter() {
@@ -63,38 +63,40 @@ impl BSPInteriorBuilder {
         // starting position now handled by room_based_starting.rs
         //spawning now handled by room_based_spawner.rs
     }
+}
 
-    //BSP subdivision, horizontal or vertical
-    fn add_subrects(&mut self, rect : Rect, rng : &mut RandomNumberGenerator) {
-        // Remove the last rect from the list
-        if !self.rects.is_empty() {
-            self.rects.remove(self.rects.len() - 1);
-        }
+//BSP subdivision, horizontal or vertical
+//the split leaves a one tile gap between the halves, which ends up as the wall between them
+//town buildings use this too, scoped to the inside of the building and with a smaller minimum
+pub fn subdivide(rects : &mut Vec<Rect>, rect : Rect, min_room_size : i32, rng : &mut RandomNumberGenerator) {
+    // Remove the last rect from the list
+    if !rects.is_empty() {
+        rects.remove(rects.len() - 1);
+    }
 
-        // Calculate boundaries
-        let width  = rect.x2 - rect.x1;
-        let height = rect.y2 - rect.y1;
-        let half_width = width / 2;
-        let half_height = height / 2;
+    // Calculate boundaries
+    let width  = rect.x2 - rect.x1;
+    let height = rect.y2 - rect.y1;
+    let half_width = width / 2;
+    let half_height = height / 2;
 
-        let split = rng.roll_dice(1, 4);
+    let split = rng.roll_dice(1, 4);
 
-        if split <= 2 {
-            // Horizontal split
-            let h1 = Rect::new( rect.x1, rect.y1, half_width-1, height );
-            self.rects.push( h1 );
-            if half_width > MIN_ROOM_SIZE { self.add_subrects(h1, rng); }
-            let h2 = Rect::new( rect.x1 + half_width, rect.y1, half_width, height );
-            self.rects.push( h2 );
-            if half_width > MIN_ROOM_SIZE { self.add_subrects(h2, rng); }
-        } else {
-            // Vertical split
-            let v1 = Rect::new( rect.x1, rect.y1, width, half_height-1 );
-            self.rects.push(v1);
-            if half_height > MIN_ROOM_SIZE { self.add_subrects(v1, rng); }
-            let v2 = Rect::new( rect.x1, rect.y1 + half_height, width, half_height );
-            self.rects.push(v2);
-            if half_height > MIN_ROOM_SIZE { self.add_subrects(v2, rng); }
-        }
+    if split <= 2 {
+        // Horizontal split
+        let h1 = Rect::new( rect.x1, rect.y1, half_width-1, height );
+        rects.push( h1 );
+        if half_width > min_room_size { subdivide(rects, h1, min_room_size, rng); }
+        let h2 = Rect::new( rect.x1 + half_width, rect.y1, half_width, height );
+        rects.push( h2 );
+        if half_width > min_room_size { subdivide(rects, h2, min_room_size, rng); }
+    } else {
+        // Vertical split
+        let v1 = Rect::new( rect.x1, rect.y1, width, half_height-1 );
+        rects.push(v1);
+        if half_height > min_room_size { subdivide(rects, v1, min_room_size, rng); }
+        let v2 = Rect::new( rect.x1, rect.y1 + half_height, width, half_height );
+        rects.push(v2);
+        if half_height > min_room_size { subdivide(rects, v2, min_room_size, rng); }
     }
-}
\ No newline at end of file
+}
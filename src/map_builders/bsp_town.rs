@@ -1,9 +1,21 @@
 use super::{InitialMapBuilder, MetaMapBuilder, BuilderMap, Rect, TileType, Position };
+use super::bsp_interior::subdivide;
+use crate::map::tile_walkable;
 use rltk::RandomNumberGenerator;
 //console is RLTK's wrapper around either println or the web console macro
 use rltk::{console};
 
 const MIN_ROOM_SIZE : i32 = 8;
+//roughly how far apart the streets are, and how wide
+const STREET_SPACING : i32 = 24;
+const STREET_WIDTH : i32 = 2;
+//residential buildings at least this big get split into apartments
+const APARTMENT_AREA : i32 = 64;
+//insides at least this wide and tall get split into rooms, none smaller than the room size
+const INTERIOR_MIN_SIZE : i32 = 7;
+const INTERIOR_ROOM_SIZE : i32 = 3;
+//buildings this big get a back door as well
+const BACK_DOOR_AREA : i32 = 48;
 
 pub struct BSPTownBuilder {
     rects: Vec<Rect>
@@ -31,13 +43,36 @@ impl MetaMapBuilder for BSPTownBuilder {
     }
 }
 
-#[derive(Debug)]
-enum BuildingTag {
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum District { Market, Residential, Industrial, Police }
+
+#[derive(PartialEq, Copy, Clone, Debug)]
+pub enum BuildingTag {
     Pub,
+    Clinic,
+    GunShop,
+    Grocery,
+    PoliceStation,
+    Apartments,
     Hovel,
+    Warehouse,
     Unassigned,
 }
 
+/// Everything we know about a town building, kept around for AI schedules and the like
+#[derive(Clone, Debug)]
+#[allow(dead_code)]
+pub struct Building {
+    pub rect : Rect,
+    pub tag : BuildingTag,
+    pub district : District,
+    pub door : usize,
+    pub back_door : Option<usize>,
+    //empty for open plan buildings
+    pub rooms : Vec<Rect>,
+    pub basement : Option<usize>
+}
+
 impl BSPTownBuilder {
     #[allow(dead_code)]
     pub fn new() -> Box<BSPTownBuilder> {
@@ -77,6 +112,10 @@ impl BSPTownBuilder {
         }
         build_data.take_snapshot();
 
+        //streets go in first, so buildings keep clear of them
+        let blocks = self.lay_streets(rng, build_data, sx, sy, endx, endy);
+        let districts = self.assign_districts(rng, &blocks);
+        build_data.take_snapshot();
 
         //place walls around
         //Rust is weird, ranges are inclusive at the beginning but exclusive at the end
@@ -128,6 +167,7 @@ impl BSPTownBuilder {
 
         //let rooms_copy = self.rects.clone();
         let rooms_copy = rooms.clone();
+        let mut doors : Vec<usize> = Vec::new();
         for r in rooms_copy.iter() {
             let room = *r;
             //rooms.push(room);
@@ -158,65 +198,371 @@ impl BSPTownBuilder {
                 1 => { 
                     let idx = build_data.map.xy_idx(cent.0, room.y1); //north
                     build_data.map.tiles[idx] = TileType::Floor;
+                    doors.push(idx);
                 }
                 2 => { 
                     let idx = build_data.map.xy_idx(cent.0, room.y2-1); //south
                     build_data.map.tiles[idx] = TileType::Floor;
+                    doors.push(idx);
                 }
                 3 => { 
                     let idx = build_data.map.xy_idx(room.x1, cent.1); //west
                     build_data.map.tiles[idx] = TileType::Floor;
+                    doors.push(idx);
                 }
                 _ => { 
                     let idx = build_data.map.xy_idx(room.x2-1, cent.1); //east
                     build_data.map.tiles[idx] = TileType::Floor;
+                    doors.push(idx);
                 }
             }
             build_data.take_snapshot();
         }
 
-        console::log(format!("Buildings: {:?}", rooms_copy));
-        let building_size = self.sort_buildings(&rooms_copy);
-        console::log(format!("Buildings sorted: {:?}", building_size));
-        self.building_factory(rng, build_data, &rooms_copy, &building_size);
+        let mut buildings = self.assign_buildings(&rooms_copy, &doors, &districts);
+        console::log(format!("Buildings: {:?}", buildings.iter().map(|b| (b.tag, b.district)).collect::<Vec<(BuildingTag, District)>>()));
+        self.building_factory(rng, build_data, &mut buildings);
+        self.landscape(rng, build_data, &buildings, &districts, sx, sy, endx, endy);
+        build_data.take_snapshot();
+        build_data.buildings = Some(buildings);
+        build_data.districts = Some(districts);
+    }
+
+    //a grid of streets, a little uneven; hands back the blocks in between
+    fn lay_streets(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap, sx : i32, sy : i32, endx : i32, endy : i32) -> Vec<Rect> {
+        let mut columns : Vec<i32> = Vec::new();
+        let mut x = sx + STREET_SPACING + rng.roll_dice(1, 7) - 4;
+        while x < endx - STREET_SPACING / 2 {
+            columns.push(x);
+            x += STREET_SPACING + rng.roll_dice(1, 7) - 4;
+        }
+        let mut rows : Vec<i32> = Vec::new();
+        let mut y = sy + STREET_SPACING + rng.roll_dice(1, 7) - 4;
+        while y < endy - STREET_SPACING / 2 {
+            rows.push(y);
+            y += STREET_SPACING + rng.roll_dice(1, 7) - 4;
+        }
+
+        for y in sy .. endy {
+            for x in sx .. endx {
+                let on_street = columns.iter().any(|c| x >= *c && x < c + STREET_WIDTH) || rows.iter().any(|r| y >= *r && y < r + STREET_WIDTH);
+                if on_street {
+                    let idx = build_data.map.xy_idx(x, y);
+                    build_data.map.tiles[idx] = TileType::Road;
+                }
+            }
+        }
+
+        //one of the north-south streets is a canal instead, with a bridge wherever a street crosses it.
+        //Only when there are crossings, or it would cut the town in two
+        if columns.len() >= 2 && !rows.is_empty() {
+            let canal = columns[(rng.roll_dice(1, columns.len() as i32) - 1) as usize];
+            for y in sy .. endy {
+                let crossing = rows.iter().any(|r| y >= *r && y < r + STREET_WIDTH);
+                for x in canal - 1 .. canal + STREET_WIDTH + 1 {
+                    let idx = build_data.map.xy_idx(x, y);
+                    build_data.map.tiles[idx] = if crossing { TileType::Bridge }
+                        else if x < canal || x >= canal + STREET_WIDTH { TileType::ShallowWater }
+                        else { TileType::DeepWater };
+                }
+            }
+        }
+
+        // The blocks are whatever lies between two streets (or a street and the edge of town)
+        let spans = |start : i32, end : i32, streets : &[i32]| -> Vec<(i32, i32)> {
+            let mut result = Vec::new();
+            let mut from = start;
+            for street in streets.iter() {
+                result.push((from, street - 1));
+                from = street + STREET_WIDTH;
+            }
+            result.push((from, end - 1));
+            result
+        };
+        let mut blocks : Vec<Rect> = Vec::new();
+        for (y1, y2) in spans(sy, endy, &rows[..]).iter() {
+            for (x1, x2) in spans(sx, endx, &columns[..]).iter() {
+                blocks.push(Rect::new(*x1, *y1, x2 - x1, y2 - y1));
+            }
+        }
+        blocks
     }
 
-    fn sort_buildings(&mut self, buildings: &Vec<Rect>) -> Vec<(usize, i32, BuildingTag)> 
+    //what's left outside once the buildings are up: sidewalks along the streets, lawns where people
+    //live and rubble where they work. The market square stays paved
+    #[allow(clippy::too_many_arguments)]
+    fn landscape(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap, buildings : &[Building],
+        districts : &[(Rect, District)], sx : i32, sy : i32, endx : i32, endy : i32)
     {
-        let mut building_size : Vec<(usize, i32, BuildingTag)> = Vec::new();
-        for (i,building) in buildings.iter().enumerate() {
-            let rect_width = i32::abs(building.x1 - building.x2);
-            let rect_height = i32::abs(building.y1 - building.y2);
-            building_size.push((
-                i,
-                rect_height * rect_width,
-                BuildingTag::Unassigned
-            ));
+        let in_building = |x : i32, y : i32| buildings.iter().any(|b| x >= b.rect.x1 && x < b.rect.x2 && y >= b.rect.y1 && y < b.rect.y2);
+        let width = build_data.map.width as usize;
+        for y in sy .. endy {
+            for x in sx .. endx {
+                let idx = build_data.map.xy_idx(x, y);
+                if build_data.map.tiles[idx] != TileType::Floor || in_building(x, y) { continue; }
+
+                let beside_street = [idx - 1, idx + 1, idx - width, idx + width].iter()
+                    .any(|n| build_data.map.tiles[*n] == TileType::Road || build_data.map.tiles[*n] == TileType::Bridge);
+                let district = districts.iter()
+                    .find(|(block, _)| x >= block.x1 && x <= block.x2 && y >= block.y1 && y <= block.y2)
+                    .map(|(_, d)| *d);
+                build_data.map.tiles[idx] = match district {
+                    _ if beside_street => TileType::Sidewalk,
+                    Some(District::Residential) | Some(District::Police) => TileType::Grass,
+                    Some(District::Industrial) if rng.roll_dice(1, 8) == 1 => TileType::Rubble,
+                    _ => TileType::Floor
+                };
+            }
+        }
+    }
+
+    //market in the middle, industry on the outskirts, homes in between, and one block for the police
+    fn assign_districts(&mut self, rng : &mut RandomNumberGenerator, blocks : &[Rect]) -> Vec<(Rect, District)> {
+        let mut districts : Vec<(Rect, District)> = blocks.iter().map(|b| (*b, District::Residential)).collect();
+        if districts.is_empty() { return districts; }
+
+        let (mut cx, mut cy) = (0, 0);
+        for b in blocks.iter() {
+            let c = b.center();
+            cx += c.0;
+            cy += c.1;
+        }
+        let centre = (cx / blocks.len() as i32, cy / blocks.len() as i32);
+        let mut order : Vec<usize> = (0..blocks.len()).collect();
+        order.sort_by_key(|i| {
+            let c = blocks[*i].center();
+            (c.0 - centre.0).pow(2) + (c.1 - centre.1).pow(2)
+        });
+
+        let market = usize::max(1, blocks.len() / 4);
+        let industrial = blocks.len() / 4;
+        for (n, i) in order.iter().enumerate() {
+            if n < market {
+                districts[*i].1 = District::Market;
+            } else if n >= blocks.len() - industrial {
+                districts[*i].1 = District::Industrial;
+            }
+        }
+
+        let residential : Vec<usize> = (0..districts.len()).filter(|i| districts[*i].1 == District::Residential).collect();
+        if !residential.is_empty() {
+            let police = residential[(rng.roll_dice(1, residential.len() as i32) - 1) as usize];
+            districts[police].1 = District::Police;
         }
-        building_size.sort_by(|a,b| b.1.cmp(&a.1));
-        building_size[0].2 = BuildingTag::Pub;
-        for b in building_size.iter_mut().skip(1) {
-            b.2 = BuildingTag::Hovel;
+        districts
+    }
+
+    fn assign_buildings(&mut self, buildings : &[Rect], doors : &[usize], districts : &[(Rect, District)]) -> Vec<Building> {
+        let mut result : Vec<Building> = buildings.iter().zip(doors.iter()).map(|(rect, door)| {
+            let (x, y) = rect.center();
+            let district = districts.iter()
+                .find(|(block, _)| x >= block.x1 && x <= block.x2 && y >= block.y1 && y <= block.y2)
+                .map_or(District::Residential, |(_, d)| *d);
+            Building{ rect : *rect, tag : BuildingTag::Unassigned, district, door : *door, back_door : None, rooms : Vec::new(), basement : None }
+        }).collect();
+        if result.is_empty() { return result; }
+
+        //biggest first
+        let area = |b : &Building| i32::abs(b.rect.x1 - b.rect.x2) * i32::abs(b.rect.y1 - b.rect.y2);
+        result.sort_by(|a, b| area(b).cmp(&area(a)));
+
+        //the player starts in the pub, so the biggest building always gets to be one
+        result[0].tag = BuildingTag::Pub;
+
+        //the one-off buildings want a district, but will settle for anywhere
+        for (tag, district) in [(BuildingTag::Clinic, District::Market), (BuildingTag::GunShop, District::Market),
+            (BuildingTag::PoliceStation, District::Police)].iter()
+        {
+            let pick = result.iter().position(|b| b.tag == BuildingTag::Unassigned && b.district == *district)
+                .or_else(|| result.iter().position(|b| b.tag == BuildingTag::Unassigned));
+            if let Some(i) = pick {
+                result[i].tag = *tag;
+            }
         }
 
-        building_size
+        for b in result.iter_mut().filter(|b| b.tag == BuildingTag::Unassigned) {
+            b.tag = match b.district {
+                District::Market => BuildingTag::Grocery,
+                District::Industrial => BuildingTag::Warehouse,
+                _ if area(&*b) >= APARTMENT_AREA => BuildingTag::Apartments,
+                _ => BuildingTag::Hovel
+            };
+        }
+        result
     }
 
     fn building_factory(&mut self, 
         rng: &mut rltk::RandomNumberGenerator, 
         build_data : &mut BuilderMap, 
-        buildings: &Vec<Rect>, 
-        building_index : &[(usize, i32, BuildingTag)]) 
+        buildings: &mut [Building]) 
     {
-        for (i,building) in buildings.iter().enumerate() {
-            let build_type = &building_index[i].2;
-            match build_type {
-                BuildingTag::Pub => self.build_pub(&building, build_data, rng),
+        for building in buildings.iter_mut() {
+            //shops, the pub and warehouses stay open plan
+            match building.tag {
+                BuildingTag::Apartments | BuildingTag::Clinic | BuildingTag::PoliceStation | BuildingTag::Hovel => {
+                    building.rooms = self.build_interior(&building.rect, build_data, rng);
+                }
                 _ => {}
             }
+            self.open_inside(&building.rect, building.door, build_data);
+            let area = i32::abs(building.rect.x1 - building.rect.x2) * i32::abs(building.rect.y1 - building.rect.y2);
+            if area >= BACK_DOOR_AREA {
+                building.back_door = self.back_door(building, build_data);
+            }
+            self.add_windows(building, build_data, rng);
+            if (building.tag == BuildingTag::Hovel || building.tag == BuildingTag::Warehouse) && rng.roll_dice(1, 6) == 1 {
+                building.basement = self.basement_stairs(&building.rect, build_data, rng);
+            }
+            build_data.take_snapshot();
+
+            //a door in every way in; apartments are residents only, front and back
+            let door = if building.tag == BuildingTag::Apartments { "Locked Door" } else { "Door" };
+            build_data.list_spawns.push((building.door, door.to_string()));
+            if let Some(back_door) = building.back_door {
+                build_data.list_spawns.push((back_door, door.to_string()));
+            }
+
+            match building.tag {
+                BuildingTag::Pub => self.build_pub(&building.rect, build_data, rng),
+                _ => self.furnish(&building.rect, &building_contents(building.tag), None, build_data, rng)
+            }
         }
     }
 
+    //splits the inside of a building into rooms, the same way the BSP interior builder splits a whole map
+    fn build_interior(&mut self, 
+        building : &Rect, 
+        build_data : &mut BuilderMap, 
+        rng: &mut rltk::RandomNumberGenerator) -> Vec<Rect>
+    {
+        let inside = Rect::new(building.x1+1, building.y1+1, building.x2-building.x1-2, building.y2-building.y1-2);
+        if inside.x2 - inside.x1 < INTERIOR_MIN_SIZE || inside.y2 - inside.y1 < INTERIOR_MIN_SIZE { return Vec::new(); }
+
+        let mut rooms = vec![inside];
+        subdivide(&mut rooms, inside, INTERIOR_ROOM_SIZE, rng);
+
+        //the gaps between the rooms become the internal walls
+        for y in inside.y1 .. inside.y2 {
+            for x in inside.x1 .. inside.x2 {
+                let idx = build_data.map.xy_idx(x, y);
+                build_data.map.tiles[idx] = TileType::Wall;
+            }
+        }
+        for room in rooms.iter() {
+            for y in room.y1 .. room.y2 {
+                for x in room.x1 .. room.x2 {
+                    let idx = build_data.map.xy_idx(x, y);
+                    build_data.map.tiles[idx] = TileType::FloorIndoor;
+                }
+            }
+        }
+        self.connect_rooms(&rooms, build_data);
+        rooms
+    }
+
+    //grows out from the first room, each time putting a door in the wall between a connected room and a
+    //neighbour that isn't yet, until every room can be reached
+    fn connect_rooms(&mut self, rooms : &[Rect], build_data : &mut BuilderMap) {
+        let mut connected = vec![false; rooms.len()];
+        if let Some(first) = connected.first_mut() { *first = true; }
+        loop {
+            let mut door = None;
+            'search: for (i, from) in rooms.iter().enumerate() {
+                if !connected[i] { continue; }
+                for (j, to) in rooms.iter().enumerate() {
+                    if connected[j] { continue; }
+                    if let Some(idx) = shared_wall_door(from, to, build_data) {
+                        door = Some((j, idx));
+                        break 'search;
+                    }
+                }
+            }
+            match door {
+                None => break,
+                Some((j, idx)) => {
+                    connected[j] = true;
+                    build_data.map.tiles[idx] = TileType::FloorIndoor;
+                    build_data.list_spawns.push((idx, "Door".to_string()));
+                }
+            }
+        }
+    }
+
+    //an internal wall may have ended up right behind a door
+    fn open_inside(&mut self, building : &Rect, door : usize, build_data : &mut BuilderMap) {
+        let (x, y) = ((door as i32) % build_data.map.width, (door as i32) / build_data.map.width);
+        let (dx, dy) = if y == building.y1 { (0, 1) }
+            else if y == building.y2-1 { (0, -1) }
+            else if x == building.x1 { (1, 0) }
+            else { (-1, 0) };
+        let inside = build_data.map.xy_idx(x + dx, y + dy);
+        if build_data.map.tiles[inside] == TileType::Wall {
+            build_data.map.tiles[inside] = TileType::FloorIndoor;
+        }
+    }
+
+    //straight across from the front door, if there's somewhere to step out to
+    fn back_door(&mut self, building : &Building, build_data : &mut BuilderMap) -> Option<usize> {
+        let rect = building.rect;
+        let cent = rect.center();
+        let (dx, dy) = ((building.door as i32) % build_data.map.width, (building.door as i32) / build_data.map.width);
+        let (x, y, out_x, out_y) = if dy == rect.y1 { (cent.0, rect.y2-1, 0, 1) }
+            else if dy == rect.y2-1 { (cent.0, rect.y1, 0, -1) }
+            else if dx == rect.x1 { (rect.x2-1, cent.1, 1, 0) }
+            else { (rect.x1, cent.1, -1, 0) };
+
+        let outside = build_data.map.xy_idx(x + out_x, y + out_y);
+        if !tile_walkable(build_data.map.tiles[outside]) { return None; }
+        let idx = build_data.map.xy_idx(x, y);
+        build_data.map.tiles[idx] = TileType::Floor;
+        self.open_inside(&rect, idx, build_data);
+        Some(idx)
+    }
+
+    //every so often a stretch of outside wall gets a window, but never a corner or right next to a door
+    fn add_windows(&mut self, building : &Building, build_data : &mut BuilderMap, rng : &mut RandomNumberGenerator) {
+        let r = building.rect;
+        //each outside wall tile, along with the way in
+        let mut walls : Vec<(i32, i32, i32, i32)> = Vec::new();
+        for x in r.x1+1 .. r.x2-1 {
+            walls.push((x, r.y1, 0, 1));
+            walls.push((x, r.y2-1, 0, -1));
+        }
+        for y in r.y1+1 .. r.y2-1 {
+            walls.push((r.x1, y, 1, 0));
+            walls.push((r.x2-1, y, -1, 0));
+        }
+
+        let width = build_data.map.width;
+        let mut doors = vec![building.door];
+        doors.extend(building.back_door);
+        for (x, y, in_x, in_y) in walls.iter() {
+            let idx = build_data.map.xy_idx(*x, *y);
+            let inside = build_data.map.xy_idx(x + in_x, y + in_y);
+            let near_door = doors.iter().any(|d| i32::abs(*d as i32 % width - x) + i32::abs(*d as i32 / width - y) <= 1);
+            if build_data.map.tiles[idx] == TileType::Wall && build_data.map.tiles[inside] == TileType::FloorIndoor 
+                && !near_door && rng.roll_dice(1, 4) == 1 
+            {
+                build_data.map.tiles[idx] = TileType::Window;
+            }
+        }
+    }
+
+    //a way down, tucked into one of the corners
+    fn basement_stairs(&mut self, building : &Rect, build_data : &mut BuilderMap, rng : &mut RandomNumberGenerator) -> Option<usize> {
+        let corners = [(building.x1+1, building.y1+1), (building.x2-2, building.y1+1), (building.x1+1, building.y2-2), (building.x2-2, building.y2-2)];
+        let first = rng.roll_dice(1, 4) - 1;
+        for n in 0..4 {
+            let (x, y) = corners[((first + n) % 4) as usize];
+            let idx = build_data.map.xy_idx(x, y);
+            if build_data.map.tiles[idx] == TileType::FloorIndoor && !build_data.list_spawns.iter().any(|(i, _)| *i == idx) {
+                build_data.map.tiles[idx] = TileType::DownStairs;
+                return Some(idx);
+            }
+        }
+        None
+    }
+
     fn build_pub(&mut self, 
         building: &Rect, 
         build_data : &mut BuilderMap, 
@@ -231,12 +577,23 @@ impl BSPTownBuilder {
         let player_idx = build_data.map.xy_idx(cent.0, cent.1);
     
         // Place other items
-        let mut to_place : Vec<&str> = vec!["Barkeep", "Shady Salesman", "Patron", "Patron",
-            "Table", "Chair", "Table", "Chair"];
+        self.furnish(building, &building_contents(BuildingTag::Pub), Some(player_idx), build_data, rng);
+    }
+
+    fn furnish(&mut self, 
+        building: &Rect, 
+        contents : &[&str], 
+        keep_clear : Option<usize>, 
+        build_data : &mut BuilderMap, 
+        rng: &mut rltk::RandomNumberGenerator) 
+    {
+        let mut to_place : Vec<&str> = contents.to_vec();
         for y in building.y1 .. building.y2 {
             for x in building.x1 .. building.x2 {
                 let idx = build_data.map.xy_idx(x, y);
-                if build_data.map.tiles[idx] == TileType::FloorIndoor && idx != player_idx && rng.roll_dice(1, 3)==1 && !to_place.is_empty() {
+                //internal doors are indoor floor too, so check nothing's there yet
+                let occupied = build_data.list_spawns.iter().any(|(i, _)| *i == idx);
+                if build_data.map.tiles[idx] == TileType::FloorIndoor && Some(idx) != keep_clear && !occupied && rng.roll_dice(1, 3)==1 && !to_place.is_empty() {
                     let entity_tag = to_place[0];
                     to_place.remove(0);
                     build_data.list_spawns.push((idx, entity_tag.to_string()));
@@ -319,4 +676,33 @@ impl BSPTownBuilder {
 
         can_build
     }
-}
\ No newline at end of file
+}
+
+//the middle of the wall two rooms share, if they're side by side with just a wall between them
+fn shared_wall_door(a : &Rect, b : &Rect, build_data : &BuilderMap) -> Option<usize> {
+    //room floors run from x1 up to but not including x2, so a one tile wall sits at a.x2 when b starts at a.x2 + 1
+    let (top, bottom) = (i32::max(a.y1, b.y1), i32::min(a.y2, b.y2));
+    let (left, right) = (i32::max(a.x1, b.x1), i32::min(a.x2, b.x2));
+    let (x, y) = if b.x1 == a.x2 + 1 && top < bottom { (a.x2, (top + bottom - 1) / 2) }
+        else if a.x1 == b.x2 + 1 && top < bottom { (b.x2, (top + bottom - 1) / 2) }
+        else if b.y1 == a.y2 + 1 && left < right { ((left + right - 1) / 2, a.y2) }
+        else if a.y1 == b.y2 + 1 && left < right { ((left + right - 1) / 2, b.y2) }
+        else { return None; };
+    let idx = build_data.map.xy_idx(x, y);
+    if build_data.map.tiles[idx] == TileType::Wall { Some(idx) } else { None }
+}
+
+//what goes in each kind of building; people first, so even cramped ones get someone to talk to
+fn building_contents(tag : BuildingTag) -> Vec<&'static str> {
+    match tag {
+        BuildingTag::Pub => vec!["Barkeep", "Shady Salesman", "Patron", "Patron", "Table", "Chair", "Table", "Chair"],
+        BuildingTag::Clinic => vec!["Doctor", "Bed", "Bed", "Medkit"],
+        BuildingTag::GunShop => vec!["Gun Dealer", "Counter", "Crate"],
+        BuildingTag::Grocery => vec!["Grocer", "Counter", "Crate", "Rations"],
+        BuildingTag::PoliceStation => vec!["Cop", "Cop", "Table", "Chair", "Crate"],
+        BuildingTag::Apartments => vec!["Resident", "Resident", "Bed", "Bed", "Table"],
+        BuildingTag::Hovel => vec!["Resident", "Bed"],
+        BuildingTag::Warehouse => vec!["Worker", "Crate", "Crate", "Crate", "Crate"],
+        BuildingTag::Unassigned => Vec::new()
+    }
+}
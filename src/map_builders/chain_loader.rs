@@ -0,0 +1,146 @@
+use super::*;
+use crate::raws::BuilderChainRecipe;
+
+//"RoomSorter(CENTRAL)" -> ("RoomSorter", ["CENTRAL"]); presets like "DLABuilder::insectoid" keep the whole name
+fn parse_spec(spec : &str) -> (String, Vec<String>) {
+    let spec = spec.trim();
+    match spec.find('(') {
+        None => (spec.to_string(), Vec::new()),
+        Some(open) => {
+            let name = spec[..open].trim().to_string();
+            let args = spec[open+1..].trim_end_matches(')')
+                .split(',')
+                .map(|a| a.trim().to_string())
+                .filter(|a| !a.is_empty())
+                .collect();
+            (name, args)
+        }
+    }
+}
+
+fn arg<'a>(args : &'a [String], n : usize) -> &'a str {
+    args.get(n).map_or("", |a| a.as_str())
+}
+
+//builders that can both start a chain and modify one
+fn dla_preset(preset : &str) -> Option<Box<DLABuilder>> {
+    match preset {
+        "DLABuilder" | "DLABuilder::new" => Some(DLABuilder::new()),
+        "DLABuilder::walk_inwards" => Some(DLABuilder::walk_inwards()),
+        "DLABuilder::walk_outwards" => Some(DLABuilder::walk_outwards()),
+        "DLABuilder::central_attractor" => Some(DLABuilder::central_attractor()),
+        "DLABuilder::insectoid" => Some(DLABuilder::insectoid()),
+        "DLABuilder::heavy_erosion" => Some(DLABuilder::heavy_erosion()),
+        _ => None
+    }
+}
+
+fn drunkard_preset(preset : &str) -> Option<Box<DrunkardsWalkBuilder>> {
+    match preset {
+        "DrunkardsWalkBuilder::open_area" => Some(DrunkardsWalkBuilder::open_area()),
+        "DrunkardsWalkBuilder::open_halls" => Some(DrunkardsWalkBuilder::open_halls()),
+        "DrunkardsWalkBuilder::winding_passages" => Some(DrunkardsWalkBuilder::winding_passages()),
+        "DrunkardsWalkBuilder::fat_passages" => Some(DrunkardsWalkBuilder::fat_passages()),
+        "DrunkardsWalkBuilder::fearful_symmetry" => Some(DrunkardsWalkBuilder::fearful_symmetry()),
+        _ => None
+    }
+}
+
+fn prefab(name : &str, args : &[String]) -> Option<Box<PrefabBuilder>> {
+    match (name, arg(args, 0)) {
+        ("PrefabBuilder::constant", level) => prefab_builders::prefab_levels::prefab_level(level).map(PrefabBuilder::constant),
+        ("PrefabBuilder::sectional", section) => prefab_builders::prefab_sections::prefab_section(section).map(PrefabBuilder::sectional),
+        ("PrefabBuilder::vaults", _) => Some(PrefabBuilder::vaults()),
+        _ => None
+    }
+}
+
+pub fn initial_builder(spec : &str) -> Option<Box<dyn InitialMapBuilder>> {
+    let (name, args) = parse_spec(spec);
+    let builder : Box<dyn InitialMapBuilder> = match name.as_str() {
+        "SimpleMapBuilder" => SimpleMapBuilder::new(),
+        "BSPDungeonBuilder" => BSPDungeonBuilder::new(),
+        "BSPInteriorBuilder" => BSPInteriorBuilder::new(),
+        "BSPTownBuilder" => BSPTownBuilder::new(),
+        "CellularAutomataBuilder" => CellularAutomataBuilder::new(),
+        "MazeBuilder" => MazeBuilder::new(),
+        "NoiseMapBuilder" => NoiseMapBuilder::new(),
+        "VoronoiBuilder" | "VoronoiBuilder::new" => VoronoiBuilder::new(),
+        "VoronoiBuilder::pythagoras" => VoronoiBuilder::pythagoras(),
+        "VoronoiBuilder::manhattan" => VoronoiBuilder::manhattan(),
+        _ => {
+            if let Some(b) = dla_preset(&name) { b }
+            else if let Some(b) = drunkard_preset(&name) { b }
+            else if let Some(b) = prefab(&name, &args) { b }
+            else { return None; }
+        }
+    };
+    Some(builder)
+}
+
+pub fn meta_builder(spec : &str) -> Option<Box<dyn MetaMapBuilder>> {
+    let (name, args) = parse_spec(spec);
+    let builder : Box<dyn MetaMapBuilder> = match name.as_str() {
+        "AreaStartingPosition" => {
+            let x = match arg(&args, 0) { "LEFT" => XStart::LEFT, "RIGHT" => XStart::RIGHT, _ => XStart::CENTER };
+            let y = match arg(&args, 1) { "TOP" => YStart::TOP, "BOTTOM" => YStart::BOTTOM, _ => YStart::CENTER };
+            AreaStartingPosition::new(x, y)
+        }
+        "RoomSorter" => {
+            let sort = match arg(&args, 0) {
+                "LEFTMOST" => RoomSort::LEFTMOST,
+                "RIGHTMOST" => RoomSort::RIGHTMOST,
+                "TOPMOST" => RoomSort::TOPMOST,
+                "BOTTOMMOST" => RoomSort::BOTTOMMOST,
+                _ => RoomSort::CENTRAL
+            };
+            RoomSorter::new(sort)
+        }
+        "BSPCorridors" => BSPCorridors::new(),
+        "BSPTownBuilder" => BSPTownBuilder::new(),
+        "CellularAutomataBuilder" => CellularAutomataBuilder::new(),
+        "CorridorSpawner" => CorridorSpawner::new(),
+        "CullUnreachable" => CullUnreachable::new(),
+        "CullUnreachable::tunnel" => CullUnreachable::tunnel(),
+        "DoglegCorridors" => DoglegCorridors::new(),
+        "DoorPlacement" => DoorPlacement::new(),
+        "NearestCorridors" => NearestCorridors::new(),
+        "RectBuilder" => RectBuilder::new(),
+        "RoomBasedSpawner" => RoomBasedSpawner::new(),
+        "RoomBasedStartingPosition" => RoomBasedStartingPosition::new(),
+        "RoomCornerRounder" => RoomCornerRounder::new(),
+        "RoomDrawer" => RoomDrawer::new(),
+        "RoomEroder" => RoomEroder::new(),
+        "StraightLineCorridors" => StraightLineCorridors::new(),
+        "VoronoiSpawning" => VoronoiSpawning::new(),
+        _ => {
+            if let Some(b) = dla_preset(&name) { b }
+            else if let Some(b) = drunkard_preset(&name) { b }
+            else if let Some(b) = prefab(&name, &args) { b }
+            else { return None; }
+        }
+    };
+    Some(builder)
+}
+
+/// Turns a recipe from the raws into a chain, or None if it names a builder we don't know
+pub fn chain_from_recipe(recipe : &BuilderChainRecipe, width : i32, height : i32) -> Option<BuilderChain> {
+    let mut builder = BuilderChain::new(width, height);
+    match initial_builder(&recipe.start) {
+        None => {
+            console::log(&format!("WARNING: unknown starting builder [{}] in chain [{}]", recipe.start, recipe.name));
+            return None;
+        }
+        Some(starter) => builder.start_with(starter)
+    }
+    for spec in recipe.meta.iter() {
+        match meta_builder(spec) {
+            None => {
+                console::log(&format!("WARNING: unknown meta builder [{}] in chain [{}]", spec, recipe.name));
+                return None;
+            }
+            Some(meta) => builder.with(meta)
+        }
+    }
+    Some(builder)
+}
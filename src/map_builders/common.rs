@@ -1,6 +1,7 @@
 use super::{Map, TileType, Rect};
 use std::cmp::{max, min};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use crate::map::tile_walkable;
 
 #[derive(PartialEq, Copy, Clone)]
 pub enum Symmetry { None, Horizontal, Vertical, Both }
@@ -57,6 +58,41 @@ pub fn draw_corridor(map: &mut Map, x1:i32, y1:i32, x2:i32, y2:i32) -> Vec<usize
     corridor
 }
 
+//connectivity
+//pathing distance from the start to every tile; MAX means we can't get there
+pub fn distances_from(map : &mut Map, start_idx : usize) -> Vec<f32> {
+    //the dijkstra map paths over blocked, which the builders never fill in
+    map.populate_blocked();
+    let map_starts : Vec<i32> = vec![start_idx as i32];
+    let dijkstra_map = rltk::DijkstraMap::new(map.width, map.height, &map_starts, &*map, 1000.0);
+    dijkstra_map.map
+}
+
+//every walkable tile connected to idx, the same way the dijkstra map moves (diagonals included)
+pub fn flood_region(map : &Map, idx : usize) -> Vec<usize> {
+    let mut seen = vec![false; map.tiles.len()];
+    let mut region = Vec::new();
+    let mut open = VecDeque::new();
+    open.push_back(idx);
+    seen[idx] = true;
+    while let Some(current) = open.pop_front() {
+        region.push(current);
+        let (x, y) = (current as i32 % map.width, current as i32 / map.width);
+        for dy in -1 ..= 1 {
+            for dx in -1 ..= 1 {
+                let (nx, ny) = (x + dx, y + dy);
+                if nx < 1 || nx > map.width-2 || ny < 1 || ny > map.height-2 { continue; }
+                let next = map.xy_idx(nx, ny);
+                if !seen[next] && tile_walkable(map.tiles[next]) {
+                    seen[next] = true;
+                    open.push_back(next);
+                }
+            }
+        }
+    }
+    region
+}
+
 //brushes
 pub fn paint(map: &mut Map, mode: Symmetry, brush_size: i32, x: i32, y:i32) {
     match mode {
@@ -1,7 +1,17 @@
-use super::{MetaMapBuilder, BuilderMap, TileType};
-use rltk::RandomNumberGenerator;
+use super::{MetaMapBuilder, BuilderMap, TileType, Map, distances_from, flood_region};
+use crate::map::tile_walkable;
+use rltk::{RandomNumberGenerator, console};
+use std::collections::VecDeque;
 
-pub struct CullUnreachable {}
+//pockets smaller than this aren't worth a tunnel, they just get filled in
+const MIN_TUNNEL_REGION : usize = 10;
+
+#[derive(PartialEq, Copy, Clone)]
+pub enum CullMode { Cull, Tunnel }
+
+pub struct CullUnreachable {
+    mode : CullMode
+}
 
 impl MetaMapBuilder for CullUnreachable {
     fn build_map(&mut self, rng: &mut rltk::RandomNumberGenerator, build_data : &mut BuilderMap)  {
@@ -12,27 +22,133 @@ impl MetaMapBuilder for CullUnreachable {
 impl CullUnreachable {
     #[allow(dead_code)]
     pub fn new() -> Box<CullUnreachable> {
-        Box::new(CullUnreachable{})
+        Box::new(CullUnreachable{ mode : CullMode::Cull })
+    }
+
+    /// Connects pockets to the rest of the map instead of filling them in
+    #[allow(dead_code)]
+    pub fn tunnel() -> Box<CullUnreachable> {
+        Box::new(CullUnreachable{ mode : CullMode::Tunnel })
     }
 
     fn build(&mut self, _rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
-        let starting_pos = build_data.starting_position.as_ref().unwrap().clone();
-        let start_idx = build_data.map.xy_idx(
-            starting_pos.x, 
-            starting_pos.y
-        );
-        //crucial for it to work, not implemented yet
-        //build_data.map.populate_blocked();
-        let map_starts : Vec<i32> = vec![start_idx as i32];
-        let dijkstra_map = rltk::DijkstraMap::new(build_data.map.width, build_data.map.height, &map_starts , &build_data.map, 1000.0);
+        let starting_pos = match &build_data.starting_position {
+            None => {
+                console::log("WARNING: CullUnreachable needs a starting position, skipping");
+                return;
+            }
+            Some(pos) => pos.clone()
+        };
+        let start_idx = build_data.map.xy_idx(starting_pos.x, starting_pos.y);
+
+        if self.mode == CullMode::Tunnel {
+            let distances = distances_from(&mut build_data.map, start_idx);
+            let mut reachable : Vec<bool> = distances.iter().map(|d| *d < std::f32::MAX).collect();
+            let mut checked = reachable.clone();
+            let no_dig = building_walls(build_data);
+
+            for idx in 0..build_data.map.tiles.len() {
+                if !tile_walkable(build_data.map.tiles[idx]) || checked[idx] { continue; }
+                let region = flood_region(&build_data.map, idx);
+                for tile in region.iter() { checked[*tile] = true; }
+                // too small to bother with, the cull below fills it in
+                if region.len() < MIN_TUNNEL_REGION { continue; }
+                if let Some(corridor) = tunnel_to_reachable(&mut build_data.map, &region, &reachable, &no_dig) {
+                    for tile in region.iter().chain(corridor.iter()) {
+                        reachable[*tile] = true;
+                        checked[*tile] = true;
+                    }
+                }
+            }
+            build_data.take_snapshot();
+        }
+
+        // Anything we still can't get to gets filled in
+        let distances = distances_from(&mut build_data.map, start_idx);
         for (i, tile) in build_data.map.tiles.iter_mut().enumerate() {
-            if *tile == TileType::Floor {
-                let distance_to_start = dijkstra_map.map[i];
-                // We can't get to this tile - so we'll make it a wall
-                if distance_to_start == std::f32::MAX {
-                    *tile = TileType::Wall;
+            if tile_walkable(*tile) && distances[i] == std::f32::MAX {
+                *tile = TileType::Wall;
+            }
+        }
+        build_data.map.populate_blocked();
+
+        // No spawning inside the walls we just made
+        build_data.list_spawns.retain(|spawn| distances[spawn.0] < std::f32::MAX);
+
+        // Make sure there's a way out: the furthest reachable tile if there isn't one already
+        let has_exit = build_data.exits().iter().any(|idx| distances[*idx] < std::f32::MAX);
+        if !has_exit {
+            let furthest = distances.iter().enumerate()
+                .filter(|(_, d)| **d < std::f32::MAX)
+                .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
+                .map(|(i, _)| i);
+            if let Some(exit_idx) = furthest {
+                build_data.map.tiles[exit_idx] = TileType::DownStairs;
+                build_data.list_spawns.retain(|spawn| spawn.0 != exit_idx);
+            }
+        }
+        build_data.take_snapshot();
+    }
+}
+
+//digs from the region to the nearest tile we can already reach, walking around anything we mustn't dig
+//through; returns the dug tiles
+fn tunnel_to_reachable(map : &mut Map, region : &[usize], reachable : &[bool], no_dig : &[bool]) -> Option<Vec<usize>> {
+    //a breadth-first search from the whole region, so the first reachable tile it meets is the nearest
+    let mut came_from : Vec<Option<usize>> = vec![None; map.tiles.len()];
+    let mut open = VecDeque::new();
+    for idx in region.iter() {
+        came_from[*idx] = Some(*idx);
+        open.push_back(*idx);
+    }
+    let mut target = None;
+    while let Some(idx) = open.pop_front() {
+        if reachable[idx] {
+            target = Some(idx);
+            break;
+        }
+        let (x, y) = map.idx_xy(idx);
+        for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)].iter() {
+            let (nx, ny) = (x + dx, y + dy);
+            //keep the map's edge solid
+            if nx < 1 || nx >= map.width - 1 || ny < 1 || ny >= map.height - 1 { continue; }
+            let next = map.xy_idx(nx, ny);
+            if came_from[next].is_some() { continue; }
+            if !tile_walkable(map.tiles[next]) && no_dig[next] { continue; }
+            came_from[next] = Some(idx);
+            open.push_back(next);
+        }
+    }
+
+    //walk back to the region, digging out whatever isn't floor already
+    let mut corridor = Vec::new();
+    let mut idx = target?;
+    while let Some(previous) = came_from[idx] {
+        if previous == idx { break; }
+        idx = previous;
+        if came_from[idx] == Some(idx) { break; }
+        //leave existing floor alone, so indoor floors and stairs survive
+        if !tile_walkable(map.tiles[idx]) {
+            map.tiles[idx] = TileType::Floor;
+        }
+        corridor.push(idx);
+    }
+    Some(corridor)
+}
+
+//building walls; a tunnel that went through one would make a hole nobody put a door in
+fn building_walls(build_data : &BuilderMap) -> Vec<bool> {
+    let map = &build_data.map;
+    let mut walls = vec![false; map.tiles.len()];
+    if let Some(buildings) = &build_data.buildings {
+        for building in buildings.iter() {
+            for y in building.rect.y1 .. building.rect.y2 {
+                for x in building.rect.x1 .. building.rect.x2 {
+                    let idx = map.xy_idx(x, y);
+                    walls[idx] = !tile_walkable(map.tiles[idx]);
                 }
             }
         }
     }
-}
\ No newline at end of file
+    walls
+}
@@ -26,6 +26,9 @@ impl DoorPlacement {
     }
 
     fn door_possible(&self, build_data : &mut BuilderMap, idx : usize) -> bool {
+        //something (like a locked door) is already there
+        if build_data.list_spawns.iter().any(|spawn| spawn.0 == idx) { return false; }
+
         let x = (idx % build_data.map.width as usize) as i32;
         let y = (idx / build_data.map.width as usize) as i32;
     
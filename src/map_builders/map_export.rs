@@ -0,0 +1,96 @@
+use super::{Map, TileType, prefab_builders::TEMPLATE_SPAWNS};
+use rltk::rex::{XpFile, XpColor};
+use rltk::RGB;
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::Write;
+//console is RLTK's wrapper around either println or the web console macro
+use rltk::{console};
+
+//one character per tile, entities drawn over the tile they stand on
+fn export_glyphs(map : &Map, spawns : &[(usize, String)]) -> Vec<(char, RGB)> {
+    let mut glyphs : Vec<(char, RGB)> = map.tiles.iter().map(|tile| tile_glyph(*tile)).collect();
+
+    let raws = crate::raws::RAWS.lock().unwrap();
+    for (idx, name) in spawns.iter() {
+        if *idx >= glyphs.len() { continue; }
+        //spawns the templates know about get their template glyph so they load back in
+        match TEMPLATE_SPAWNS.iter().find(|(_, spawn)| *spawn == name.as_str()) {
+            Some((glyph, _)) => glyphs[*idx] = (*glyph, RGB::named(rltk::YELLOW)),
+            None => {
+                //the rest are there to look at; the loader logs them as unknown glyphs
+                if let Some(glyph) = crate::raws::get_spawn_glyph(&raws, name) {
+                    glyphs[*idx] = glyph;
+                }
+            }
+        }
+    }
+    glyphs
+}
+
+fn tile_glyph(tile : TileType) -> (char, RGB) {
+    let properties = crate::map::tile_properties(tile);
+    (properties.template, properties.fg)
+}
+
+/// The map as prefab template rows, one per line, using the glyphs .xp levels are read with
+pub fn map_to_template(map : &Map, spawns : &[(usize, String)]) -> String {
+    let glyphs = export_glyphs(map, spawns);
+    let mut template = String::new();
+    for y in 0..map.height {
+        template.push('\n');
+        for x in 0..map.width {
+            template.push(glyphs[map.xy_idx(x, y)].0);
+        }
+    }
+    template.push('\n');
+    template
+}
+
+/// The map as a single layer REX Paint image, readable by PrefabBuilder::rex_level
+pub fn map_to_xp(map : &Map, spawns : &[(usize, String)]) -> XpFile {
+    let glyphs = export_glyphs(map, spawns);
+    let mut xp = XpFile::new(map.width as usize, map.height as usize);
+    for y in 0..map.height {
+        for x in 0..map.width {
+            let (glyph, fg) = glyphs[map.xy_idx(x, y)];
+            if let Some(cell) = xp.layers[0].get_mut(x as usize, y as usize) {
+                cell.ch = rltk::to_cp437(glyph) as u32;
+                cell.fg = XpColor::new((fg.r * 255.0) as u8, (fg.g * 255.0) as u8, (fg.b * 255.0) as u8);
+                cell.bg = XpColor::BLACK;
+            }
+        }
+    }
+    xp
+}
+
+//exports get a folder of their own rather than cluttering wherever the game was started from
+#[cfg(not(target_arch = "wasm32"))]
+const EXPORT_DIR : &str = "exports";
+
+/// Writes name.xp and name.txt to the exports folder, and says how it went
+#[cfg(not(target_arch = "wasm32"))]
+pub fn export_map(map : &Map, spawns : &[(usize, String)], name : &str) -> Result<String, String> {
+    let path = std::path::Path::new(EXPORT_DIR);
+    let xp_path = path.join(format!("{}.xp", name));
+    let txt_path = path.join(format!("{}.txt", name));
+    let result = std::fs::create_dir_all(path)
+        .and_then(|_| std::fs::File::create(&xp_path))
+        .and_then(|mut f| map_to_xp(map, spawns).write(&mut f))
+        .and_then(|_| std::fs::File::create(&txt_path))
+        .and_then(|mut f| f.write_all(map_to_template(map, spawns).as_bytes()));
+
+    let outcome = match result {
+        Ok(()) => Ok(format!("Exported map to {} and {} ({}x{})", xp_path.display(), txt_path.display(), map.width, map.height)),
+        Err(e) => Err(format!("Could not export map: {}", e))
+    };
+    match &outcome { Ok(msg) | Err(msg) => console::log(msg) }
+    outcome
+}
+
+//there's no file system on the web
+#[cfg(target_arch = "wasm32")]
+pub fn export_map(_map : &Map, _spawns : &[(usize, String)], _name : &str) -> Result<String, String> {
+    let msg = "Could not export map: the web version can't save files".to_string();
+    console::log(&msg);
+    Err(msg)
+}
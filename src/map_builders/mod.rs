@@ -23,6 +23,7 @@ mod perlin_noise;
 use perlin_noise::NoiseMapBuilder;
 mod bsp_town;
 use bsp_town::BSPTownBuilder;
+pub use bsp_town::{Building, BuildingTag, District};
 
 //postprocessing stuff
 mod rooms_corridors_dogleg;
@@ -53,6 +54,8 @@ mod voronoi_spawning;
 use voronoi_spawning::VoronoiSpawning;
 mod door_placement;
 use door_placement::DoorPlacement;
+mod cull_unreachable;
+use cull_unreachable::CullUnreachable;
 
 //custom postprocess
 mod rectangle_builder;
@@ -60,9 +63,37 @@ use rectangle_builder::RectBuilder;
 
 mod common;
 use common::*;
+mod chain_loader;
+mod validation;
+pub use validation::*;
+mod map_export;
+pub use map_export::*;
 use specs::prelude::*;
 //console is RLTK's wrapper around either println or the web console macro
 use rltk::{console};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+//whether builders record snapshots for the visualizer, can be flipped while the game runs
+static RECORD_SNAPSHOTS : AtomicBool = AtomicBool::new(SHOW_MAPGEN_VISUALIZER);
+
+pub fn mapgen_snapshots() -> bool {
+    RECORD_SNAPSHOTS.load(Ordering::Relaxed)
+}
+
+pub fn set_mapgen_snapshots(on : bool) {
+    RECORD_SNAPSHOTS.store(on, Ordering::Relaxed);
+}
+
+/// One frame of the visualizer: the map plus what the builder knew about it at the time
+#[derive(Clone)]
+pub struct MapSnapshot {
+    pub map : Map,
+    pub label : String,
+    pub rooms : Option<Vec<Rect>>,
+    pub corridors : Option<Vec<Vec<usize>>>,
+    pub submaps : Option<Vec<Rect>>,
+    pub spawns : Vec<(usize, String)>
+}
 
 pub struct BuilderMap {
     pub list_spawns : Vec<(usize, String)>,
@@ -71,19 +102,41 @@ pub struct BuilderMap {
     pub rooms: Option<Vec<Rect>>,
     pub corridors: Option<Vec<Vec<usize>>>,
     pub submaps: Option<Vec<Rect>>, //not reusing rooms because some submaps have rooms themselves...
-    pub history : Vec<Map>,
+    //towns only: what every building is for and which part of town it's in
+    pub buildings: Option<Vec<Building>>,
+    pub districts: Option<Vec<(Rect, District)>>,
+    pub history : Vec<MapSnapshot>,
+    pub current_builder : String,
+    pub depth : i32,
     pub width: i32,
     pub height: i32
 }
 
 impl BuilderMap {
+    /// The level's main ways down. Basement stairs go down too, but they're tucked away inside
+    /// buildings, so they don't count when making sure there's a way on
+    pub fn exits(&self) -> Vec<usize> {
+        let basements : Vec<usize> = self.buildings.iter().flatten().filter_map(|b| b.basement).collect();
+        self.map.tiles.iter().enumerate()
+            .filter(|(idx, tile)| **tile == TileType::DownStairs && !basements.contains(idx))
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
     fn take_snapshot(&mut self) {
-        if SHOW_MAPGEN_VISUALIZER {
+        if mapgen_snapshots() {
             let mut snapshot = self.map.clone();
             for v in snapshot.revealed_tiles.iter_mut() {
                 *v = true;
             }
-            self.history.push(snapshot);
+            self.history.push(MapSnapshot{
+                map : snapshot,
+                label : self.current_builder.clone(),
+                rooms : self.rooms.clone(),
+                corridors : self.corridors.clone(),
+                submaps : self.submaps.clone(),
+                spawns : self.list_spawns.clone()
+            });
         }
     }
 }
@@ -106,7 +159,11 @@ impl BuilderChain {
                 rooms: None,
                 corridors: None,
                 submaps: None,
+                buildings: None,
+                districts: None,
                 history : Vec::new(),
+                current_builder : String::new(),
+                depth : 1,
                 width,
                 height
             }
@@ -130,12 +187,14 @@ impl BuilderChain {
             None => panic!("Cannot run a map builder chain without a starting build system"),
             Some(starter) => {
                 // Build the starting map
+                self.build_data.current_builder = builder_label(starter.name());
                 starter.build_map(rng, &mut self.build_data);
             }
         }
 
         // Build additional layers in turn
         for metabuilder in self.builders.iter_mut() {
+            self.build_data.current_builder = builder_label(metabuilder.name());
             metabuilder.build_map(rng, &mut self.build_data);
         }
     }
@@ -150,69 +209,147 @@ impl BuilderChain {
 //Rust's interfaces - unfortunately, no variables allowed here!
 pub trait InitialMapBuilder {
     fn build_map(&mut self, rng: &mut rltk::RandomNumberGenerator, build_data : &mut BuilderMap);
+    //used to label visualizer snapshots
+    fn name(&self) -> &'static str { std::any::type_name::<Self>() }
 }
 
 pub trait MetaMapBuilder {    
     fn build_map(&mut self, rng: &mut rltk::RandomNumberGenerator, build_data : &mut BuilderMap);
+    fn name(&self) -> &'static str { std::any::type_name::<Self>() }
 }
 
+//type names come with the whole module path, we only want the struct
+fn builder_label(type_name : &str) -> String {
+    type_name.rsplit("::").next().unwrap_or(type_name).to_string()
+}
 
-//Factory function for builder
+
+//Factory functions for builder chains; the recipes live in data/builders.json
+#[allow(dead_code)]
 pub fn random_builder(rng: &mut rltk::RandomNumberGenerator, width: i32, height: i32) -> BuilderChain {
+    level_builder(Some("town"), 1, rng, width, height)
+}
+
+/// A chain by its name in the raws, e.g. to try out a new recipe
+#[allow(dead_code)]
+pub fn named_builder(name : &str, width: i32, height: i32) -> Option<BuilderChain> {
+    let recipe = crate::raws::get_builder_chain(&crate::raws::RAWS.lock().unwrap(), name);
+    recipe.and_then(|recipe| chain_loader::chain_from_recipe(&recipe, width, height))
+}
+
+/// A weighted pick among the chains that fit the level type and depth
+pub fn level_builder(level_type : Option<&str>, depth : i32, rng: &mut rltk::RandomNumberGenerator, width: i32, height: i32) -> BuilderChain {
+    let options = crate::raws::get_builder_chains(&crate::raws::RAWS.lock().unwrap(), level_type, depth);
+    let total_weight : i32 = options.iter().map(|o| o.1).sum();
+    if total_weight > 0 {
+        let mut roll = rng.roll_dice(1, total_weight);
+        for (recipe, weight) in options.iter() {
+            if roll <= *weight {
+                if let Some(mut builder) = chain_loader::chain_from_recipe(recipe, width, height) {
+                    builder.build_data.depth = depth;
+                    builder.build_data.map.depth = depth;
+                    return builder;
+                }
+                break;
+            }
+            roll -= weight;
+        }
+    }
+
+    rltk::console::log(&format!("WARNING: no usable builder chain for {:?} at depth {}, using the default", level_type, depth));
+    let mut builder = fallback_builder(depth, width, height);
+    builder.build_data.depth = depth;
+    builder.build_data.map.depth = depth;
+    builder
+}
+
+//how many chains to try before falling back to one that always works
+const MAX_MAPGEN_ATTEMPTS : i32 = 10;
+
+/// Builds chains for the level until one passes validation
+pub fn build_valid_map(level_type : Option<&str>, depth : i32, rng: &mut rltk::RandomNumberGenerator, width: i32, height: i32) -> BuilderChain {
+    let thresholds = MapThresholds::default();
+    let mut attempt = 1;
+    loop {
+        let mut builder = level_builder(level_type, depth, rng, width, height);
+        builder.build_map(rng);
+        match map_stats(&builder.build_data).validate(&thresholds) {
+            Ok(()) => return builder,
+            Err(reason) => {
+                rltk::console::log(&format!("Map rejected ({}), attempt {} of {}", reason, attempt, MAX_MAPGEN_ATTEMPTS));
+                if attempt >= MAX_MAPGEN_ATTEMPTS {
+                    rltk::console::log(&format!("WARNING: no chain for {:?} at depth {} made a valid map, using the default", level_type, depth));
+                    let mut builder = fallback_builder(depth, width, height);
+                    builder.build_data.depth = depth;
+                    builder.build_data.map.depth = depth;
+                    builder.build_map(rng);
+                    return builder;
+                }
+            }
+        }
+        attempt += 1;
+    }
+}
+
+/// Generates count maps with every chain in the raws and prints how they turned out
+pub fn batch_stats(count : i32, rng: &mut rltk::RandomNumberGenerator, width: i32, height: i32) {
+    let thresholds = MapThresholds::default();
+    //hundreds of maps worth of snapshots add up fast
+    let recording = mapgen_snapshots();
+    set_mapgen_snapshots(false);
+    let names = crate::raws::get_builder_chain_names(&crate::raws::RAWS.lock().unwrap());
+    for name in names.iter() {
+        let (mut floor, mut regions, mut spawns, mut busiest, mut doors) = (0.0, 0, 0, 0, 0);
+        let (mut stairs, mut with_stairs, mut failed, mut built) = (0.0, 0, 0, 0);
+        for _i in 0..count {
+            let mut builder = match named_builder(name, width, height) {
+                None => break,
+                Some(builder) => builder
+            };
+            builder.build_map(rng);
+            let stats = map_stats(&builder.build_data);
+            floor += stats.floor_percent;
+            regions += stats.regions;
+            spawns += stats.spawns;
+            busiest += stats.spawns_per_region.iter().max().unwrap_or(&0);
+            doors += stats.doors;
+            if let Some(distance) = stats.stairs_distance {
+                stairs += distance;
+                with_stairs += 1;
+            }
+            if stats.validate(&thresholds).is_err() { failed += 1; }
+            built += 1;
+        }
+        if built == 0 {
+            rltk::console::log(&format!("{}: could not be built", name));
+            continue;
+        }
+        let n = built as f32;
+        rltk::console::log(&format!("{}: {} maps, {:.1}% floor, {:.1} regions, {:.1} steps to stairs ({} without), {:.1} spawns ({:.1} in the busiest region), {:.1} doors, {} failed validation",
+            name, built, floor / n, regions as f32 / n, stairs / f32::max(1.0, with_stairs as f32), built - with_stairs,
+            spawns as f32 / n, busiest as f32 / n, doors as f32 / n, failed));
+    }
+    set_mapgen_snapshots(recording);
+}
+
+//a chain that always comes out in one piece with a way down, for when the raws let us down
+fn fallback_builder(depth : i32, width: i32, height: i32) -> BuilderChain {
+    if depth == 1 { return default_builder(width, height); }
     let mut builder = BuilderChain::new(width, height);
-    //simple map
-    // builder.start_with(SimpleMapBuilder::new());
-    // builder.with(RoomDrawer::new());
-    // //builder.with(DoglegCorridors::new());
-    // //builder.start_with(BSPDungeonBuilder::new());
-    // builder.with(RoomSorter::new(RoomSort::CENTRAL));
-    // builder.with(NearestCorridors::new());
-    // //builder.with(BSPCorridors::new());
-    // builder.with(RoomBasedSpawner::new());
-    // builder.with(CorridorSpawner::new());
-    // builder.with(RoomBasedStartingPosition::new());
-    // builder
-
-    // cellular
-    //builder.start_with(CellularAutomataBuilder::new());
-    //builder.start_with(DrunkardsWalkBuilder::fearful_symmetry());
-    //builder.start_with(DLABuilder::insectoid());
-    //builder.start_with(MazeBuilder::new());
-    // builder.start_with(VoronoiBuilder::pythagoras());
-    // builder.with(AreaStartingPosition::new(XStart::CENTER, YStart::CENTER));
-    // //builder.with(CullUnreachable::new()); culling not implemented yet
-    // builder.with(VoronoiSpawning::new());
-    // builder
-
-    //another example
-    // let mut builder = BuilderChain::new(width, height);
-    // builder.start_with(SimpleMapBuilder::new());
-    // //builder.with(DrunkardsWalkBuilder::winding_passages());
-    // //builder.with(DLABuilder::heavy_erosion());
-    // //builder.with(RoomEroder::new());
-    // builder.with(RoomCornerRounder::new());
-    // builder.with(AreaStartingPosition::new(XStart::CENTER, YStart::CENTER));
-    // //builder.with(CullUnreachable::new()); culling not implemented yet
-    // builder.with(VoronoiSpawning::new());
-    // builder
-
-
-    //show off
+    builder.start_with(CellularAutomataBuilder::new());
+    builder.with(AreaStartingPosition::new(XStart::CENTER, YStart::CENTER));
+    builder.with(CullUnreachable::new());
+    builder.with(VoronoiSpawning::new());
+    builder
+}
+
+fn default_builder(width: i32, height: i32) -> BuilderChain {
     let mut builder = BuilderChain::new(width, height);
     builder.start_with(NoiseMapBuilder::new());
-    //builder.with(AreaStartingPosition::new(XStart::CENTER, YStart::CENTER));
-    //builder.with(CullUnreachable::new()); culling not implemented yet
     builder.with(VoronoiSpawning::new());
     builder.with(RectBuilder::new());
     builder.with(BSPTownBuilder::new());
     builder.with(DoorPlacement::new());
-    //builder.with(PrefabBuilder::sectional(prefab_builders::prefab_sections::UNDERGROUND_FORT));
+    builder.with(CullUnreachable::tunnel());
     builder
-
-    // let mut builder = BuilderChain::new(width, height);
-    // builder.start_with(BSPTownBuilder::new());
-    // builder.with(AreaStartingPosition::new(XStart::CENTER, YStart::CENTER));
-    // builder.with(VoronoiSpawning::new());
-    // builder
-
 }
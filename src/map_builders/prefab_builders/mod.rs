@@ -1,8 +1,9 @@
-use super::{InitialMapBuilder, MetaMapBuilder, BuilderMap, TileType, Position};
+use super::{InitialMapBuilder, MetaMapBuilder, BuilderMap, TileType, Position, Rect};
 use rltk::RandomNumberGenerator;
 //these need to be accessible on the outside
 pub mod prefab_levels;
 pub mod prefab_sections;
+pub mod prefab_rooms;
 //console is RLTK's wrapper around either println or the web console macro
 use rltk::{console};
 
@@ -12,13 +13,67 @@ use rltk::{console};
 pub enum PrefabMode { 
     RexLevel{ template : &'static str },
     Constant{ level : prefab_levels::PrefabLevel },
-    Sectional{ section : prefab_sections::PrefabSection }
+    Sectional{ section : prefab_sections::PrefabSection },
+    RoomVaults
 }
 
 pub struct PrefabBuilder {
     mode: PrefabMode
 }
 
+/// What one glyph in a template stands for
+#[derive(PartialEq, Clone)]
+pub struct PrefabGlyph {
+    pub glyph : char,
+    pub tile : TileType,
+    pub spawn : Option<String>
+}
+
+//the raws already checked the legend when loading, so nothing here should fail
+pub fn legend_from_raws(raw : &crate::raws::PrefabRaw) -> Vec<PrefabGlyph> {
+    raw.legend.iter().flatten()
+        .filter_map(|entry| {
+            let glyph = entry.glyph.chars().next()?;
+            let tile = crate::raws::parse_tile_type(&entry.tile)?;
+            Some(PrefabGlyph{ glyph, tile, spawn : entry.spawn.clone() })
+        })
+        .collect()
+}
+
+//.xp levels have no legend file, so they use the same glyphs the map exporter writes
+fn default_legend() -> Vec<PrefabGlyph> {
+    let raws = crate::raws::RAWS.lock().unwrap();
+    let mut legend : Vec<PrefabGlyph> = crate::map::template_glyphs().into_iter()
+        .map(|(glyph, tile)| PrefabGlyph{ glyph, tile, spawn : None })
+        .collect();
+    for (glyph, spawn) in TEMPLATE_SPAWNS.iter() {
+        if crate::raws::is_spawnable(&raws, spawn) {
+            legend.push(PrefabGlyph{ glyph : *glyph, tile : TileType::Floor, spawn : Some(spawn.to_string()) });
+        }
+    }
+    legend
+}
+
+//tiles take their template glyph from data/tiles.json; these are the things stood on the floor
+pub const TEMPLATE_SPAWNS : &[(char, &str)] = &[
+    ('+', "Door"),
+    ('!', "Medkit"),
+    ('%', "Rations"),
+    ('^', "Bear Trap"),
+    ('t', "Thug"),
+    ('c', "Cop"),
+    //the old tutorial goblin; there's no Human in the raws, so older templates get a thug. After 't' so exports use that
+    ('g', "Thug")
+];
+
+//.xp cells store codepage 437, so anything outside plain ASCII has to be looked up
+fn template_char_from_cp437(legend : &[PrefabGlyph], ch : u32) -> char {
+    for entry in legend.iter() {
+        if rltk::to_cp437(entry.glyph) as u32 == ch { return entry.glyph; }
+    }
+    ch as u8 as char
+}
+
 //it implements two interfaces at once, as it can be both the initial and the meta builder
 impl MetaMapBuilder for PrefabBuilder {
     fn build_map(&mut self, rng: &mut rltk::RandomNumberGenerator, build_data : &mut BuilderMap)  {
@@ -48,6 +103,13 @@ impl PrefabBuilder {
         })
     }
 
+    #[allow(dead_code)]
+    pub fn vaults() -> Box<PrefabBuilder> {
+        Box::new(PrefabBuilder{
+            mode : PrefabMode::RoomVaults,
+        })
+    }
+
     #[allow(dead_code)]
     pub fn rex_level(template : &'static str) -> Box<PrefabBuilder> {
         Box::new(PrefabBuilder{
@@ -57,30 +119,31 @@ impl PrefabBuilder {
 
 
     fn build(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
-        match self.mode {
+        match self.mode.clone() {
             //makes template available in match scope
             PrefabMode::RexLevel{template} => self.load_rex_map(&template, build_data),
             PrefabMode::Constant{level} => self.load_ascii_map(&level, build_data),
-            PrefabMode::Sectional{section} => self.apply_sectional(&section, rng, build_data)
+            PrefabMode::Sectional{section} => self.apply_sectional(&section, rng, build_data),
+            PrefabMode::RoomVaults => self.apply_room_vaults(rng, build_data)
         }
         build_data.take_snapshot();
 
         //spawning handled by post-process
     }
 
-    fn char_to_map(&mut self, ch : char, idx: usize, build_data : &mut BuilderMap) {
-        match ch {
-            ' ' => build_data.map.tiles[idx] = TileType::Floor, //space
-            '#' => build_data.map.tiles[idx] = TileType::Wall, // #
-            'g' => {
-                build_data.map.tiles[idx] = TileType::Floor;
-                build_data.list_spawns.push((idx, "Human".to_string()));
+    fn char_to_map(&mut self, legend : &[PrefabGlyph], ch : char, idx: usize, build_data : &mut BuilderMap) {
+        match legend.iter().find(|g| g.glyph == ch) {
+            Some(g) => {
+                build_data.map.tiles[idx] = g.tile;
+                if let Some(name) = &g.spawn {
+                    build_data.list_spawns.push((idx, name.to_string()));
+                }
             }
-            _ => {
+            None => {
                 //put a floor
                 build_data.map.tiles[idx] = TileType::Floor;
                 //log an error
-                console::log(&format!("Unknown glyph loading map: {}", (ch as u8) as char));
+                console::log(&format!("Unknown glyph loading map: {}", ch));
             }
         }
     }
@@ -96,17 +159,16 @@ impl PrefabBuilder {
     #[allow(dead_code)]
     fn load_ascii_map(&mut self, level: &prefab_levels::PrefabLevel, build_data : &mut BuilderMap) {
 
-        let string_vec = PrefabBuilder::read_ascii_to_vec(level.template);
+        let string_vec = PrefabBuilder::read_ascii_to_vec(&level.template);
 
         let mut i = 0;
         for ty in 0..level.height {
-            //fix off by one
-            for tx in 0..level.width-1 {
+            for tx in 0..level.width {
                 if tx < build_data.map.width as usize && ty < build_data.map.height as usize {
                     let idx = build_data.map.xy_idx(tx as i32, ty as i32);
                     //paranoia
                     if i < string_vec.len() {
-                        self.char_to_map(string_vec[i], idx, build_data);
+                        self.char_to_map(&level.legend, string_vec[i], idx, build_data);
                     }
                 }
                 i += 1;
@@ -137,7 +199,7 @@ impl PrefabBuilder {
     pub fn apply_sectional(&mut self, section : &prefab_sections::PrefabSection, rng: &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
         use prefab_sections::*;
 
-        let string_vec = PrefabBuilder::read_ascii_to_vec(section.template);
+        let string_vec = PrefabBuilder::read_ascii_to_vec(&section.template);
         
         // Place the new section
         let chunk_x;
@@ -166,7 +228,7 @@ impl PrefabBuilder {
             for tx in 0..section.width {
                 if tx < build_data.map.width as usize && ty < build_data.map.height as usize {
                     let idx = build_data.map.xy_idx(tx as i32 + chunk_x, ty as i32 + chunk_y);
-                    self.char_to_map(string_vec[i], idx, build_data);
+                    self.char_to_map(&section.legend, string_vec[i], idx, build_data);
                 }
                 i += 1;
             }
@@ -174,10 +236,99 @@ impl PrefabBuilder {
         build_data.take_snapshot();
     }
 
+    fn apply_room_vaults(&mut self, rng : &mut RandomNumberGenerator, build_data : &mut BuilderMap) {
+        // Which vaults can we use on this level?
+        let mut possible = prefab_rooms::vaults_for_depth(build_data.depth);
+        if possible.is_empty() { return; }
+
+        let mut used : Vec<Rect> = Vec::new();
+        let n_vaults = i32::min(rng.roll_dice(1, 3), possible.len() as i32);
+        for _i in 0..n_vaults {
+            //weighted pick, and each vault at most once per level
+            let total : i32 = possible.iter().map(|v| v.frequency).sum();
+            if total < 1 { break; }
+            let mut roll = rng.roll_dice(1, total);
+            let mut pick = 0;
+            for (i, vault) in possible.iter().enumerate() {
+                if roll <= vault.frequency { pick = i; break; }
+                roll -= vault.frequency;
+            }
+            let vault = possible.remove(pick);
+
+            let string_vec = PrefabBuilder::read_ascii_to_vec(&vault.template);
+            let spots = PrefabBuilder::vault_spots(&vault, &used, build_data);
+            if spots.is_empty() { continue; }
+            let (chunk_x, chunk_y) = spots[(rng.roll_dice(1, spots.len() as i32) - 1) as usize];
+            let footprint = Rect::new(chunk_x, chunk_y, vault.width as i32 - 1, vault.height as i32 - 1);
+
+            // Whatever was going to spawn here makes way for the vault
+            let width = build_data.map.width;
+            build_data.list_spawns.retain(|(idx, _)| {
+                let x = *idx as i32 % width;
+                let y = *idx as i32 / width;
+                x < footprint.x1 || x > footprint.x2 || y < footprint.y1 || y > footprint.y2
+            });
+
+            let mut i = 0;
+            for ty in 0..vault.height {
+                for tx in 0..vault.width {
+                    let idx = build_data.map.xy_idx(tx as i32 + chunk_x, ty as i32 + chunk_y);
+                    let was = build_data.map.tiles[idx];
+                    self.char_to_map(&vault.legend, string_vec[i], idx, build_data);
+                    //a vault indoors keeps the indoor floor
+                    if was == TileType::FloorIndoor && build_data.map.tiles[idx] == TileType::Floor {
+                        build_data.map.tiles[idx] = TileType::FloorIndoor;
+                    }
+                    i += 1;
+                }
+            }
+            used.push(footprint);
+            build_data.take_snapshot();
+        }
+    }
+
+    //top left corners where the whole vault sits on one kind of open floor, indoors or out, clear of the start,
+    //the stairs and other vaults
+    fn vault_spots(vault : &prefab_rooms::PrefabRoom, used : &[Rect], build_data : &BuilderMap) -> Vec<(i32, i32)> {
+        let map = &build_data.map;
+        let (w, h) = (vault.width as i32, vault.height as i32);
+        // Inside the rooms if the builder made any, anywhere otherwise
+        let areas : Vec<Rect> = match &build_data.rooms {
+            Some(rooms) if !rooms.is_empty() => rooms.clone(),
+            _ => vec![Rect::new(1, 1, map.width - 3, map.height - 3)]
+        };
+        let start_idx = build_data.starting_position.as_ref().map(|p| map.xy_idx(p.x, p.y));
+
+        let mut spots = Vec::new();
+        for area in areas.iter() {
+            for y in area.y1 ..= area.y2 - h + 1 {
+                for x in area.x1 ..= area.x2 - w + 1 {
+                    let footprint = Rect::new(x, y, w - 1, h - 1);
+                    if used.iter().any(|r| r.intersect(&footprint)) { continue; }
+                    if x < 1 || y < 1 { continue; }
+                    //half in a building and half out would knock a hole in its wall
+                    let floor = map.tiles[map.xy_idx(x, y)];
+                    if floor != TileType::Floor && floor != TileType::FloorIndoor { continue; }
+                    let mut fits = true;
+                    for ty in y .. y + h {
+                        for tx in x .. x + w {
+                            if tx < 1 || ty < 1 || tx > map.width - 2 || ty > map.height - 2 { fits = false; continue; }
+                            let idx = map.xy_idx(tx, ty);
+                            //stairs aren't floor, so this keeps them clear too
+                            if map.tiles[idx] != floor || Some(idx) == start_idx { fits = false; }
+                        }
+                    }
+                    if fits { spots.push((x, y)); }
+                }
+            }
+        }
+        spots
+    }
 
     #[allow(dead_code)]
     fn load_rex_map(&mut self, path: &str, build_data : &mut BuilderMap) {
         let xp_file = rltk::rex::XpFile::from_resource(path).unwrap();
+        let legend = default_legend();
 
         for layer in &xp_file.layers {
             for y in 0..layer.height {
@@ -186,7 +337,7 @@ impl PrefabBuilder {
                     if x < build_data.map.width as usize && y < build_data.map.height as usize {
                         let idx = build_data.map.xy_idx(x as i32, y as i32);
                         // We're doing some nasty casting to make it easier to type things like '#' in the match
-                        self.char_to_map(cell.ch as u8 as char, idx, build_data);
+                        self.char_to_map(&legend, template_char_from_cp437(&legend, cell.ch), idx, build_data);
                     }
                 }
             }
@@ -1,65 +1,20 @@
-#[derive(PartialEq, Copy, Clone)]
+use super::{PrefabGlyph, legend_from_raws};
+
+//the templates themselves live in data/prefabs/levels.json
+#[derive(PartialEq, Clone)]
 pub struct PrefabLevel {
-    pub template : &'static str,
+    pub template : String,
     pub width : usize,
-    pub height: usize
+    pub height: usize,
+    pub legend : Vec<PrefabGlyph>
 }
 
-#[allow(dead_code)]
-pub const WFC_POPULATED : PrefabLevel = PrefabLevel{
-    template : LEVEL_MAP,
-    width: 80,
-    height: 43 //13 for the smaller map
-};
-
-#[allow(dead_code)]
-const LEVEL_MAP_TEST_SMALL : &str =
-"
-###############################################################################
-###############################################################################
-#### ######### #    # #######       #########  ####    #####                ###
-#### ######### ###### #######       #########  #### ## #####                ###
-#                        ####       #########   ### ##                      ###
-#### ######### ###       ####       #######         ## #####                ###
-#### ######### ###       ####       ####### #   ### ## #####                ###
-##          ## ###       ###### ###########     ###                         ###
-##                       ###### ########### #   ###       ##                ###
-##          ## ###              ######   ## #######       ##                ###
-###############################################################################
-###############################################################################
-###############################################################################
-";
-
-#[allow(dead_code)]
-const LEVEL_MAP : &str = 
-"
-###############################################################################
-#### ######### #    # #######       #########  ####    #####                ###
-#### ######### ###### #######   o   #########  #### ## #####                ###
-#                        ####       #########   ### ##         o            ###
-#### ######### ###       ####       #######         ## #####                ###
-#### ######### ###       ####       ####### #   ### ## #####                ###
-#### ######### ###       ####       ####### #######    #####     o          ###
-##          ## ###       ####       ####### ################                ###
-##          ## ###   o   ###### ########### #   ############                ###
-##          ## ###       ###### ###########     ###                         ###
-##    %                  ###### ########### #   ###   !   ##                ###
-##          ## ###              ######   ## #######       ##                ###
-##          ## ###       ## ### #####     # ########################      #####
-##          ## ###       ## ### #####     # #   ######################    #####
-### ## ####### ###### ##### ### ####          o ###########     ######    #####
-### ## ####### ###### ####   ## ####        #   #########         ###### ######
-######                  ####### ####            ######     !    !    ### #    #
-#####                     ##### ####        #   ######               ### ######
-####                            #####     # ##########               ### ######
-####           !           ### ######     # ##########      o##o     ### #   ##
-####                       ### #######   ## #   ######               ###   g ##
-####   ######### ########## %  ######## ###################     ######## ##   #
-### ### ######## ##########    ######## #################### ##########   #   #
-## ##### ######   #########    ########          ########### #######   # g#   #
-## #####           ###############      ###      ########### #######   ####   #
-## ##### ####       ############## ######## g  g ########### ####         # ^ #
-### ###^####         ############# ########      #####       ####      # g#   #
-####   ######       ###            ########      ##### g     ####   !  ####^^ #
-###############################################################################
-";
\ No newline at end of file
+pub fn prefab_level(name : &str) -> Option<PrefabLevel> {
+    let raws = crate::raws::RAWS.lock().unwrap();
+    crate::raws::get_prefab(&raws.prefab_levels, name).map(|raw| PrefabLevel{
+        template : raw.template.concat(),
+        width : raw.width,
+        height : raw.height,
+        legend : legend_from_raws(raw)
+    })
+}
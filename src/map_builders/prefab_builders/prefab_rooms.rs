@@ -0,0 +1,31 @@
+use super::{PrefabGlyph, legend_from_raws};
+
+//the templates themselves live in data/prefabs/vaults.json
+#[derive(PartialEq, Clone)]
+pub struct PrefabRoom {
+    pub template : String,
+    pub width : usize,
+    pub height: usize,
+    pub first_depth: i32,
+    pub last_depth: i32,
+    //relative weight when picking which vaults go in a level
+    pub frequency: i32,
+    pub legend : Vec<PrefabGlyph>
+}
+
+/// Every vault the room vault builder may pick from at this depth
+pub fn vaults_for_depth(depth : i32) -> Vec<PrefabRoom> {
+    let raws = crate::raws::RAWS.lock().unwrap();
+    raws.prefab_vaults.iter()
+        .map(|raw| PrefabRoom{
+            template : raw.template.concat(),
+            width : raw.width,
+            height : raw.height,
+            first_depth : raw.first_depth.unwrap_or(1),
+            last_depth : raw.last_depth.unwrap_or(std::i32::MAX),
+            frequency : raw.frequency.unwrap_or(1),
+            legend : legend_from_raws(raw)
+        })
+        .filter(|v| depth >= v.first_depth && depth <= v.last_depth)
+        .collect()
+}
@@ -1,3 +1,5 @@
+use super::{PrefabGlyph, legend_from_raws};
+
 #[allow(dead_code)]
 #[derive(PartialEq, Copy, Clone)]
 pub enum HorizontalPlacement { Left, Center, Right }
@@ -6,66 +8,36 @@ pub enum HorizontalPlacement { Left, Center, Right }
 #[derive(PartialEq, Copy, Clone)]
 pub enum VerticalPlacement { Top, Center, Bottom }
 
-#[allow(dead_code)]
-#[derive(PartialEq, Copy, Clone)]
+//the templates themselves live in data/prefabs/sections.json
+#[derive(PartialEq, Clone)]
 pub struct PrefabSection {
-    pub template : &'static str,
+    pub template : String,
     pub width : usize,
     pub height: usize,
-    pub placement : (HorizontalPlacement, VerticalPlacement)
+    pub placement : (HorizontalPlacement, VerticalPlacement),
+    pub legend : Vec<PrefabGlyph>
 }
 
-#[allow(dead_code)]
-pub const UNDERGROUND_FORT : PrefabSection = PrefabSection{
-    template : RIGHT_FORT,
-    width: 15,
-    height: 43,
-    placement: ( HorizontalPlacement::Right, VerticalPlacement::Top )
-};
-
-#[allow(dead_code)]
-const RIGHT_FORT : &str = "
-     #         
-  #######      
-  #     #      
-  #     #######
-  #  g        #
-  #     #######
-  #     #      
-  ### ###      
-    # #        
-    # #        
-    # ##       
-    ^          
-    ^          
-    # ##       
-    # #        
-    # #        
-    # #        
-    # #        
-  ### ###      
-  #     #      
-  #     #      
-  #  g  #      
-  #     #      
-  #     #      
-  ### ###      
-    # #        
-    # #        
-    # #        
-    # ##       
-    ^          
-    ^          
-    # ##       
-    # #        
-    # #        
-    # #        
-  ### ###      
-  #     #      
-  #     #######
-  #  g        #
-  #     #######
-  #     #      
-  #######      
-     #         
-";
\ No newline at end of file
+pub fn prefab_section(name : &str) -> Option<PrefabSection> {
+    let raws = crate::raws::RAWS.lock().unwrap();
+    crate::raws::get_prefab(&raws.prefab_sections, name).map(|raw| {
+        let placement = raw.placement.clone().unwrap_or_default();
+        let horizontal = match placement.get(0).map(|p| p.as_str()) {
+            Some("Left") => HorizontalPlacement::Left,
+            Some("Right") => HorizontalPlacement::Right,
+            _ => HorizontalPlacement::Center
+        };
+        let vertical = match placement.get(1).map(|p| p.as_str()) {
+            Some("Top") => VerticalPlacement::Top,
+            Some("Bottom") => VerticalPlacement::Bottom,
+            _ => VerticalPlacement::Center
+        };
+        PrefabSection{
+            template : raw.template.concat(),
+            width : raw.width,
+            height : raw.height,
+            placement : (horizontal, vertical),
+            legend : legend_from_raws(raw)
+        }
+    })
+}
@@ -0,0 +1,111 @@
+use super::{BuilderMap, TileType, distances_from, flood_region};
+use crate::map::tile_walkable;
+use crate::raws::{RAWS, spawn_blocks_path};
+
+/// What a finished map looks like, for deciding whether it's playable
+pub struct MapStats {
+    pub floor_percent : f32,
+    pub regions : usize,
+    pub stairs_distance : Option<f32>,
+    pub spawns_per_region : Vec<usize>,
+    pub doors : usize,
+    pub spawns : usize
+}
+
+/// The minimum a map has to meet before we let anyone play it
+pub struct MapThresholds {
+    pub min_floor_percent : f32,
+    pub max_regions : usize,
+    pub min_stairs_distance : f32,
+    pub min_spawns : usize
+}
+
+impl Default for MapThresholds {
+    fn default() -> MapThresholds {
+        MapThresholds{
+            min_floor_percent : 20.0,
+            max_regions : 1,
+            min_stairs_distance : 20.0,
+            min_spawns : 5
+        }
+    }
+}
+
+impl MapStats {
+    pub fn validate(&self, thresholds : &MapThresholds) -> Result<(), String> {
+        if self.floor_percent < thresholds.min_floor_percent {
+            return Err(format!("only {:.1}% floor", self.floor_percent));
+        }
+        if self.regions > thresholds.max_regions {
+            return Err(format!("{} disconnected regions", self.regions));
+        }
+        match self.stairs_distance {
+            None => return Err("no reachable stairs".to_string()),
+            Some(d) if d < thresholds.min_stairs_distance => return Err(format!("stairs only {:.0} steps from the start", d)),
+            _ => {}
+        }
+        if self.spawns < thresholds.min_spawns {
+            return Err(format!("only {} spawns", self.spawns));
+        }
+        Ok(())
+    }
+}
+
+pub fn map_stats(build_data : &BuilderMap) -> MapStats {
+    let walkable = build_data.map.tiles.iter().filter(|t| tile_walkable(**t)).count();
+    let floor_percent = (walkable as f32 / build_data.map.tiles.len() as f32) * 100.0;
+
+    //props that block a tile for good (a locked door, a counter across a doorway) can seal a building
+    //off as surely as a wall, so they count as one when finding regions and paths
+    let mut map = build_data.map.clone();
+    {
+        let raws = RAWS.lock().unwrap();
+        for spawn in build_data.list_spawns.iter() {
+            if spawn_blocks_path(&raws, &spawn.1) {
+                map.tiles[spawn.0] = TileType::Wall;
+            }
+        }
+    }
+    let map = &map;
+
+    // Label the connected regions
+    let mut region_of : Vec<Option<usize>> = vec![None; map.tiles.len()];
+    let mut regions = 0;
+    for idx in 0..map.tiles.len() {
+        if tile_walkable(map.tiles[idx]) && region_of[idx].is_none() {
+            for tile in flood_region(map, idx).iter() {
+                region_of[*tile] = Some(regions);
+            }
+            regions += 1;
+        }
+    }
+
+    let mut spawns_per_region = vec![0; regions];
+    for spawn in build_data.list_spawns.iter() {
+        if let Some(region) = region_of[spawn.0] {
+            spawns_per_region[region] += 1;
+        }
+    }
+    let doors = build_data.list_spawns.iter().filter(|s| s.1 == "Door").count();
+
+    // How far the nearest stairs are from the start, on foot
+    let mut stairs_distance = None;
+    if let Some(start) = &build_data.starting_position {
+        //distances_from needs blocked filled in, so work on a copy
+        let mut scratch = map.clone();
+        let distances = distances_from(&mut scratch, map.xy_idx(start.x, start.y));
+        stairs_distance = build_data.exits().iter()
+            .filter(|i| distances[**i] < std::f32::MAX)
+            .map(|i| distances[*i])
+            .fold(None, |best : Option<f32>, d| Some(best.map_or(d, |b| f32::min(b, d))));
+    }
+
+    MapStats{
+        floor_percent,
+        regions,
+        stairs_distance,
+        spawns_per_region,
+        doors,
+        spawns : build_data.list_spawns.len()
+    }
+}
@@ -1,7 +1,8 @@
 extern crate specs;
 use specs::prelude::*;
-use super::{Attributes, Pools, WantsToMelee, Name, SufferDamage, Entity, gamelog::GameLog,
-MeleeWeapon, EquipmentSlot, DefenseBonus, Equipped, particle_system::ParticleBuilder, Position};
+use super::{Attributes, Pools, WantsToMelee, Name, Entity, gamelog::{GameLog, LogCategory, LogEntry},
+MeleeWeapon, EquipmentSlot, DefenseBonus, Equipped, particle_system::ParticleBuilder, Position,
+effects::{add_effect, EffectType, Targets}, unarmed, MELEE_HIT_TARGET};
 //console is RLTK's wrapper around either println or the web console macro
 use rltk::{console};
 
@@ -14,20 +15,18 @@ impl<'a> System<'a> for MeleeCombatSystem {
                         ReadStorage<'a, Name>,
                         ReadStorage<'a, Attributes>,
                         ReadStorage<'a, Pools>,
-                        WriteStorage<'a, SufferDamage>,
                         //bonuses from equipped stuff
                         ReadStorage<'a, MeleeWeapon>,
                         ReadStorage<'a, DefenseBonus>,
                         ReadStorage<'a, Equipped>,
                         WriteExpect<'a, ParticleBuilder>,
                         ReadStorage<'a, Position>,
-                        WriteExpect<'a, rltk::RandomNumberGenerator>,
-                        ReadExpect<'a, Entity>
+                        WriteExpect<'a, rltk::RandomNumberGenerator>
                       );
 
     fn run(&mut self, data : Self::SystemData) {
-        let (entities, mut log, mut wants_melee, names, attributes, pools, mut inflict_damage, 
-            melee_weapons, defense_bonuses, equipped, mut particle_builder, positions, mut rng, player_entity) = data;
+        let (entities, mut log, mut wants_melee, names, attributes, pools,
+            melee_weapons, defense_bonuses, equipped, mut particle_builder, positions, mut rng) = data;
 
         for (entity, wants_melee, name, attacker_attributes, attacker_pools) in (&entities, &wants_melee, &names, &attributes, &pools).join() {
             // Are the attacker and defender alive? Only attack if they are
@@ -39,11 +38,7 @@ impl<'a> System<'a> for MeleeCombatSystem {
                 let natural_roll = rng.roll_dice(1, 100);
                 let attribute_hit_bonus = attacker_attributes.strength.bonus;
 
-                let mut weapon_info = MeleeWeapon{
-                    damage_n_dice : 1,
-                    damage_die_type : 4,
-                    damage_bonus : 0
-                };
+                let mut weapon_info = unarmed();
 
                 for (wielded,melee) in (&equipped, &melee_weapons).join() {
                     if wielded.owner == entity && wielded.slot == EquipmentSlot::Melee {
@@ -62,7 +57,7 @@ impl<'a> System<'a> for MeleeCombatSystem {
                 let modified_hit_roll = natural_roll + attribute_hit_bonus + offensive_bonus;
 
                 //d100 roll under
-                if modified_hit_roll < 55 { // temporary target
+                if modified_hit_roll < MELEE_HIT_TARGET {
                     // Target hit! Roll weapon's dice
                     let base_damage = rng.roll_dice(weapon_info.damage_n_dice, weapon_info.damage_die_type);
                     let attr_damage_bonus = attacker_attributes.strength.bonus;
@@ -83,26 +78,16 @@ impl<'a> System<'a> for MeleeCombatSystem {
 
                      // the tutorial inserts at 0, so the latest is at the top. we do what is more usual, append, so the latest is at bottom
                      if damage == 0 {
-                        log.entries.push(format!("{} is unable to hurt {}", &name.name, &target_name.name));
+                        log.push(LogEntry::new(LogCategory::Combat).npc_name(&name.name).append(" is unable to hurt ").npc_name(&target_name.name));
                     } else {
-                        log.entries.push(format!("{} hits {}, for {} hp.", &name.name, &target_name.name, damage));
-                        //inflict_damage.insert(wants_melee.target, SufferDamage{ amount: damage }).expect("Unable to do damage");
-                        inflict_damage.insert(wants_melee.target, 
-                            SufferDamage{ 
-                                amount: damage,
-                                from_player: entity == *player_entity
-                            }
-                        ).expect("Unable to insert damage component");
-                    }
-                    //particle
-                    let pos = positions.get(wants_melee.target);
-                    if let Some(pos) = pos {
-                        particle_builder.request(pos.x, pos.y, rltk::RGB::named(rltk::ORANGE), rltk::RGB::named(rltk::BLACK), rltk::to_cp437('‼'), 200.0);
+                        log.push(LogEntry::new(LogCategory::Combat).npc_name(&name.name).append(" hits ").npc_name(&target_name.name).append(", for ").damage(damage).append(" hp."));
+                        //the same damage path items and traps use; it handles the particle and the loot
+                        add_effect(Some(entity), EffectType::Damage{ amount: damage }, Targets::Single{ target: wants_melee.target });
                     }
                 }
                 else {
                     //Miss
-                    log.entries.push(format!("{} attacks {}, but misses!", &name.name, &target_name.name));
+                    log.push(LogEntry::new(LogCategory::Combat).npc_name(&name.name).append(" attacks ").npc_name(&target_name.name).append(", but misses!"));
                     //particle
                     let pos = positions.get(wants_melee.target);
                     if let Some(pos) = pos {
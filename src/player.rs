@@ -1,18 +1,49 @@
-use rltk::{VirtualKeyCode, Rltk, Point};
+use rltk::{Rltk, Point};
 use specs::prelude::*;
 use super::{Position, Player, Viewshed, Pools, WantsToMelee, 
     TileType, State, Map, RunState, Entity, Item, WantsToPickupItem, EntityMoved, 
     Faction, raws::Reaction, Vendor, VendorMode,
-    Door, BlocksVisibility, BlocksTile, Renderable, gamelog::GameLog};
+    Door, BlocksVisibility, BlocksTile, Renderable, gamelog::{GameLog, LogCategory, LogEntry, LogFilter},
+    EntryTrigger, Hidden, Name, Attributes, Immobilized, Confusion, Locked, Lockpick, InBackpack, map_builders,
+    camera, travel, list_menu::ListMenu, input::{self, Command, InputContext}, web_ui, layout::layout};
 use std::cmp::{min, max};
 //console is RLTK's wrapper around either println or the web console macro
 use rltk::{console};
 
+//d20 + DEX bonus has to reach this
+const DISARM_DIFFICULTY : i32 = 12;
+
+const STAGGER_DIRECTIONS : [(i32, i32); 8] = [(0, -1), (0, 1), (-1, 0), (1, 0), (-1, -1), (1, -1), (-1, 1), (1, 1)];
+
 // Handle player movement. 
 // Delta X and Y are the relative move
 // requested by the player. We calculate the new coordinates,
 // and if it is a floor - move the player there.
 pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) -> RunState {
+    // Netted players can't go anywhere, but struggling still takes a turn; TurnStatusSystem wears the net off
+    {
+        let player_entity = ecs.fetch::<Entity>();
+        let immobilized = ecs.read_storage::<Immobilized>();
+        if immobilized.get(*player_entity).is_some() {
+            let mut gamelog = ecs.write_resource::<GameLog>();
+            gamelog.add(LogCategory::Combat, "You struggle against the net.");
+            return RunState::Ticking;
+        }
+    }
+
+    // Confused players stagger off any which way
+    let (delta_x, delta_y) = {
+        let player_entity = ecs.fetch::<Entity>();
+        if ecs.read_storage::<Confusion>().get(*player_entity).is_some() {
+            let mut rng = ecs.write_resource::<rltk::RandomNumberGenerator>();
+            let stagger = STAGGER_DIRECTIONS[(rng.roll_dice(1, 8) - 1) as usize];
+            ecs.write_resource::<GameLog>().add(LogCategory::System, "You stagger about, confused.");
+            stagger
+        } else {
+            (delta_x, delta_y)
+        }
+    };
+
     let mut positions = ecs.write_storage::<Position>();
     let mut players = ecs.write_storage::<Player>();
     let mut viewsheds = ecs.write_storage::<Viewshed>();
@@ -23,6 +54,9 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) -> RunState
     let map = ecs.fetch::<Map>();
     //doors
     let mut doors = ecs.write_storage::<Door>();
+    let mut locked = ecs.write_storage::<Locked>();
+    let lockpicks = ecs.read_storage::<Lockpick>();
+    let backpack = ecs.read_storage::<InBackpack>();
     let mut blocks_visibility = ecs.write_storage::<BlocksVisibility>();
     let mut blocks_movement = ecs.write_storage::<BlocksTile>();
     let mut renderables = ecs.write_storage::<Renderable>();
@@ -43,7 +77,7 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) -> RunState
                 for potential_target in map.tile_content[destination_idx].iter() {
                     //handle vendors first
                     if let Some(_vendor) = vendors.get(*potential_target) {
-                        return RunState::ShowVendor{ vendor: *potential_target, mode : VendorMode::Sell }
+                        return RunState::ShowVendor{ vendor: *potential_target, mode : VendorMode::Sell, menu : ListMenu::new() }
                     }
                     let mut hostile = true;
                     if pools.get(*potential_target).is_some() {
@@ -82,6 +116,17 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) -> RunState
                     }
                     let door = doors.get_mut(*potential_target);
                     if let Some(door) = door {
+                        //locked ones need picking first, in the same turn
+                        if locked.get(*potential_target).is_some() {
+                            let has_pick = (&lockpicks, &backpack).join().any(|(_, pack)| pack.owner == entity);
+                            let mut gamelog = ecs.write_resource::<GameLog>();
+                            if !has_pick {
+                                gamelog.add(LogCategory::System, "The door is locked. You'd need a lockpick.");
+                                return RunState::AwaitingInput;
+                            }
+                            gamelog.add(LogCategory::System, "You pick the lock.");
+                            locked.remove(*potential_target);
+                        }
                         //unblock visibility, movement and change the glyph
                         door.open = true;
                         blocks_visibility.remove(*potential_target);
@@ -125,6 +170,48 @@ pub fn try_move_player(delta_x: i32, delta_y: i32, ecs: &mut World) -> RunState
     result
 }
 
+//only matters for the next map we build, doesn't take a turn
+fn toggle_mapgen_visualizer(ecs: &mut World) -> RunState {
+    let on = !map_builders::mapgen_snapshots();
+    map_builders::set_mapgen_snapshots(on);
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+    gamelog.add(LogCategory::System, format!("Map generation visualizer {}.", if on { "on" } else { "off" }));
+    RunState::AwaitingInput
+}
+
+fn try_next_level(ecs: &mut World) -> RunState {
+    let player_pos = ecs.fetch::<Point>();
+    let map = ecs.fetch::<Map>();
+    if map.tiles[map.xy_idx(player_pos.x, player_pos.y)] == TileType::DownStairs {
+        RunState::NextLevel
+    } else {
+        let mut gamelog = ecs.fetch_mut::<GameLog>();
+        gamelog.add(LogCategory::System, "There is no way down from here.");
+        RunState::AwaitingInput
+    }
+}
+
+//the level as it is now, with whoever's still standing on it
+fn export_current_map(ecs: &mut World) -> RunState {
+    let result = {
+        let map = ecs.fetch::<Map>();
+        let player_entity = ecs.fetch::<Entity>();
+        let entities = ecs.entities();
+        let positions = ecs.read_storage::<Position>();
+        let names = ecs.read_storage::<Name>();
+        let spawns : Vec<(usize, String)> = (&entities, &positions, &names).join()
+            .filter(|(entity, _, _)| *entity != *player_entity)
+            .map(|(_, pos, name)| (map.xy_idx(pos.x, pos.y), name.name.clone()))
+            .collect();
+        map_builders::export_map(&*map, &spawns, &format!("level_{}", map.depth))
+    };
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+    match result {
+        Ok(msg) | Err(msg) => gamelog.add(LogCategory::System, msg)
+    }
+    RunState::AwaitingInput
+}
+
 fn get_item(ecs: &mut World) {
     let player_pos = ecs.fetch::<Point>();
     let player_entity = ecs.fetch::<Entity>();
@@ -142,7 +229,7 @@ fn get_item(ecs: &mut World) {
 
     match target_item {
         // the tutorial inserts at 0, so the latest is at the top. we do what is more usual, append, so the latest is at bottom
-        None => gamelog.entries.push("There is nothing here to pick up.".to_string()),
+        None => gamelog.add(LogCategory::Loot, "There is nothing here to pick up."),
         Some(item) => {
             let mut pickup = ecs.write_storage::<WantsToPickupItem>();
             pickup.insert(*player_entity, WantsToPickupItem{ collected_by: *player_entity, item }).expect("Unable to insert want to pickup");
@@ -150,78 +237,144 @@ fn get_item(ecs: &mut World) {
     }
 }
 
+fn disarm_trap(ecs: &mut World) -> RunState {
+    let player_pos = ecs.fetch::<Point>();
+    let player_entity = ecs.fetch::<Entity>();
+    let map = ecs.fetch::<Map>();
+    let entities = ecs.entities();
+    let triggers = ecs.read_storage::<EntryTrigger>();
+    let hidden = ecs.read_storage::<Hidden>();
+    let names = ecs.read_storage::<Name>();
+    let attributes = ecs.read_storage::<Attributes>();
+    let mut rng = ecs.write_resource::<rltk::RandomNumberGenerator>();
+    let mut gamelog = ecs.fetch_mut::<GameLog>();
+
+    // Look for a trap we know about, underfoot or next to us
+    let mut target_trap : Option<Entity> = None;
+    for y in player_pos.y-1 ..= player_pos.y+1 {
+        for x in player_pos.x-1 ..= player_pos.x+1 {
+            if x > 0 && x < map.width-1 && y > 0 && y < map.height-1 {
+                let idx = map.xy_idx(x, y);
+                for potential_trap in map.tile_content[idx].iter() {
+                    if triggers.get(*potential_trap).is_some() && hidden.get(*potential_trap).is_none() {
+                        target_trap = Some(*potential_trap);
+                    }
+                }
+            }
+        }
+    }
+
+    match target_trap {
+        None => {
+            gamelog.add(LogCategory::System, "There is no trap nearby to disarm.");
+            RunState::AwaitingInput
+        }
+        Some(trap) => {
+            let dex_bonus = if let Some(attr) = attributes.get(*player_entity) { attr.dexterity.bonus } else { 0 };
+            let trap_name = if let Some(name) = names.get(trap) { name.name.clone() } else { "trap".to_string() };
+            if rng.roll_dice(1, 20) + dex_bonus >= DISARM_DIFFICULTY {
+                gamelog.push(LogEntry::new(LogCategory::System).append("You disarm the ").item_name(&trap_name).append("."));
+                entities.delete(trap).expect("Unable to delete trap");
+            } else {
+                gamelog.push(LogEntry::new(LogCategory::System).append("You fail to disarm the ").item_name(&trap_name).append("."));
+            }
+            RunState::Ticking
+        }
+    }
+}
+
+//the buttons the web page shows while playing; movement in keypad order
+const WEB_MOVES : [Command; 9] = [Command::MoveNW, Command::MoveN, Command::MoveNE, Command::MoveW, Command::Wait,
+    Command::MoveE, Command::MoveSW, Command::MoveS, Command::MoveSE];
+const WEB_ACTIONS : [Command; 10] = [Command::Inventory, Command::Drop, Command::Remove, Command::Throw, Command::Disarm,
+    Command::Explore, Command::Log, Command::Character, Command::Examine, Command::Overview];
+
+fn offer_web_actions(ecs : &World) {
+    for command in WEB_MOVES.iter() {
+        web_ui::offer(InputContext::Game, *command);
+    }
+    //picking up only when there's something underfoot
+    let player_pos = ecs.fetch::<Point>();
+    let items = ecs.read_storage::<Item>();
+    let positions = ecs.read_storage::<Position>();
+    if (&items, &positions).join().any(|(_, pos)| pos.x == player_pos.x && pos.y == player_pos.y) {
+        web_ui::offer(InputContext::Game, Command::PickUp);
+    }
+    //and going down only on the stairs
+    let map = ecs.fetch::<Map>();
+    if map.tiles[map.xy_idx(player_pos.x, player_pos.y)] == TileType::DownStairs {
+        web_ui::offer(InputContext::Game, Command::Descend);
+    }
+    for command in WEB_ACTIONS.iter() {
+        web_ui::offer(InputContext::Game, *command);
+    }
+}
+
 // Implement the game loop
+//travel takes its steps from here on, one per frame
+fn start_travel(gs: &mut State, travel: Option<travel::Travel>) -> RunState {
+    gs.travel = travel;
+    RunState::AwaitingInput
+}
+
 pub fn player_input(gs: &mut State, ctx: &mut Rltk) -> RunState {
-    // Player movement
-    // New: Handle web buttons
-    if let Some(btn) = &ctx.web_button {
-        match btn.trim() {
-            "go_nw" => return try_move_player(-1, -1, &mut gs.ecs),
-            "go_n" => return try_move_player(0, -1, &mut gs.ecs),
-            "go_ne" => return try_move_player(1, -1, &mut gs.ecs),
-            "go_w" => return try_move_player(-1, 0, &mut gs.ecs),
-            "go_e" => return try_move_player(1, 0, &mut gs.ecs),
-            "go_sw" => return try_move_player(-1, 1, &mut gs.ecs),
-            "go_s" => return try_move_player(0, 1, &mut gs.ecs),
-            "go_se" => return try_move_player(1, 1, &mut gs.ecs),
-            //skip turn
-            "go_wait" => return RunState::Ticking,
-            //others
-            "get" => get_item(&mut gs.ecs),
-            "inven" => return RunState::ShowInventory,
-            "drop" => return RunState::ShowDropItem,
-            "remove" => return RunState::ShowRemoveItem,
-            _ => { return RunState::AwaitingInput } //Nothing happened
+    //travelling walks by itself; any key or click calls it off
+    if let Some(mut travelling) = gs.travel.take() {
+        if ctx.key.is_some() || ctx.web_button.is_some() || ctx.left_click {
+            return RunState::AwaitingInput;
         }
+        if let Some(result) = travel::continue_travel(&mut travelling, &mut gs.ecs) {
+            gs.travel = Some(travelling);
+            return result;
+        }
+        return RunState::AwaitingInput;
     }
-    else {
-        // New: handle keyboard inputs.
-        match ctx.key {
-            None => { return RunState::AwaitingInput } // Nothing happened
-            Some(key) => {
-                // A key is pressed or held
-                match key {
-                    // We're matching a key code from GLFW (the GL library underlying RLTK),
-                    // and applying movement via the move_player function.
-
-                    // Numpad
-                    VirtualKeyCode::Numpad8 => return try_move_player(0, -1, &mut gs.ecs),
-                    VirtualKeyCode::Numpad4 => return try_move_player(-1, 0, &mut gs.ecs),
-                    VirtualKeyCode::Numpad6 => return try_move_player(1, 0, &mut gs.ecs),
-                    VirtualKeyCode::Numpad2 => return try_move_player(0, 1, &mut gs.ecs),
-
-                    // Numpad diagonals
-                    VirtualKeyCode::Numpad7 => return try_move_player(-1, -1, &mut gs.ecs),
-                    VirtualKeyCode::Numpad9 => return try_move_player(1, -1, &mut gs.ecs),
-                    VirtualKeyCode::Numpad1 => return try_move_player(-1, 1, &mut gs.ecs),
-                    VirtualKeyCode::Numpad3 => return try_move_player(1, 1, &mut gs.ecs),
-
-                    //vi keys
-                    VirtualKeyCode::Y => return try_move_player(-1, -1, &mut gs.ecs),
-                    VirtualKeyCode::U => return try_move_player(1, -1, &mut gs.ecs),
-                    VirtualKeyCode::B => return try_move_player(-1, 1, &mut gs.ecs),
-                    VirtualKeyCode::N => return try_move_player(1, 1, &mut gs.ecs),
-
-                    // Cursors
-                    VirtualKeyCode::Up => return try_move_player(0, -1, &mut gs.ecs),
-                    VirtualKeyCode::Down => return try_move_player(0, 1, &mut gs.ecs),
-                    VirtualKeyCode::Left => return try_move_player(-1, 0, &mut gs.ecs),
-                    VirtualKeyCode::Right => return try_move_player(1, 0, &mut gs.ecs),
-
-                    // Skip turn
-                    VirtualKeyCode::Numpad5 => return RunState::Ticking,
-                    VirtualKeyCode::Space => return RunState::Ticking,
-
-                    VirtualKeyCode::G => get_item(&mut gs.ecs),
-                    VirtualKeyCode::I => return RunState::ShowInventory,
-                    VirtualKeyCode::D => return RunState::ShowDropItem,
-                    VirtualKeyCode::R => return RunState::ShowRemoveItem,
-
-
-                    _ => { return RunState::AwaitingInput } // Nothing happened, ignore all the other possibilities
-                }
-            }
+
+    //click on the map to walk there
+    if ctx.left_click {
+        let mouse_pos = ctx.mouse_pos();
+        if layout(ctx).in_view(mouse_pos.0, mouse_pos.1) {
+            let (min_x, _max_x, min_y, _max_y) = camera::get_screen_bounds(&gs.ecs, ctx);
+            let target = Point::new(mouse_pos.0 + min_x, mouse_pos.1 + min_y);
+            let travelling = travel::travel_to(&mut gs.ecs, target);
+            return start_travel(gs, travelling);
+        }
+    }
+
+    offer_web_actions(&gs.ecs);
+
+    // Player movement and everything else; the bindings decide which keys mean what
+    let command = match input::read_command(ctx, InputContext::Game) {
+        None => return RunState::AwaitingInput, // Nothing happened
+        Some(command) => command
+    };
+    if let Some((delta_x, delta_y)) = command.direction() {
+        return try_move_player(delta_x, delta_y, &mut gs.ecs);
+    }
+    match command {
+        // Skip turn
+        Command::Wait => return RunState::Ticking,
+        Command::PickUp => get_item(&mut gs.ecs),
+        Command::Inventory => return RunState::ShowInventory{ menu : ListMenu::new() },
+        Command::Drop => return RunState::ShowDropItem{ menu : ListMenu::new() },
+        Command::Remove => return RunState::ShowRemoveItem{ menu : ListMenu::new() },
+        Command::Disarm => return disarm_trap(&mut gs.ecs),
+        Command::Descend => return try_next_level(&mut gs.ecs),
+        Command::Throw => return RunState::ShowThrowItem{ menu : ListMenu::new() },
+        Command::ToggleVisualizer => return toggle_mapgen_visualizer(&mut gs.ecs),
+        Command::Export => return export_current_map(&mut gs.ecs),
+        Command::Log => return RunState::ShowLog{ scroll : 0, filter : LogFilter::all() },
+        Command::Character => return RunState::ShowCharacterSheet,
+        Command::Examine => return RunState::ShowExamineMenu{ menu : ListMenu::new() },
+        Command::Overview => {
+            let player_pos = gs.ecs.fetch::<Point>();
+            return RunState::ShowOverview{ x : player_pos.x, y : player_pos.y, zoomed_out : true };
+        }
+        Command::Explore => {
+            let travelling = travel::auto_explore(&mut gs.ecs);
+            return start_travel(gs, travelling);
         }
+        _ => { return RunState::AwaitingInput } // Nothing happened, ignore all the other possibilities
     }
 
     RunState::Ticking
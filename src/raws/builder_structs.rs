@@ -0,0 +1,18 @@
+use serde::{Deserialize};
+
+#[derive(Deserialize, Debug)]
+pub struct BuilderRaws {
+    pub chains : Vec<BuilderChainRecipe>
+}
+
+//a map builder chain: one starting builder, then the meta builders in order
+#[derive(Deserialize, Debug, Clone)]
+pub struct BuilderChainRecipe {
+    pub name : String,
+    pub level_type : Option<String>,
+    pub min_depth : Option<i32>,
+    pub max_depth : Option<i32>,
+    pub weight : Option<i32>,
+    pub start : String,
+    pub meta : Vec<String>
+}
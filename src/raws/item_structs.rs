@@ -7,11 +7,15 @@ pub struct Item {
     pub name : String,
     pub renderable : Option<Renderable>,
     pub consumable : Option<Consumable>,
+    pub charged : Option<Charged>,
     pub weapon : Option<Weapon>,
     pub wearable : Option<Wearable>,
     pub weight_lbs : Option<f32>,
     pub base_value : Option<f32>,
-    pub vendor_category : Option<String>
+    pub vendor_category : Option<String>,
+    pub fragile : Option<bool>,
+    pub stackable : Option<bool>,
+    pub lockpick : Option<bool>
 }
 
 #[derive(Deserialize, Debug)]
@@ -27,6 +31,14 @@ pub struct Consumable {
     pub effects : HashMap<String, String>
 }
 
+//used over and over until it runs dry, then reloaded from an ammo item
+#[derive(Deserialize, Debug)]
+pub struct Charged {
+    pub charges : i32,
+    pub ammo : String,
+    pub effects : HashMap<String, String>
+}
+
 #[derive(Deserialize, Debug)]
 pub struct Weapon {
     pub range: String,
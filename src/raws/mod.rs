@@ -12,11 +12,26 @@ mod spawn_table_structs;
 use spawn_table_structs::*;
 mod faction_structs;
 pub use faction_structs::*;
+mod builder_structs;
+pub use builder_structs::*;
+mod prefab_structs;
+pub use prefab_structs::*;
+mod tile_structs;
+pub use tile_structs::*;
 use serde::{Deserialize};
 //console is RLTK's wrapper around either println or the web console macro
 use rltk::{console};
 
 rltk::embedded_resource!(RAW_FILE, "../../data/spawns.json");
+rltk::embedded_resource!(BUILDER_FILE, "../../data/builders.json");
+rltk::embedded_resource!(PREFAB_LEVEL_FILE, "../../data/prefabs/levels.json");
+rltk::embedded_resource!(PREFAB_SECTION_FILE, "../../data/prefabs/sections.json");
+rltk::embedded_resource!(PREFAB_VAULT_FILE, "../../data/prefabs/vaults.json");
+rltk::embedded_resource!(TILE_FILE, "../../data/tiles.json");
+
+//read from disk on native builds, so recipes can be tried out without a rebuild; there's no file system on the web
+#[cfg(not(target_arch = "wasm32"))]
+const BUILDER_PATH : &str = "data/builders.json";
 
 lazy_static! {
     pub static ref RAWS : Mutex<RawMaster> = Mutex::new(RawMaster::empty());
@@ -45,4 +60,67 @@ pub fn load_raws() {
     //console::log(&format!("{:?}", decoder));
     //store it
     RAWS.lock().unwrap().load(decoder);
-}
\ No newline at end of file
+
+    // Map builder chains live in their own file
+    let builder_decoder = match builder_file() {
+        Some(recipes) => recipes,
+        None => {
+            rltk::link_resource!(BUILDER_FILE, "../../data/builders.json");
+            let builder_data = rltk::embedding::EMBED
+            .lock()
+            .unwrap()
+            .get_resource("../../data/builders.json".to_string())
+            .unwrap();
+            let builder_string = std::str::from_utf8(&builder_data).expect("Unable to convert to a valid UTF-8 string.");
+            serde_json::from_str(&builder_string).expect("Unable to parse JSON")
+        }
+    };
+    RAWS.lock().unwrap().load_builder_chains(builder_decoder);
+
+    // Tile properties go to the map, which looks them up far too often to go through RAWS
+    rltk::link_resource!(TILE_FILE, "../../data/tiles.json");
+    let tile_data = rltk::embedding::EMBED
+    .lock()
+    .unwrap()
+    .get_resource("../../data/tiles.json".to_string())
+    .unwrap();
+    let tile_string = std::str::from_utf8(&tile_data).expect("Unable to convert to a valid UTF-8 string.");
+    let tile_decoder : TileFile = serde_json::from_str(&tile_string).expect("Unable to parse JSON");
+    crate::map::set_tile_properties(tile_properties_from_raws(&tile_decoder));
+
+    // Prefabs come after the spawns, so their legends can be checked against them
+    rltk::link_resource!(PREFAB_LEVEL_FILE, "../../data/prefabs/levels.json");
+    rltk::link_resource!(PREFAB_SECTION_FILE, "../../data/prefabs/sections.json");
+    rltk::link_resource!(PREFAB_VAULT_FILE, "../../data/prefabs/vaults.json");
+    let mut raws = RAWS.lock().unwrap();
+    raws.prefab_levels = raws.validate_prefabs(load_prefab_file("../../data/prefabs/levels.json"));
+    raws.prefab_sections = raws.validate_prefabs(load_prefab_file("../../data/prefabs/sections.json"));
+    raws.prefab_vaults = raws.validate_prefabs(load_prefab_file("../../data/prefabs/vaults.json"));
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn builder_file() -> Option<BuilderRaws> {
+    let text = std::fs::read_to_string(BUILDER_PATH).ok()?;
+    match serde_json::from_str(&text) {
+        Ok(recipes) => Some(recipes),
+        Err(e) => {
+            console::log(&format!("Could not read builder chains from {}, using the built-in ones: {}", BUILDER_PATH, e));
+            None
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn builder_file() -> Option<BuilderRaws> {
+    None
+}
+
+fn load_prefab_file(path : &str) -> PrefabFile {
+    let prefab_data = rltk::embedding::EMBED
+    .lock()
+    .unwrap()
+    .get_resource(path.to_string())
+    .unwrap();
+    let prefab_string = std::str::from_utf8(&prefab_data).expect("Unable to convert to a valid UTF-8 string.");
+    serde_json::from_str(&prefab_string).expect("Unable to parse JSON")
+}
@@ -0,0 +1,29 @@
+use serde::{Deserialize};
+
+//one file in data/prefabs: the glyph legend, then the templates that use it
+#[derive(Deserialize, Debug)]
+pub struct PrefabFile {
+    pub legend : Vec<PrefabLegendEntry>,
+    pub prefabs : Vec<PrefabRaw>
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PrefabLegendEntry {
+    pub glyph : String,
+    pub tile : String,
+    pub spawn : Option<String>
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PrefabRaw {
+    pub name : String,
+    pub width : usize,
+    pub height : usize,
+    pub template : Vec<String>,
+    //extra glyphs for this prefab only; after loading, the whole legend it uses
+    pub legend : Option<Vec<PrefabLegendEntry>>,
+    pub placement : Option<Vec<String>>,
+    pub first_depth : Option<i32>,
+    pub last_depth : Option<i32>,
+    pub frequency : Option<i32>
+}
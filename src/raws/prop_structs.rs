@@ -11,6 +11,7 @@ pub struct Prop {
     pub blocks_tile : Option<bool>,
     pub blocks_visibility : Option<bool>,
     pub door_open : Option<bool>,
+    pub locked : Option<bool>,
     pub light : Option<Light>
 }
 
@@ -1,11 +1,13 @@
 use std::collections::{HashMap, HashSet};
 use specs::prelude::*;
 use crate::components::*;
-use super::{Raws, faction_structs::Reaction};
+use super::{Raws, faction_structs::Reaction, BuilderRaws, BuilderChainRecipe, PrefabFile, PrefabRaw, PrefabLegendEntry, TileFile};
+use crate::map::{TileType, TileProperties};
 use crate::random_table::{RandomTable};
+use crate::list_menu::ItemCategory;
 //console is RLTK's wrapper around either println or the web console macro
 use rltk::{console};
-use crate::{attr_bonus};
+use crate::{attr_bonus, summarize_item};
 use regex::Regex;
 
 pub fn parse_dice_string(dice : &str) -> (i32, i32, i32) {
@@ -42,7 +44,11 @@ pub struct RawMaster {
     item_index : HashMap<String, usize>,
     mob_index : HashMap<String, usize>,
     prop_index : HashMap<String, usize>,
-    faction_index : HashMap<String, HashMap<String, Reaction>>
+    faction_index : HashMap<String, HashMap<String, Reaction>>,
+    builder_chains : Vec<BuilderChainRecipe>,
+    pub prefab_levels : Vec<PrefabRaw>,
+    pub prefab_sections : Vec<PrefabRaw>,
+    pub prefab_vaults : Vec<PrefabRaw>
 }
 
 impl RawMaster {
@@ -54,10 +60,66 @@ impl RawMaster {
             item_index : HashMap::new(),
             mob_index : HashMap::new(),
             prop_index : HashMap::new(),
-            faction_index : HashMap::new()
+            faction_index : HashMap::new(),
+            builder_chains : Vec::new(),
+            prefab_levels : Vec::new(),
+            prefab_sections : Vec::new(),
+            prefab_vaults : Vec::new()
         }
     }
 
+    pub fn load_builder_chains(&mut self, raws : BuilderRaws) {
+        self.builder_chains = raws.chains;
+    }
+
+    /// Gives each prefab the legend it uses and drops the ones that wouldn't load right
+    pub fn validate_prefabs(&self, file : PrefabFile) -> Vec<PrefabRaw> {
+        let mut valid = Vec::new();
+        for mut prefab in file.prefabs.into_iter() {
+            // The file's legend, plus whatever this prefab adds or overrides
+            let mut legend : Vec<PrefabLegendEntry> = file.legend.clone();
+            if let Some(extra) = &prefab.legend {
+                for entry in extra.iter() {
+                    legend.retain(|l| l.glyph != entry.glyph);
+                    legend.push(entry.clone());
+                }
+            }
+
+            let mut ok = true;
+            for entry in legend.iter_mut() {
+                if entry.glyph.chars().count() != 1 || parse_tile_type(&entry.tile).is_none() {
+                    console::log(&format!("WARNING - prefab [{}] has a bad legend entry [{}] => [{}]", prefab.name, entry.glyph, entry.tile));
+                    ok = false;
+                }
+                if let Some(spawn) = &entry.spawn {
+                    if !is_spawnable(self, spawn) {
+                        //the tile still goes in, there's just nothing on it
+                        console::log(&format!("WARNING - prefab [{}] wants unknown spawn [{}]", prefab.name, spawn));
+                        entry.spawn = None;
+                    }
+                }
+            }
+
+            if prefab.template.len() != prefab.height || prefab.template.iter().any(|row| row.chars().count() != prefab.width) {
+                console::log(&format!("WARNING - prefab [{}] doesn't match its declared size of {}x{}", prefab.name, prefab.width, prefab.height));
+                ok = false;
+            }
+            for ch in prefab.template.iter().flat_map(|row| row.chars()) {
+                if !legend.iter().any(|l| l.glyph.starts_with(ch)) {
+                    console::log(&format!("WARNING - prefab [{}] uses [{}], which isn't in the legend", prefab.name, ch));
+                    ok = false;
+                    break;
+                }
+            }
+
+            if ok {
+                prefab.legend = Some(legend);
+                valid.push(prefab);
+            }
+        }
+        valid
+    }
+
     pub fn load(&mut self, raws : Raws) {
         self.raws = raws;
         self.item_index = HashMap::new();
@@ -170,6 +232,51 @@ fn find_slot_for_equippable_item(tag : &str, raws: &RawMaster) -> EquipmentSlot
     panic!("Trying to equip {}, but it has no slot tag.", tag);
 }
 
+pub fn get_builder_chain(raws : &RawMaster, name : &str) -> Option<BuilderChainRecipe> {
+    raws.builder_chains.iter().find(|c| c.name == name).cloned()
+}
+
+pub fn get_builder_chain_names(raws : &RawMaster) -> Vec<String> {
+    raws.builder_chains.iter().map(|c| c.name.clone()).collect()
+}
+
+/// All the chains that fit the level type (if any) and depth, with their weights
+pub fn get_builder_chains(raws : &RawMaster, level_type : Option<&str>, depth : i32) -> Vec<(BuilderChainRecipe, i32)> {
+    raws.builder_chains
+        .iter()
+        .filter(|c| level_type.is_none() || c.level_type.as_deref() == level_type)
+        .filter(|c| depth >= c.min_depth.unwrap_or(0) && depth <= c.max_depth.unwrap_or(std::i32::MAX))
+        .map(|c| (c.clone(), c.weight.unwrap_or(1)))
+        .filter(|c| c.1 > 0)
+        .collect()
+}
+
+//the same short summary the menus show for items already spawned
+pub fn item_summary(name : &str, raws : &RawMaster) -> String {
+    if let Some(item) = raws.raws.items.iter().find(|i| i.name == name) {
+        let weapon = item.weapon.as_ref().map(|w| {
+            let (damage_n_dice, damage_die_type, damage_bonus) = parse_dice_string(&w.base_damage);
+            MeleeWeapon{ damage_n_dice, damage_die_type, damage_bonus }
+        });
+        return summarize_item(weapon.as_ref(), item.wearable.as_ref().map(|w| w.defense_bonus));
+    }
+    String::new()
+}
+
+pub fn item_weight(name : &str, raws : &RawMaster) -> f32 {
+    raws.raws.items.iter().find(|i| i.name == name).and_then(|i| i.weight_lbs).unwrap_or(0.0)
+}
+
+//which list menu tab a not-yet-spawned item goes under
+pub fn item_category(name : &str, raws : &RawMaster) -> ItemCategory {
+    match raws.raws.items.iter().find(|i| i.name == name) {
+        Some(item) if item.weapon.is_some() || item.charged.is_some() => ItemCategory::Weapons,
+        Some(item) if item.wearable.is_some() => ItemCategory::Armor,
+        Some(item) if item.consumable.is_some() => ItemCategory::Consumables,
+        _ => ItemCategory::Other
+    }
+}
+
 pub fn get_vendor_items(categories: &[String], raws : &RawMaster) -> Vec<(String, f32)> {
     let mut result : Vec<(String, f32)> = Vec::new();
 
@@ -203,6 +310,78 @@ fn spawn_position<'a>(pos : SpawnType, new_entity : EntityBuilder<'a>, tag : &st
 
 }
 
+pub fn parse_tile_type(name : &str) -> Option<TileType> {
+    match name {
+        "Wall" => Some(TileType::Wall),
+        "Tree" => Some(TileType::Tree),
+        "Floor" => Some(TileType::Floor),
+        "FloorIndoor" => Some(TileType::FloorIndoor),
+        "DownStairs" => Some(TileType::DownStairs),
+        "UpStairs" => Some(TileType::UpStairs),
+        "Road" => Some(TileType::Road),
+        "Sidewalk" => Some(TileType::Sidewalk),
+        "Bridge" => Some(TileType::Bridge),
+        "Grass" => Some(TileType::Grass),
+        "Rubble" => Some(TileType::Rubble),
+        "ShallowWater" => Some(TileType::ShallowWater),
+        "DeepWater" => Some(TileType::DeepWater),
+        "Window" => Some(TileType::Window),
+        "Fence" => Some(TileType::Fence),
+        _ => None
+    }
+}
+
+pub fn tile_properties_from_raws(file : &TileFile) -> Vec<(TileType, TileProperties)> {
+    let mut result = Vec::new();
+    for tile in file.tiles.iter() {
+        match parse_tile_type(&tile.name) {
+            Some(tile_type) => result.push((tile_type, TileProperties{
+                walkable : tile.walkable,
+                opaque : tile.opaque,
+                cost : tile.cost,
+                glyph : rltk::to_cp437(tile.glyph.chars().next().unwrap_or('?')),
+                template : tile.template.chars().next().unwrap_or('?'),
+                fg : rltk::RGB::from_hex(&tile.fg).expect("Invalid RGB")
+            })),
+            None => console::log(&format!("WARNING - unknown tile type [{}] in tiles.json", tile.name))
+        }
+    }
+    result
+}
+
+pub fn get_prefab<'a>(prefabs : &'a [PrefabRaw], name : &str) -> Option<&'a PrefabRaw> {
+    prefabs.iter().find(|p| p.name == name)
+}
+
+pub fn is_spawnable(raws : &RawMaster, key : &str) -> bool {
+    raws.item_index.contains_key(key) || raws.mob_index.contains_key(key) || raws.prop_index.contains_key(key)
+}
+
+/// Whether a spawn shuts its tile for good: a prop that blocks it and can't be opened like a door
+pub fn spawn_blocks_path(raws : &RawMaster, key : &str) -> bool {
+    match raws.prop_index.get(key) {
+        None => false,
+        Some(idx) => {
+            let prop = &raws.raws.props[*idx];
+            prop.blocks_tile.unwrap_or(false) && prop.door_open.is_none()
+        }
+    }
+}
+
+/// What a spawn looks like without spawning it, e.g. for exporting maps
+pub fn get_spawn_glyph(raws : &RawMaster, key : &str) -> Option<(char, rltk::RGB)> {
+    let renderable = if raws.item_index.contains_key(key) {
+        &raws.raws.items[raws.item_index[key]].renderable
+    } else if raws.mob_index.contains_key(key) {
+        &raws.raws.mobs[raws.mob_index[key]].renderable
+    } else if raws.prop_index.contains_key(key) {
+        &raws.raws.props[raws.prop_index[key]].renderable
+    } else {
+        return None;
+    };
+    renderable.as_ref().map(|r| (r.glyph.chars().next().unwrap(), rltk::RGB::from_hex(&r.fg).expect("Invalid RGB")))
+}
+
 fn get_renderable_component(renderable : &super::item_structs::Renderable) -> crate::components::Renderable {
     crate::components::Renderable{  
         glyph: rltk::to_cp437(renderable.glyph.chars().next().unwrap()),
@@ -225,6 +404,38 @@ pub fn spawn_named_entity(raws: &RawMaster, ecs : &mut World, key : &str, pos :
     None
 }
 
+//one effect table for every entity type, so a new effect in the raws works on items and traps alike
+macro_rules! apply_effects {
+    ( $effects:expr, $eb:expr ) => {
+        for effect in $effects.iter() {
+            let effect_name = effect.0.as_str();
+            match effect_name {
+                "med_item" => { $eb = $eb.with(MedItem{ heal_amount: effect.1.parse::<i32>().unwrap() }) }
+                "ranged" => { $eb = $eb.with(Ranged{ range: effect.1.parse::<i32>().unwrap() }) },
+                "damage" => { $eb = $eb.with(InflictsDamage{ damage : effect.1.parse::<i32>().unwrap() }) }
+                "area_of_effect" => { $eb = $eb.with(AreaOfEffect{ radius: effect.1.parse::<i32>().unwrap(), shape: AoEShape::Circle }) }
+                "cone" => { $eb = $eb.with(AreaOfEffect{ radius: effect.1.parse::<i32>().unwrap(), shape: AoEShape::Cone }) }
+                "line" => { $eb = $eb.with(AreaOfEffect{ radius: effect.1.parse::<i32>().unwrap(), shape: AoEShape::Line }) }
+                "falloff" => { $eb = $eb.with(DamageFalloff{}) }
+                "spares_allies" => { $eb = $eb.with(SparesAllies{}) }
+                "confusion" => { $eb = $eb.with(Confusion{ turns: effect.1.parse::<i32>().unwrap() }) }
+                "immobilize" => { $eb = $eb.with(Immobilized{ turns: effect.1.parse::<i32>().unwrap() }) }
+                "teleport" => { $eb = $eb.with(Teleports{ range: effect.1.parse::<i32>().unwrap() }) }
+                "alarm" => { $eb = $eb.with(Alarm{ faction: effect.1.clone() }) }
+                "spawn" => { $eb = $eb.with(SpawnsEntity{ name: effect.1.clone(), deploy: false }) }
+                //deploying a trap kit is spawning the trap next to you, where you can see it
+                "deploy" => { $eb = $eb.with(SpawnsEntity{ name: effect.1.clone(), deploy: true }) }
+                "food" => { $eb = $eb.with(ProvidesFood{}) }
+                "drink" => { $eb = $eb.with(ProvidesQuench{}) }
+                "single_activation" => { $eb = $eb.with(SingleActivation{}) }
+                _ => {
+                    console::log(&format!("Warning: effect {} not implemented.", effect_name));
+                }
+            }
+        }
+    };
+}
+
 pub fn spawn_named_item(raws: &RawMaster, ecs: &mut World, key : &str, pos : SpawnType) -> Option<Entity> {
     if raws.item_index.contains_key(key) {
         let item_template = &raws.raws.items[raws.item_index[key]];
@@ -246,23 +457,24 @@ pub fn spawn_named_item(raws: &RawMaster, ecs: &mut World, key : &str, pos : Spa
 
         if let Some(consumable) = &item_template.consumable {
             eb = eb.with(crate::components::Consumable{});
-            for effect in consumable.effects.iter() {
-                let effect_name = effect.0.as_str();
-                match effect_name {
-                    "med_item" => { 
-                        eb = eb.with(MedItem{ heal_amount: effect.1.parse::<i32>().unwrap() }) 
-                    }
-                    "ranged" => { eb = eb.with(Ranged{ range: effect.1.parse::<i32>().unwrap() }) },
-                    "damage" => { eb = eb.with(InflictsDamage{ damage : effect.1.parse::<i32>().unwrap() }) }
-                    "area_of_effect" => { eb = eb.with(AreaOfEffect{ radius: effect.1.parse::<i32>().unwrap() }) }
-                    "confusion" => { eb = eb.with(Confusion{ turns: effect.1.parse::<i32>().unwrap() }) }
-                    "food" => { eb = eb.with(ProvidesFood{}) }
-                    "drink" => { eb = eb.with(ProvidesQuench{}) }
-                    _ => {
-                        println!("Warning: consumable effect {} not implemented.", effect_name);
-                    }
-                }
-            }
+            apply_effects!(consumable.effects, eb);
+        }
+
+        if let Some(charged) = &item_template.charged {
+            eb = eb.with(Charges{ current: charged.charges, max: charged.charges, ammo: charged.ammo.clone() });
+            apply_effects!(charged.effects, eb);
+        }
+
+        if let Some(stackable) = item_template.stackable {
+            if stackable { eb = eb.with(Stackable{ quantity: 1 }) };
+        }
+
+        if let Some(fragile) = item_template.fragile {
+            if fragile { eb = eb.with(Fragile{}) };
+        }
+
+        if let Some(lockpick) = item_template.lockpick {
+            if lockpick { eb = eb.with(Lockpick{}) };
         }
 
         if let Some(weapon) = &item_template.weapon {
@@ -423,13 +635,7 @@ pub fn spawn_named_prop(raws: &RawMaster, ecs: &mut World, key : &str, pos : Spa
         }
         if let Some(entry_trigger) = &prop_template.entry_trigger {
             eb = eb.with(EntryTrigger{});
-            for effect in entry_trigger.effects.iter() {
-                match effect.0.as_str() {
-                    "damage" => { eb = eb.with(InflictsDamage{ damage : effect.1.parse::<i32>().unwrap() }) }
-                    "single_activation" => { eb = eb.with(SingleActivation{}) }
-                    _ => {}
-                }
-            }
+            apply_effects!(entry_trigger.effects, eb);
         }
         if let Some(blocks_tile) = prop_template.blocks_tile {
             if blocks_tile { eb = eb.with(BlocksTile{}) };
@@ -440,6 +646,9 @@ pub fn spawn_named_prop(raws: &RawMaster, ecs: &mut World, key : &str, pos : Spa
         if let Some(door_open) = prop_template.door_open {
             eb = eb.with(Door{ open: door_open });
         }
+        if let Some(locked) = prop_template.locked {
+            if locked { eb = eb.with(Locked{}) };
+        }
         if let Some(light) = &prop_template.light {
             eb = eb.with(LightSource{ range: light.range, color : rltk::RGB::from_hex(&light.color).expect("Bad color") });
             eb = eb.with(Viewshed{ range: light.range, dirty: true, visible_tiles: Vec::new() });
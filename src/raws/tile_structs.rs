@@ -0,0 +1,18 @@
+use serde::{Deserialize};
+
+#[derive(Deserialize, Debug)]
+pub struct TileFile {
+    pub tiles : Vec<TileRaw>
+}
+
+//cost is what pathfinding pays to step onto the tile; diagonals pay a bit more on top
+#[derive(Deserialize, Debug)]
+pub struct TileRaw {
+    pub name : String,
+    pub glyph : String,
+    pub template : String,
+    pub fg : String,
+    pub walkable : bool,
+    pub opaque : bool,
+    pub cost : f32
+}
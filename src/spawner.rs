@@ -129,7 +129,7 @@ pub fn spawn_region(map: &Map, rng: &mut RandomNumberGenerator, area : &[usize],
 }
 
 /// Spawns a named entity (name in tuple.1) at the location in (tuple.0)
-pub fn spawn_entity(ecs: &mut World, spawn : &(&usize, &String)) {
+pub fn spawn_entity(ecs: &mut World, spawn : &(&usize, &String)) -> Option<Entity> {
     let map = ecs.fetch::<Map>();
     let width = map.width as usize;
     let x = (*spawn.0 % width) as i32;
@@ -138,11 +138,10 @@ pub fn spawn_entity(ecs: &mut World, spawn : &(&usize, &String)) {
 
     //spawn from data
     let spawn_result = spawn_named_entity(&RAWS.lock().unwrap(), ecs, &spawn.1, SpawnType::AtPosition{ x, y});
-    if spawn_result.is_some() {
-        return;
+    if spawn_result.is_none() {
+        console::log(&format!("WARNING: We don't know how to spawn [{}]!", spawn.1));
     }
-
-    console::log(&format!("WARNING: We don't know how to spawn [{}]!", spawn.1));
+    spawn_result
 }
 
 ///Random selection
@@ -0,0 +1,217 @@
+use rltk::{Point, BaseMap, Algorithm2D};
+use specs::prelude::*;
+use std::collections::HashSet;
+use super::{Map, RunState, Viewshed, Pools, Faction, Name, Door, Locked, Position, gamelog::{GameLog, LogCategory},
+    tile_walkable, tile_cost, raws::{RAWS, faction_reaction, Reaction}, player::try_move_player};
+
+pub enum TravelMode {
+    //the rest of a path to a clicked tile
+    Path(Vec<usize>),
+    //towards the nearest tile we haven't seen, recalculated every step
+    Explore
+}
+
+/// Walking somewhere without a keypress per step; stops when anything interesting happens
+pub struct Travel {
+    mode : TravelMode,
+    //what we already knew about when we set off, so we only stop for new things
+    hostiles_seen : HashSet<Entity>,
+    combat_messages : usize
+}
+
+//pathfinding over what the player has seen; closed doors count as open, since walking into one opens it,
+//unless it's locked
+struct KnownMap<'a> {
+    map : &'a Map,
+    doors : HashSet<usize>
+}
+
+impl<'a> KnownMap<'a> {
+    fn new(ecs : &World, map : &'a Map) -> KnownMap<'a> {
+        let positions = ecs.read_storage::<Position>();
+        let doors = ecs.read_storage::<Door>();
+        let locked = ecs.read_storage::<Locked>();
+        let doors = (&positions, &doors, !&locked).join().map(|(pos, _, _)| map.xy_idx(pos.x, pos.y)).collect();
+        KnownMap{ map, doors }
+    }
+
+    fn passable(&self, idx : usize) -> bool {
+        self.map.revealed_tiles[idx] && tile_walkable(self.map.tiles[idx]) && (!self.map.blocked[idx] || self.doors.contains(&idx))
+    }
+}
+
+impl<'a> BaseMap for KnownMap<'a> {
+    fn is_opaque(&self, idx:i32) -> bool {
+        self.map.is_opaque(idx)
+    }
+
+    fn get_available_exits(&self, idx:i32) -> Vec<(i32, f32)> {
+        let mut exits : Vec<(i32, f32)> = Vec::new();
+        let x = idx % self.map.width;
+        let y = idx / self.map.width;
+        for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1), (-1, -1), (1, -1), (-1, 1), (1, 1)].iter() {
+            let (nx, ny) = (x + dx, y + dy);
+            if nx < 1 || nx > self.map.width-2 || ny < 1 || ny > self.map.height-2 { continue; }
+            let next = self.map.xy_idx(nx, ny);
+            if !self.passable(next) { continue; }
+            let cost = tile_cost(self.map.tiles[next]);
+            exits.push((next as i32, if *dx != 0 && *dy != 0 { cost * 1.45 } else { cost }));
+        }
+        exits
+    }
+
+    fn get_pathing_distance(&self, idx1:i32, idx2:i32) -> f32 {
+        self.map.get_pathing_distance(idx1, idx2)
+    }
+}
+
+impl<'a> Algorithm2D for KnownMap<'a> {
+    fn point2d_to_index(&self, pt: Point) -> i32 {
+        self.map.point2d_to_index(pt)
+    }
+
+    fn index_to_point2d(&self, idx:i32) -> Point {
+        self.map.index_to_point2d(idx)
+    }
+}
+
+//hostiles the player can see right now
+fn visible_hostiles(ecs : &World) -> Vec<Entity> {
+    let player_entity = ecs.fetch::<Entity>();
+    let map = ecs.fetch::<Map>();
+    let viewsheds = ecs.read_storage::<Viewshed>();
+    let pools = ecs.read_storage::<Pools>();
+    let factions = ecs.read_storage::<Faction>();
+    let raws = RAWS.lock().unwrap();
+
+    let mut hostiles = Vec::new();
+    if let Some(viewshed) = viewsheds.get(*player_entity) {
+        for tile in viewshed.visible_tiles.iter() {
+            let idx = map.xy_idx(tile.x, tile.y);
+            for entity in map.tile_content[idx].iter() {
+                if *entity == *player_entity || pools.get(*entity).is_none() { continue; }
+                if let Some(faction) = factions.get(*entity) {
+                    if faction_reaction(&faction.name, "Player", &raws) == Reaction::Attack {
+                        hostiles.push(*entity);
+                    }
+                }
+            }
+        }
+    }
+    hostiles
+}
+
+fn set_off(ecs : &World, mode : TravelMode) -> Travel {
+    Travel{
+        mode,
+        hostiles_seen : visible_hostiles(ecs).into_iter().collect(),
+        combat_messages : ecs.fetch::<GameLog>().combat_messages
+    }
+}
+
+/// Plans a walk to a tile the player has seen
+pub fn travel_to(ecs : &mut World, target : Point) -> Option<Travel> {
+    let path = {
+        let map = ecs.fetch::<Map>();
+        let player_pos = ecs.fetch::<Point>();
+        let mut known = KnownMap::new(ecs, &*map);
+        let in_bounds = target.x >= 1 && target.x <= map.width-2 && target.y >= 1 && target.y <= map.height-2;
+        let target_idx = if in_bounds { map.xy_idx(target.x, target.y) } else { 0 };
+        if !in_bounds || !known.passable(target_idx) {
+            None
+        } else {
+            let path = rltk::a_star_search(map.xy_idx(player_pos.x, player_pos.y) as i32, target_idx as i32, &mut known);
+            if !path.success || path.steps.len() < 2 { None } else { Some(path.steps) }
+        }
+    };
+
+    match path {
+        None => {
+            ecs.fetch_mut::<GameLog>().add(LogCategory::System, "You can't see a way there.");
+            None
+        }
+        //the first step is where we're standing
+        Some(steps) => Some(set_off(ecs, TravelMode::Path(steps.iter().skip(1).map(|i| *i as usize).collect())))
+    }
+}
+
+/// Sets off exploring, as long as there's nobody to worry about
+pub fn auto_explore(ecs : &mut World) -> Option<Travel> {
+    if !visible_hostiles(ecs).is_empty() {
+        ecs.fetch_mut::<GameLog>().add(LogCategory::System, "Not with enemies in sight!");
+        return None;
+    }
+    Some(set_off(ecs, TravelMode::Explore))
+}
+
+//the next step towards the nearest revealed tile that borders one we haven't seen
+fn explore_step(ecs : &World) -> Option<usize> {
+    let map = ecs.fetch::<Map>();
+    let player_pos = ecs.fetch::<Point>();
+    let known = KnownMap::new(ecs, &*map);
+
+    let mut frontier : Vec<i32> = Vec::new();
+    for y in 1 .. map.height-1 {
+        for x in 1 .. map.width-1 {
+            let idx = map.xy_idx(x, y);
+            if !known.passable(idx) { continue; }
+            let unexplored = [(-1, 0), (1, 0), (0, -1), (0, 1)].iter().any(|(dx, dy)| {
+                let (nx, ny) = (x + dx, y + dy);
+                nx >= 1 && nx <= map.width-2 && ny >= 1 && ny <= map.height-2 && !map.revealed_tiles[map.xy_idx(nx, ny)]
+            });
+            if unexplored { frontier.push(idx as i32); }
+        }
+    }
+    if frontier.is_empty() { return None; }
+
+    let player_idx = map.xy_idx(player_pos.x, player_pos.y);
+    let dijkstra_map = rltk::DijkstraMap::new(map.width, map.height, &frontier, &known, 1000.0);
+    if dijkstra_map.map[player_idx] == std::f32::MAX || dijkstra_map.map[player_idx] < 1.0 { return None; }
+    rltk::DijkstraMap::find_lowest_exit(&dijkstra_map, player_idx as i32, &known).map(|idx| idx as usize)
+}
+
+/// Takes one step of the current travel, or ends it; returns None once there's nothing left to do
+pub fn continue_travel(travel : &mut Travel, ecs : &mut World) -> Option<RunState> {
+    //stop for anything new: someone hostile turning up, or a fight starting
+    if let Some(hostile) = visible_hostiles(ecs).into_iter().find(|e| !travel.hostiles_seen.contains(e)) {
+        let name = ecs.read_storage::<Name>().get(hostile).map_or("something".to_string(), |n| n.name.to_string());
+        ecs.fetch_mut::<GameLog>().add(LogCategory::System, format!("You see {} and stop.", name));
+        return None;
+    }
+    if ecs.fetch::<GameLog>().combat_messages != travel.combat_messages { return None; }
+
+    let next = match &travel.mode {
+        TravelMode::Path(steps) => steps.first().copied(),
+        TravelMode::Explore => {
+            let step = explore_step(ecs);
+            if step.is_none() {
+                ecs.fetch_mut::<GameLog>().add(LogCategory::System, "There's nowhere left to explore.");
+            }
+            step
+        }
+    };
+    let next = next?;
+
+    let (delta_x, delta_y) = {
+        let map = ecs.fetch::<Map>();
+        let player_pos = ecs.fetch::<Point>();
+        let (x, y) = map.idx_xy(next);
+        (x - player_pos.x, y - player_pos.y)
+    };
+    if i32::abs(delta_x) > 1 || i32::abs(delta_y) > 1 { return None; }
+
+    //walking into a closed door opens it, so we only move on once we're actually there
+    let result = try_move_player(delta_x, delta_y, ecs);
+    if result != RunState::Ticking { return None; }
+    let arrived = {
+        let map = ecs.fetch::<Map>();
+        let player_pos = ecs.fetch::<Point>();
+        map.xy_idx(player_pos.x, player_pos.y) == next
+    };
+    if arrived {
+        if let TravelMode::Path(steps) = &mut travel.mode {
+            steps.remove(0);
+        }
+    }
+    Some(result)
+}
@@ -1,7 +1,8 @@
 extern crate specs;
 use specs::prelude::*;
-use super::{EntityMoved, Position, EntryTrigger, Hidden, Map, Name, gamelog::GameLog, 
-    InflictsDamage, particle_system::ParticleBuilder, SufferDamage, SingleActivation};
+use super::{EntityMoved, Position, EntryTrigger, Map, Name, gamelog::{GameLog, LogCategory, LogEntry}, AreaOfEffect,
+    effects::{add_effect, aoe_tiles, EffectType, Targets}};
+use rltk::Point;
 
 pub struct TriggerSystem {}
 
@@ -11,21 +12,15 @@ impl<'a> System<'a> for TriggerSystem {
                         WriteStorage<'a, EntityMoved>,
                         ReadStorage<'a, Position>,
                         ReadStorage<'a, EntryTrigger>,
-                        WriteStorage<'a, Hidden>,
                         ReadStorage<'a, Name>,
                         Entities<'a>,
                         WriteExpect<'a, GameLog>,
-                        ReadStorage<'a, InflictsDamage>,
-                        WriteExpect<'a, ParticleBuilder>,
-                        WriteStorage<'a, SufferDamage>,
-                        ReadStorage<'a, SingleActivation>
+                        ReadStorage<'a, AreaOfEffect>
                     );
 
     fn run(&mut self, data : Self::SystemData) {
-        let (map, mut entity_moved, position, entry_trigger, mut hidden, names, entities, mut log,
-            inflicts_damage, mut particle_builder, mut inflict_damage, single_activation) = data;
+        let (map, mut entity_moved, position, entry_trigger, names, entities, mut log, aoe) = data;
 
-        let mut remove_entities : Vec<Entity> = Vec::new();
         // Iterate the entities that moved and their final position
         for (entity, mut _entity_moved, pos) in (&entities, &mut entity_moved, &position).join() {
             let idx = map.xy_idx(pos.x, pos.y);
@@ -35,38 +30,28 @@ impl<'a> System<'a> for TriggerSystem {
                     match maybe_trigger {
                         None => {},
                         Some(_trigger) => {
-                            // We triggered it                            
+                            // We triggered it
                             let name = names.get(*entity_id);
                             if let Some(name) = name {
-                                log.entries.push(format!("{} triggers!", &name.name));
+                                log.push(LogEntry::new(LogCategory::Combat).item_name(&name.name).append(" triggers!"));
                             }
 
-                            hidden.remove(*entity_id); // The trap is no longer hidden
-
-                            // If the trap is damage inflicting, do it
-                            let damage = inflicts_damage.get(*entity_id);
-                            if let Some(damage) = damage {
-                                particle_builder.request(pos.x, pos.y, rltk::RGB::named(rltk::ORANGE), rltk::RGB::named(rltk::BLACK), rltk::to_cp437('‼'), 200.0);
-                                inflict_damage.insert(entity, SufferDamage{ amount: damage.damage, from_player: false }).expect("Unable to do damage");
-                            }
-
-                            // If it is single activation, it needs to be removed
-                            let sa = single_activation.get(*entity_id);
-                            if let Some(_sa) = sa {
-                                remove_entities.push(*entity_id);
-                            }
+                            // Gas and the like hit everyone around, the rest only the victim
+                            let targets = match aoe.get(*entity_id) {
+                                None => Targets::Single{ target: entity },
+                                Some(area_effect) => {
+                                    let trap_pt = Point::new(pos.x, pos.y);
+                                    Targets::Area{ centre: idx as i32, tiles: aoe_tiles(&*map, trap_pt, trap_pt, area_effect) }
+                                }
+                            };
+                            add_effect(Some(*entity_id), EffectType::TriggerFire{ trigger: *entity_id }, targets);
                         }
                     }
                 }
             }
         }
 
-        // Remove any single activation traps
-        for trap in remove_entities.iter() {
-            entities.delete(*trap).expect("Unable to delete trap");
-        }
-
         // Remove all entity movement markers
         entity_moved.clear();
     }
-}
\ No newline at end of file
+}
@@ -1,7 +1,7 @@
 extern crate specs;
 use specs::prelude::*;
 use super::{Viewshed, Position, Map, Player, Name, BlocksVisibility,
-    Hidden, gamelog::GameLog};
+    Hidden, gamelog::{GameLog, LogCategory, LogEntry}};
 extern crate rltk;
 use rltk::{field_of_view, Point, RandomNumberGenerator};
 
@@ -62,7 +62,7 @@ impl<'a> System<'a> for VisibilitySystem {
                                     if rng.roll_dice(1,24)==1 { //1 in 6 was too good
                                         let name = names.get(*e);
                                         if let Some(name) = name {
-                                            log.entries.push(format!("You spotted a {}.", &name.name));
+                                            log.push(LogEntry::new(LogCategory::System).append("You spotted a ").item_name(&name.name).append("."));
                                         }
                                         hidden.remove(*e);
                                     }
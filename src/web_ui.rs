@@ -0,0 +1,140 @@
+use rltk::Rltk;
+use serde::{Serialize};
+use std::sync::Mutex;
+use wasm_bindgen::prelude::*;
+use super::input::{Command, InputContext, button_for};
+
+/// A button for the page to draw; pressing it sends the id back through press_web_button
+#[derive(Serialize, Clone, Debug)]
+pub struct WebButton {
+    pub id : String,
+    pub label : String
+}
+
+/// Everything the page needs to draw the buttons for the current screen
+#[derive(Serialize, Default, Clone, Debug)]
+pub struct WebUi {
+    pub title : String,
+    //moving and waiting, in keypad order, for the page to lay out three by three
+    pub keypad : Vec<WebButton>,
+    //everything else that makes sense right now: menus, closing, paging...
+    pub actions : Vec<WebButton>,
+    //the lettered rows of whatever menu is open
+    pub options : Vec<WebButton>
+}
+
+lazy_static! {
+    //what the page sees
+    static ref PUBLISHED : Mutex<WebUi> = Mutex::new(WebUi::default());
+    //what this frame's screen is putting together
+    static ref BUILDING : Mutex<WebUi> = Mutex::new(WebUi::default());
+    //pressed on the page, waiting for the next frame
+    static ref PRESSED : Mutex<Option<String>> = Mutex::new(None);
+}
+
+fn command_label(command : Command) -> &'static str {
+    match command {
+        Command::MoveN => "▲",
+        Command::MoveS => "▼",
+        Command::MoveE => "▶",
+        Command::MoveW => "◀",
+        Command::MoveNE => "◥",
+        Command::MoveNW => "◤",
+        Command::MoveSE => "◢",
+        Command::MoveSW => "◣",
+        Command::Wait => "⋯",
+        Command::PickUp => "Get",
+        Command::Inventory => "Inventory",
+        Command::Drop => "Drop",
+        Command::Remove => "Remove",
+        Command::Throw => "Throw",
+        Command::Disarm => "Disarm",
+        Command::Descend => "Descend",
+        Command::Explore => "Explore",
+        Command::Log => "Messages",
+        Command::Character => "Character",
+        Command::Examine => "Examine",
+        Command::Overview => "Map",
+        Command::ToggleVisualizer => "Visualizer",
+        Command::Up => "Up",
+        Command::Down => "Down",
+        Command::Left => "Left",
+        Command::Right => "Right",
+        Command::PageUp => "Page up",
+        Command::PageDown => "Page down",
+        Command::Home => "Home",
+        Command::End => "End",
+        Command::Confirm => "OK",
+        Command::Cancel => "Close",
+        Command::SwitchMode => "Switch",
+        Command::Cycle => "Next",
+        Command::TogglePause => "Pause",
+        Command::StepForward => "Step",
+        Command::StepBack => "Back",
+        Command::Rewind => "Rewind",
+        Command::ToggleRooms => "Rooms",
+        Command::ToggleCorridors => "Corridors",
+        Command::ToggleSubmaps => "Submaps",
+        Command::ToggleSpawns => "Spawns",
+        Command::Export => "Export",
+        Command::Skip => "Skip",
+        Command::Choose(_) | Command::Number(_) => "?"
+    }
+}
+
+/// Starts a fresh set of buttons, and hands over anything pressed on the page since last frame
+pub fn begin_frame(ctx : &mut Rltk) {
+    *BUILDING.lock().unwrap() = WebUi::default();
+    if let Some(id) = PRESSED.lock().unwrap().take() {
+        ctx.web_button = Some(id);
+    }
+}
+
+/// Shows this frame's buttons; frames that offered nothing (the turn ticking over) keep the last ones
+pub fn end_frame() {
+    let building = BUILDING.lock().unwrap();
+    if !building.title.is_empty() || !building.keypad.is_empty() || !building.actions.is_empty() || !building.options.is_empty() {
+        *PUBLISHED.lock().unwrap() = building.clone();
+    }
+}
+
+pub fn title(title : &str) {
+    BUILDING.lock().unwrap().title = title.to_string();
+}
+
+/// Offers a command as a button, if anything on the page is bound to it
+pub fn offer(context : InputContext, command : Command) {
+    offer_labelled(context, command, command_label(command));
+}
+
+pub fn offer_labelled(context : InputContext, command : Command, label : &str) {
+    if let Some(id) = button_for(context, command) {
+        let mut building = BUILDING.lock().unwrap();
+        if building.keypad.iter().chain(building.actions.iter()).any(|b| b.id == id) { return; }
+        let button = WebButton{ id, label : label.to_string() };
+        if command.direction().is_some() || command == Command::Wait {
+            building.keypad.push(button);
+        } else {
+            building.actions.push(button);
+        }
+    }
+}
+
+/// A menu row; the letter buttons come back as Command::Choose
+pub fn option(index : usize, label : &str) {
+    if index >= 26 { return; }
+    let id = ((b'a' + index as u8) as char).to_string();
+    BUILDING.lock().unwrap().options.push(WebButton{ id, label : label.to_string() });
+}
+
+/// The current buttons, as JSON, for the page to draw
+#[wasm_bindgen]
+pub fn web_ui_state() -> String {
+    serde_json::to_string(&*PUBLISHED.lock().unwrap()).unwrap_or_else(|_| "{}".to_string())
+}
+
+/// Called by the page when one of its buttons is pressed
+#[wasm_bindgen]
+pub fn press_web_button(id : String) {
+    *PRESSED.lock().unwrap() = Some(id);
+}